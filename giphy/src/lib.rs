@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use url::Url;
+
+use error::Error;
+
+use crate::models::{Gif, Rating, Response};
+
+pub mod error;
+pub mod models;
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    api_key: Arc<str>,
+    reqwest: reqwest::Client,
+    config: Config,
+}
+
+impl Client {
+    #[must_use]
+    pub fn new(api_key: impl Into<Arc<str>>) -> Client {
+        Self::with_config(api_key, Config::new())
+    }
+
+    #[must_use]
+    pub fn with_config(api_key: impl Into<Arc<str>>, config: Config) -> Client {
+        Client {
+            api_key: api_key.into(),
+            reqwest: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    fn build_query_string(&self, query: &str) -> Vec<(&'static str, String)> {
+        let mut params = Vec::with_capacity(4);
+        params.push(("api_key", self.api_key.to_string()));
+        params.push(("q", query.to_string()));
+        if let Some(rating) = self.config.rating {
+            params.push(("rating", Into::<&'static str>::into(rating).to_string()));
+        }
+        if let Some(limit) = self.config.limit {
+            params.push(("limit", limit.to_string()));
+        }
+        params
+    }
+
+    /// Search for GIFs with the given query.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when giphy cannot be reached or an error is returned from the api.
+    pub async fn search(&self, query: &str) -> Result<Vec<Gif>, Error> {
+        let query = self.build_query_string(query);
+
+        let url = Url::parse_with_params("https://api.giphy.com/v1/gifs/search", &query)?;
+        let result: Response<Vec<Gif>> = self.reqwest.get(url).send().await?.json().await?;
+        Ok(result.data)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    rating: Option<Rating>,
+    limit: Option<u8>,
+}
+
+impl Config {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            rating: None,
+            limit: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn rating(mut self, rating: Rating) -> Self {
+        self.rating = Some(rating);
+        self
+    }
+
+    #[must_use]
+    pub const fn limit(mut self, limit: u8) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
@@ -0,0 +1,122 @@
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
+use strum_macros::IntoStaticStr;
+use url::Url;
+
+#[derive(Deserialize, Debug)]
+pub struct Response<T> {
+    pub data: T,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Gif {
+    pub id: String,
+    pub title: String,
+    pub url: Url,
+    pub images: Images,
+}
+
+impl Debug for Gif {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gif")
+            .field("id", &self.id)
+            .field("title", &self.title)
+            .field("url", &self.url.as_str())
+            .field("images", &self.images)
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Images {
+    pub original: Image,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Image {
+    pub url: Url,
+}
+
+/// The highest rating of the returned GIFs, following the ratings of the Motion Picture
+/// Association (MPA).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, IntoStaticStr)]
+pub enum Rating {
+    #[strum(serialize = "g")]
+    G,
+    #[strum(serialize = "pg")]
+    Pg,
+    #[strum(serialize = "pg-13")]
+    Pg13,
+    #[strum(serialize = "r")]
+    R,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A search response of the giphy api, shortened to one GIF with two of its renditions.
+    const SEARCH_RESPONSE: &str = r#"{
+        "data": [
+            {
+                "type": "gif",
+                "id": "l0MYt5jPR6QX5pnqM",
+                "url": "https://giphy.com/gifs/funny-cat-l0MYt5jPR6QX5pnqM",
+                "slug": "funny-cat-l0MYt5jPR6QX5pnqM",
+                "title": "Cat GIF",
+                "rating": "g",
+                "import_datetime": "2016-03-30 16:52:03",
+                "images": {
+                    "original": {
+                        "height": "270",
+                        "width": "480",
+                        "size": "1048576",
+                        "url": "https://media.giphy.com/media/l0MYt5jPR6QX5pnqM/giphy.gif",
+                        "mp4": "https://media.giphy.com/media/l0MYt5jPR6QX5pnqM/giphy.mp4"
+                    },
+                    "fixed_height": {
+                        "height": "200",
+                        "width": "356",
+                        "url": "https://media.giphy.com/media/l0MYt5jPR6QX5pnqM/200.gif"
+                    }
+                }
+            }
+        ],
+        "pagination": {"total_count": 1, "count": 1, "offset": 0},
+        "meta": {"status": 200, "msg": "OK", "response_id": "a1b2c3"}
+    }"#;
+
+    #[test]
+    fn parses_search_response() {
+        let response: Response<Vec<Gif>> = serde_json::from_str(SEARCH_RESPONSE).unwrap();
+        let [gif] = response.data.as_slice() else {
+            panic!("expected one gif, got {:?}", response.data);
+        };
+        assert_eq!(gif.id, "l0MYt5jPR6QX5pnqM");
+        assert_eq!(gif.title, "Cat GIF");
+        assert_eq!(
+            gif.url.as_str(),
+            "https://giphy.com/gifs/funny-cat-l0MYt5jPR6QX5pnqM"
+        );
+        assert_eq!(
+            gif.images.original.url.as_str(),
+            "https://media.giphy.com/media/l0MYt5jPR6QX5pnqM/giphy.gif"
+        );
+    }
+
+    #[test]
+    fn parses_empty_search_response() {
+        let response: Response<Vec<Gif>> =
+            serde_json::from_str(r#"{"data": [], "meta": {"status": 200}}"#).unwrap();
+        assert!(response.data.is_empty());
+    }
+
+    #[test]
+    fn ratings_use_the_api_names() {
+        let names: Vec<&str> = [Rating::G, Rating::Pg, Rating::Pg13, Rating::R]
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        assert_eq!(names, ["g", "pg", "pg-13", "r"]);
+    }
+}
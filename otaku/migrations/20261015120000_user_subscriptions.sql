@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS anime_has_user_subscriptions
+(
+    user_id       TEXT         NOT NULL,
+    substring     VARCHAR(255) NOT NULL,
+    partition_key VARCHAR(8)   NOT NULL,
+    PRIMARY KEY (user_id, substring)
+);
+
+CREATE INDEX idx_user_subscription_partition ON anime_has_user_subscriptions (partition_key);
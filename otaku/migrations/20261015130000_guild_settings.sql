@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS guild_settings
+(
+    guild_id              TEXT    NOT NULL PRIMARY KEY,
+    content_filter        TEXT,
+    announcement_channel  TEXT,
+    currency_name         TEXT,
+    gifs_enabled          BOOLEAN NOT NULL DEFAULT TRUE,
+    announcements_enabled BOOLEAN NOT NULL DEFAULT TRUE
+);
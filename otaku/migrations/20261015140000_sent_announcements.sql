@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS sent_announcements
+(
+    title      TEXT        NOT NULL,
+    variant    TEXT        NOT NULL,
+    channel_id TEXT        NOT NULL,
+    sent_at    TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (title, variant, channel_id)
+);
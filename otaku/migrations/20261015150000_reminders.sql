@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS reminders
+(
+    id             BIGSERIAL   NOT NULL PRIMARY KEY,
+    user_id        TEXT        NOT NULL,
+    channel_id     TEXT,
+    message        TEXT        NOT NULL,
+    due_at         TIMESTAMPTZ NOT NULL,
+    repeat_seconds BIGINT CHECK (repeat_seconds > 0)
+);
+
+CREATE INDEX idx_reminders_due_at ON reminders (due_at);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS gif_usage
+(
+    id        BIGSERIAL   NOT NULL PRIMARY KEY,
+    guild_id  TEXT,
+    query     TEXT        NOT NULL,
+    url       TEXT        NOT NULL,
+    served_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_gif_usage_served_at ON gif_usage (served_at);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS guild_gif_overrides
+(
+    id       BIGSERIAL NOT NULL PRIMARY KEY,
+    guild_id TEXT      NOT NULL,
+    command  TEXT      NOT NULL,
+    kind     TEXT      NOT NULL CHECK (kind IN ('query', 'url')),
+    value    TEXT      NOT NULL,
+    UNIQUE (guild_id, command, kind, value)
+);
@@ -0,0 +1,15 @@
+ALTER TABLE anime_has_subscriptions
+    ADD COLUMN resolution        INTEGER,
+    ADD COLUMN announce_episodes BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN announce_batches  BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN announce_movies   BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN mute_start        TIME,
+    ADD COLUMN mute_end          TIME;
+
+ALTER TABLE anime_has_user_subscriptions
+    ADD COLUMN resolution        INTEGER,
+    ADD COLUMN announce_episodes BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN announce_batches  BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN announce_movies   BOOLEAN NOT NULL DEFAULT TRUE,
+    ADD COLUMN mute_start        TIME,
+    ADD COLUMN mute_end          TIME;
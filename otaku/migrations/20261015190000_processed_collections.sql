@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS processed_collections
+(
+    title        TEXT        NOT NULL,
+    variant      TEXT        NOT NULL,
+    updated_at   TIMESTAMPTZ NOT NULL,
+    processed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (title, variant, updated_at)
+);
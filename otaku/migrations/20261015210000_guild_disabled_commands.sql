@@ -0,0 +1,2 @@
+ALTER TABLE guild_settings
+    ADD COLUMN IF NOT EXISTS disabled_commands TEXT[] NOT NULL DEFAULT '{}';
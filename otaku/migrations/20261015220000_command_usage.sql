@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS command_usage
+(
+    id         BIGSERIAL   NOT NULL PRIMARY KEY,
+    command    TEXT        NOT NULL,
+    guild_id   TEXT,
+    user_hash  TEXT        NOT NULL,
+    latency_ms BIGINT      NOT NULL,
+    success    BOOLEAN     NOT NULL,
+    invoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_command_usage_invoked_at ON command_usage (invoked_at);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS guild_removals
+(
+    guild_id   TEXT        NOT NULL PRIMARY KEY,
+    removed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    ADD COLUMN IF NOT EXISTS manager_roles       TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS command_permissions TEXT[] NOT NULL DEFAULT '{}';
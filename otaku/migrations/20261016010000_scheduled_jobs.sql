@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS scheduled_jobs
+(
+    name        TEXT        NOT NULL PRIMARY KEY,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_run_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,2 @@
+ALTER TABLE guild_settings
+    ADD COLUMN IF NOT EXISTS moderation TEXT;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS guild_gif_blocklist
+(
+    id       BIGSERIAL NOT NULL PRIMARY KEY,
+    guild_id TEXT      NOT NULL,
+    kind     TEXT      NOT NULL CHECK (kind IN ('query', 'url')),
+    value    TEXT      NOT NULL,
+    UNIQUE (guild_id, kind, value)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE guild_settings
+    ADD COLUMN IF NOT EXISTS season_override TEXT,
+    ADD COLUMN IF NOT EXISTS novelty_ratio   INTEGER CHECK (novelty_ratio >= 0);
@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS user_timezones
+(
+    user_id            TEXT    NOT NULL PRIMARY KEY,
+    utc_offset_minutes INTEGER NOT NULL CHECK (utc_offset_minutes BETWEEN -1440 AND 1440)
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS guild_games
+(
+    id       BIGSERIAL NOT NULL PRIMARY KEY,
+    guild_id TEXT      NOT NULL,
+    name     TEXT      NOT NULL,
+    aliases  TEXT[]    NOT NULL DEFAULT '{}',
+    query    TEXT      NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS guild_games_name_idx ON guild_games (guild_id, LOWER(name));
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS play_sessions
+(
+    id         BIGSERIAL   NOT NULL PRIMARY KEY,
+    guild_id   TEXT,
+    channel_id TEXT        NOT NULL,
+    host_id    TEXT        NOT NULL,
+    game       TEXT,
+    starts_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_play_sessions_starts_at ON play_sessions (starts_at);
+
+CREATE TABLE IF NOT EXISTS play_session_rsvps
+(
+    session_id BIGINT  NOT NULL REFERENCES play_sessions (id) ON DELETE CASCADE,
+    user_id    TEXT    NOT NULL,
+    attending  BOOLEAN NOT NULL,
+    PRIMARY KEY (session_id, user_id)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS sleep_nights
+(
+    guild_id TEXT NOT NULL,
+    user_id  TEXT NOT NULL,
+    night    DATE NOT NULL,
+    PRIMARY KEY (guild_id, user_id, night)
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS hurry_escalations
+(
+    id         BIGSERIAL   NOT NULL PRIMARY KEY,
+    guild_id   TEXT,
+    channel_id TEXT        NOT NULL,
+    message_id TEXT        NOT NULL,
+    user_id    TEXT        NOT NULL,
+    query      TEXT        NOT NULL,
+    gif        TEXT        NOT NULL,
+    due_at     TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_hurry_escalations_due_at ON hurry_escalations (due_at);
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS polls
+(
+    id         BIGSERIAL   NOT NULL PRIMARY KEY,
+    channel_id TEXT        NOT NULL,
+    message_id TEXT,
+    question   TEXT        NOT NULL,
+    options    TEXT[]      NOT NULL,
+    locale     TEXT        NOT NULL,
+    closes_at  TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_polls_closes_at ON polls (closes_at);
+
+CREATE TABLE IF NOT EXISTS poll_votes
+(
+    poll_id      BIGINT  NOT NULL REFERENCES polls (id) ON DELETE CASCADE,
+    user_id      TEXT    NOT NULL,
+    option_index INTEGER NOT NULL,
+    PRIMARY KEY (poll_id, user_id)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE polls
+    ADD COLUMN IF NOT EXISTS guild_id TEXT;
@@ -0,0 +1,4 @@
+DELETE
+FROM guild_games
+WHERE guild_id = $1
+  AND LOWER(name) = LOWER($2)
\ No newline at end of file
@@ -0,0 +1,4 @@
+DELETE
+FROM guild_gif_blocklist
+WHERE id = $1
+  AND guild_id = $2
\ No newline at end of file
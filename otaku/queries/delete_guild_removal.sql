@@ -0,0 +1,3 @@
+DELETE
+FROM guild_removals
+WHERE guild_id = $1
\ No newline at end of file
@@ -0,0 +1,4 @@
+DELETE
+FROM anime_has_subscriptions s
+WHERE s.anime_id IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM anime a WHERE a.id = s.anime_id)
\ No newline at end of file
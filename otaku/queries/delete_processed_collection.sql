@@ -0,0 +1,5 @@
+DELETE
+FROM processed_collections
+WHERE title = $1
+  AND variant = $2
+  AND updated_at = $3
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE
+FROM processed_collections
+WHERE processed_at < $1
\ No newline at end of file
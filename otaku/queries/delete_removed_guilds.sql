@@ -0,0 +1,14 @@
+WITH removed AS (DELETE FROM guild_removals WHERE removed_at < $1 RETURNING guild_id),
+     settings AS (DELETE FROM guild_settings WHERE guild_id IN (SELECT guild_id FROM removed)),
+     overrides AS (DELETE FROM guild_gif_overrides WHERE guild_id IN (SELECT guild_id FROM removed)),
+     blocklist AS (DELETE FROM guild_gif_blocklist WHERE guild_id IN (SELECT guild_id FROM removed)),
+     games AS (DELETE FROM guild_games WHERE guild_id IN (SELECT guild_id FROM removed)),
+     nights AS (DELETE FROM sleep_nights WHERE guild_id IN (SELECT guild_id FROM removed)),
+     subscriptions AS (DELETE FROM anime_has_subscriptions WHERE guild_id IN (SELECT guild_id FROM removed)),
+     sessions AS (DELETE FROM play_sessions WHERE guild_id IN (SELECT guild_id FROM removed)),
+     escalations AS (DELETE FROM hurry_escalations WHERE guild_id IN (SELECT guild_id FROM removed)),
+     open_polls AS (DELETE FROM polls WHERE guild_id IN (SELECT guild_id FROM removed)),
+     commands AS (DELETE FROM command_usage WHERE guild_id IN (SELECT guild_id FROM removed)),
+     gifs AS (DELETE FROM gif_usage WHERE guild_id IN (SELECT guild_id FROM removed))
+SELECT COUNT(*) AS "guilds!"
+FROM removed
\ No newline at end of file
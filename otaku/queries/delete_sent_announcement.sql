@@ -0,0 +1,5 @@
+DELETE
+FROM sent_announcements
+WHERE title = $1
+  AND variant = $2
+  AND channel_id = $3
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE
+FROM sent_announcements
+WHERE sent_at < $1
\ No newline at end of file
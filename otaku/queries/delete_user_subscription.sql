@@ -0,0 +1,4 @@
+DELETE
+FROM anime_has_user_subscriptions
+WHERE user_id = $1
+  AND substring = $2
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE
+FROM user_timezones
+WHERE user_id = $1
\ No newline at end of file
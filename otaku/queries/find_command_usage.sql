@@ -0,0 +1,10 @@
+SELECT u.command,
+       COUNT(*)                                AS "invocations!",
+       COUNT(*) FILTER (WHERE NOT u.success)   AS "errors!",
+       COUNT(DISTINCT u.user_hash)             AS "users!",
+       AVG(u.latency_ms)::BIGINT               AS "average_latency_ms!",
+       MAX(u.invoked_at)                       AS "last_used_at!"
+FROM command_usage u
+WHERE u.invoked_at >= $1
+GROUP BY u.command
+ORDER BY COUNT(*) DESC
\ No newline at end of file
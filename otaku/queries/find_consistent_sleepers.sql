@@ -0,0 +1,7 @@
+SELECT user_id, COUNT(*) AS "nights!"
+FROM sleep_nights
+WHERE guild_id = $1
+  AND night >= $2
+GROUP BY user_id
+ORDER BY COUNT(*) DESC, MAX(night) DESC
+LIMIT $3
\ No newline at end of file
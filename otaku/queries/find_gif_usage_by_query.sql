@@ -0,0 +1,5 @@
+SELECT u.query, COUNT(*) AS "served!", MAX(u.served_at) AS "last_served_at!"
+FROM gif_usage u
+WHERE u.served_at >= $1
+GROUP BY u.query
+ORDER BY COUNT(*) DESC
\ No newline at end of file
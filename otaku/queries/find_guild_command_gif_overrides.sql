@@ -0,0 +1,5 @@
+SELECT o.id, o.command, o.kind, o.value
+FROM guild_gif_overrides o
+WHERE o.guild_id = $1
+  AND o.command = $2
+ORDER BY o.id
\ No newline at end of file
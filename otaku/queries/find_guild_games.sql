@@ -0,0 +1,4 @@
+SELECT g.id, g.name, g.aliases, g.query
+FROM guild_games g
+WHERE g.guild_id = $1
+ORDER BY g.name
\ No newline at end of file
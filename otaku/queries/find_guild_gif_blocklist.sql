@@ -0,0 +1,4 @@
+SELECT b.id, b.kind, b.value
+FROM guild_gif_blocklist b
+WHERE b.guild_id = $1
+ORDER BY b.id
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT o.id, o.command, o.kind, o.value
+FROM guild_gif_overrides o
+WHERE o.guild_id = $1
+ORDER BY o.command, o.id
\ No newline at end of file
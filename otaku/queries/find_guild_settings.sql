@@ -0,0 +1,15 @@
+SELECT g.content_filter,
+       g.announcement_channel,
+       g.currency_name,
+       g.gifs_enabled,
+       g.announcements_enabled,
+       g.locale,
+       g.disabled_commands,
+       g.manager_roles,
+       g.command_permissions,
+       g.moderation,
+       g.season_override,
+       g.novelty_ratio,
+       g.gif_delivery
+FROM guild_settings g
+WHERE g.guild_id = $1
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT u.url, COUNT(*) AS "served!"
+FROM gif_usage u
+WHERE u.served_at >= $1
+GROUP BY u.url
+ORDER BY COUNT(*) DESC
+LIMIT $2
\ No newline at end of file
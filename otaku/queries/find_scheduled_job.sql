@@ -0,0 +1,3 @@
+SELECT j.next_run_at
+FROM scheduled_jobs j
+WHERE j.name = $1
\ No newline at end of file
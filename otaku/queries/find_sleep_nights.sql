@@ -0,0 +1,5 @@
+SELECT night
+FROM sleep_nights
+WHERE guild_id = $1
+  AND user_id = $2
+ORDER BY night DESC
\ No newline at end of file
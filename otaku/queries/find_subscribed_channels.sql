@@ -1,4 +1,11 @@
-SELECT a.guild_id, a.channel_id
+SELECT a.guild_id,
+       a.channel_id,
+       a.resolution,
+       a.announce_episodes,
+       a.announce_batches,
+       a.announce_movies,
+       a.mute_start,
+       a.mute_end
 FROM anime_has_subscriptions a
 WHERE a.partition_key = SUBSTRING($1, 0, 8)
   AND $1 ILIKE a.substring
\ No newline at end of file
@@ -0,0 +1,10 @@
+SELECT a.user_id,
+       a.resolution,
+       a.announce_episodes,
+       a.announce_batches,
+       a.announce_movies,
+       a.mute_start,
+       a.mute_end
+FROM anime_has_user_subscriptions a
+WHERE a.partition_key = SUBSTRING($1, 0, 8)
+  AND $1 ILIKE a.substring
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT a.substring
+FROM anime_has_user_subscriptions a
+WHERE a.user_id = $1
+ORDER BY a.substring
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT t.utc_offset_minutes
+FROM user_timezones t
+WHERE t.user_id = $1
\ No newline at end of file
@@ -0,0 +1,2 @@
+INSERT INTO command_usage (command, guild_id, user_hash, latency_ms, success)
+VALUES ($1, $2, $3, $4, $5)
\ No newline at end of file
@@ -0,0 +1,2 @@
+INSERT INTO gif_usage (guild_id, query, url)
+VALUES ($1, $2, $3)
\ No newline at end of file
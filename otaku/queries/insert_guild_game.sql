@@ -0,0 +1,3 @@
+INSERT INTO guild_games (guild_id, name, aliases, query)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING
\ No newline at end of file
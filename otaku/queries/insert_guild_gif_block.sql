@@ -0,0 +1,3 @@
+INSERT INTO guild_gif_blocklist (guild_id, kind, value)
+VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING
\ No newline at end of file
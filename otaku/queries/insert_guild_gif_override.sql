@@ -0,0 +1,3 @@
+INSERT INTO guild_gif_overrides (guild_id, command, kind, value)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT DO NOTHING
\ No newline at end of file
@@ -0,0 +1,3 @@
+INSERT INTO guild_removals (guild_id)
+VALUES ($1)
+ON CONFLICT (guild_id) DO UPDATE SET removed_at = NOW()
\ No newline at end of file
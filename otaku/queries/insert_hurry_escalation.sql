@@ -0,0 +1,2 @@
+INSERT INTO hurry_escalations (guild_id, channel_id, message_id, user_id, query, gif, due_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
\ No newline at end of file
@@ -0,0 +1,8 @@
+WITH session AS (INSERT INTO play_sessions (guild_id, channel_id, host_id, game, starts_at)
+                 VALUES ($1, $2, $3, $4, $5)
+                 RETURNING id, host_id),
+     host AS (INSERT INTO play_session_rsvps (session_id, user_id, attending)
+              SELECT id, host_id, TRUE
+              FROM session)
+SELECT id AS "id!"
+FROM session
\ No newline at end of file
@@ -0,0 +1,3 @@
+INSERT INTO polls (channel_id, guild_id, question, options, locale, closes_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+RETURNING id
\ No newline at end of file
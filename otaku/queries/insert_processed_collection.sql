@@ -0,0 +1,3 @@
+INSERT INTO processed_collections (title, variant, updated_at)
+VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING
\ No newline at end of file
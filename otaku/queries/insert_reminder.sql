@@ -0,0 +1,3 @@
+INSERT INTO reminders (user_id, channel_id, message, due_at, repeat_seconds)
+VALUES ($1, $2, $3, $4, $5)
+RETURNING id
\ No newline at end of file
@@ -0,0 +1,3 @@
+INSERT INTO sent_announcements (title, variant, channel_id)
+VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING
\ No newline at end of file
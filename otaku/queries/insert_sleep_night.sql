@@ -0,0 +1,3 @@
+INSERT INTO sleep_nights (guild_id, user_id, night)
+VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING
\ No newline at end of file
@@ -0,0 +1,3 @@
+INSERT INTO anime_has_user_subscriptions (user_id, substring, partition_key)
+VALUES ($1, $2, SUBSTRING($2, 0, 8))
+ON CONFLICT DO NOTHING
\ No newline at end of file
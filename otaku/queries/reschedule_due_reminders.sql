@@ -0,0 +1,11 @@
+UPDATE reminders r
+SET due_at = r.due_at + make_interval(secs => r.repeat_seconds *
+                                             (FLOOR(EXTRACT(EPOCH FROM $1::timestamptz - r.due_at) /
+                                                    r.repeat_seconds) + 1))
+FROM (SELECT id, due_at
+      FROM reminders
+      WHERE due_at <= $1::timestamptz
+        AND repeat_seconds IS NOT NULL
+      FOR UPDATE) due
+WHERE r.id = due.id
+RETURNING r.id, r.user_id, r.channel_id, r.message, due.due_at, r.repeat_seconds
\ No newline at end of file
@@ -0,0 +1,4 @@
+DELETE
+FROM hurry_escalations
+WHERE due_at <= $1
+RETURNING id, guild_id, channel_id, message_id, user_id, query, gif, due_at
\ No newline at end of file
@@ -0,0 +1,5 @@
+DELETE
+FROM play_sessions s
+WHERE starts_at <= $1
+RETURNING id, guild_id, channel_id, host_id, game, starts_at,
+    ARRAY(SELECT user_id FROM play_session_rsvps r WHERE r.session_id = s.id AND r.attending) AS "attendees!"
\ No newline at end of file
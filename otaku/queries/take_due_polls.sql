@@ -0,0 +1,5 @@
+DELETE
+FROM polls p
+WHERE closes_at <= $1
+RETURNING id, channel_id, message_id, question, options, locale, closes_at,
+    ARRAY(SELECT option_index FROM poll_votes v WHERE v.poll_id = p.id) AS "votes!"
\ No newline at end of file
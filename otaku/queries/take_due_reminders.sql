@@ -0,0 +1,5 @@
+DELETE
+FROM reminders
+WHERE due_at <= $1
+  AND repeat_seconds IS NULL
+RETURNING id, user_id, channel_id, message, due_at, repeat_seconds
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE polls
+SET message_id = $2
+WHERE id = $1
\ No newline at end of file
@@ -0,0 +1,9 @@
+UPDATE anime_has_user_subscriptions
+SET resolution        = $3,
+    announce_episodes = $4,
+    announce_batches  = $5,
+    announce_movies   = $6,
+    mute_start        = $7,
+    mute_end          = $8
+WHERE user_id = $1
+  AND substring = $2
\ No newline at end of file
@@ -0,0 +1,17 @@
+INSERT INTO guild_settings (guild_id, content_filter, announcement_channel, currency_name, gifs_enabled,
+                            announcements_enabled, locale, disabled_commands, manager_roles, command_permissions,
+                            moderation, season_override, novelty_ratio, gif_delivery)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+ON CONFLICT (guild_id) DO UPDATE SET content_filter        = EXCLUDED.content_filter,
+                                     announcement_channel  = EXCLUDED.announcement_channel,
+                                     currency_name         = EXCLUDED.currency_name,
+                                     gifs_enabled          = EXCLUDED.gifs_enabled,
+                                     announcements_enabled = EXCLUDED.announcements_enabled,
+                                     locale                = EXCLUDED.locale,
+                                     disabled_commands     = EXCLUDED.disabled_commands,
+                                     manager_roles         = EXCLUDED.manager_roles,
+                                     command_permissions   = EXCLUDED.command_permissions,
+                                     moderation            = EXCLUDED.moderation,
+                                     season_override       = EXCLUDED.season_override,
+                                     novelty_ratio         = EXCLUDED.novelty_ratio,
+                                     gif_delivery          = EXCLUDED.gif_delivery
\ No newline at end of file
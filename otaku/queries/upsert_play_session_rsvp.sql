@@ -0,0 +1,5 @@
+INSERT INTO play_session_rsvps (session_id, user_id, attending)
+SELECT id, $2, $3
+FROM play_sessions
+WHERE id = $1
+ON CONFLICT (session_id, user_id) DO UPDATE SET attending = EXCLUDED.attending
\ No newline at end of file
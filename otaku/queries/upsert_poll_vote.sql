@@ -0,0 +1,5 @@
+INSERT INTO poll_votes (poll_id, user_id, option_index)
+SELECT id, $2, $3
+FROM polls
+WHERE id = $1
+ON CONFLICT (poll_id, user_id) DO UPDATE SET option_index = EXCLUDED.option_index
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO scheduled_jobs (name, next_run_at)
+VALUES ($1, $2)
+ON CONFLICT (name) DO UPDATE SET next_run_at = EXCLUDED.next_run_at,
+                                 last_run_at = NOW()
\ No newline at end of file
@@ -0,0 +1,3 @@
+INSERT INTO user_timezones (user_id, utc_offset_minutes)
+VALUES ($1, $2)
+ON CONFLICT (user_id) DO UPDATE SET utc_offset_minutes = EXCLUDED.utc_offset_minutes
\ No newline at end of file
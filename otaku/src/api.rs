@@ -0,0 +1,233 @@
+use std::path::Path;
+use std::time::Duration;
+use std::{env, fs, io};
+
+use tonic::metadata::errors::InvalidMetadataValue;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::Interceptor;
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Identity};
+use tonic::{Request, Status};
+
+use crate::config::{parse_env, ConfigError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum TlsConfigError {
+    #[error("Failed to read {1}: {0}")]
+    Io(#[source] io::Error, String),
+    #[error("{0} requires {1} to be set")]
+    Incomplete(&'static str, &'static str),
+}
+
+/// TLS settings for the connection to the anime api.
+///
+/// TLS is used for `https` urls, the native root certificates are always trusted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM encoded certificate of an additional trusted certificate authority.
+    pub ca_cert: Option<Vec<u8>>,
+    /// Verify the server certificate against this domain instead of the host of the url.
+    pub domain: Option<String>,
+    /// PEM encoded client certificate and key for mutual TLS.
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl TlsConfig {
+    /// Read the TLS config from `ANIME_TLS_CA_CERT`, `ANIME_TLS_DOMAIN`, `ANIME_TLS_CLIENT_CERT`
+    /// and `ANIME_TLS_CLIENT_KEY`. The certificate and key keys contain file paths.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when a file cannot be read or only one of the client certificate and
+    /// key is set.
+    pub fn from_env() -> Result<Self, TlsConfigError> {
+        let ca_cert = read_env_file("ANIME_TLS_CA_CERT")?;
+        let domain = env::var("ANIME_TLS_DOMAIN").ok();
+        let cert = read_env_file("ANIME_TLS_CLIENT_CERT")?;
+        let key = read_env_file("ANIME_TLS_CLIENT_KEY")?;
+        let identity = match (cert, key) {
+            (Some(cert), Some(key)) => Some((cert, key)),
+            (Some(_), None) => {
+                return Err(TlsConfigError::Incomplete(
+                    "ANIME_TLS_CLIENT_CERT",
+                    "ANIME_TLS_CLIENT_KEY",
+                ))
+            }
+            (None, Some(_)) => {
+                return Err(TlsConfigError::Incomplete(
+                    "ANIME_TLS_CLIENT_KEY",
+                    "ANIME_TLS_CLIENT_CERT",
+                ))
+            }
+            (None, None) => None,
+        };
+        Ok(Self {
+            ca_cert,
+            domain,
+            identity,
+        })
+    }
+
+    fn client_config(&self) -> ClientTlsConfig {
+        let mut config = ClientTlsConfig::new().with_native_roots();
+        if let Some(ca_cert) = &self.ca_cert {
+            config = config.ca_certificate(Certificate::from_pem(ca_cert));
+        }
+        if let Some(domain) = &self.domain {
+            config = config.domain_name(domain);
+        }
+        if let Some((cert, key)) = &self.identity {
+            config = config.identity(Identity::from_pem(cert, key));
+        }
+        config
+    }
+}
+
+fn read_env_file(key: &'static str) -> Result<Option<Vec<u8>>, TlsConfigError> {
+    let Ok(path) = env::var(key) else {
+        return Ok(None);
+    };
+    fs::read(Path::new(&path))
+        .map(Some)
+        .map_err(|err| TlsConfigError::Io(err, path))
+}
+
+/// Timeouts of the connection to the anime api.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ChannelConfig {
+    /// Interval of the HTTP/2 keepalive pings, also sent while the stream is idle.
+    pub keepalive_interval: Duration,
+    /// Close the connection when a keepalive ping is not answered within this time.
+    pub keepalive_timeout: Duration,
+    pub connect_timeout: Duration,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_interval: Duration::from_secs(30),
+            keepalive_timeout: Duration::from_secs(10),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ChannelConfig {
+    /// Read the channel config from `ANIME_KEEPALIVE_INTERVAL`, `ANIME_KEEPALIVE_TIMEOUT` and
+    /// `ANIME_CONNECT_TIMEOUT`, using the defaults for missing keys. Durations are in seconds.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when a key is set but is not a valid number.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let default = Self::default();
+        Ok(Self {
+            keepalive_interval: parse_env("ANIME_KEEPALIVE_INTERVAL")?
+                .map_or(default.keepalive_interval, Duration::from_secs),
+            keepalive_timeout: parse_env("ANIME_KEEPALIVE_TIMEOUT")?
+                .map_or(default.keepalive_timeout, Duration::from_secs),
+            connect_timeout: parse_env("ANIME_CONNECT_TIMEOUT")?
+                .map_or(default.connect_timeout, Duration::from_secs),
+        })
+    }
+}
+
+/// Location of the anime api.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Api {
+    url: &'static str,
+    tls: TlsConfig,
+    token: Option<MetadataValue<Ascii>>,
+    channel: ChannelConfig,
+}
+
+impl Api {
+    #[must_use]
+    pub fn new(url: &'static str) -> Self {
+        Self {
+            url,
+            tls: TlsConfig::default(),
+            token: None,
+            channel: ChannelConfig::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    #[must_use]
+    pub fn with_channel_config(mut self, channel: ChannelConfig) -> Self {
+        self.channel = channel;
+        self
+    }
+
+    /// Send `token` as a bearer token with every request.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the token contains characters that are not allowed in a header.
+    pub fn with_token(mut self, token: &str) -> Result<Self, InvalidMetadataValue> {
+        let mut token: MetadataValue<Ascii> = format!("Bearer {token}").parse()?;
+        token.set_sensitive(true);
+        self.token = Some(token);
+        Ok(self)
+    }
+
+    #[must_use]
+    pub fn url(&self) -> &'static str {
+        self.url
+    }
+
+    pub(crate) fn endpoint(&self) -> Result<Endpoint, tonic::transport::Error> {
+        let endpoint = Endpoint::from_shared(self.url)?
+            .connect_timeout(self.channel.connect_timeout)
+            .http2_keep_alive_interval(self.channel.keepalive_interval)
+            .keep_alive_timeout(self.channel.keepalive_timeout)
+            .keep_alive_while_idle(true);
+        if self.url.starts_with("https://") {
+            endpoint.tls_config(self.tls.client_config())
+        } else {
+            Ok(endpoint)
+        }
+    }
+
+    pub(crate) fn interceptor(&self) -> AuthInterceptor {
+        AuthInterceptor {
+            token: self.token.clone(),
+        }
+    }
+}
+
+/// Adds the authorization header to requests when a token is configured.
+#[derive(Debug, Clone)]
+pub(crate) struct AuthInterceptor {
+    token: Option<MetadataValue<Ascii>>,
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(token) = &self.token {
+            request
+                .metadata_mut()
+                .insert("authorization", token.clone());
+        }
+        Ok(request)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn endpoint_rejects_malformed_url() {
+        assert!(Api::new("http://[::1").endpoint().is_err());
+    }
+
+    #[test]
+    fn endpoint_accepts_url() {
+        assert!(Api::new("http://localhost:8080").endpoint().is_ok());
+    }
+}
@@ -0,0 +1,162 @@
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::{Download, DownloadCollection, DownloadVariant};
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("Missing required field: {0}")]
+    MissingField(&'static str),
+    #[error("Title must not be empty")]
+    EmptyTitle,
+    #[error("Batch starts at episode {0} after it ends at episode {1}")]
+    InvalidBatch(u32, u32),
+}
+
+/// Builds a [`DownloadCollection`] that upholds its invariants.
+///
+/// The title may not be empty, batches must not end before they start and the downloads are sorted
+/// by resolution.
+#[derive(Debug, Clone, Default)]
+#[must_use]
+pub struct DownloadCollectionBuilder {
+    title: String,
+    variant: Option<DownloadVariant>,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    downloads: Vec<Download>,
+}
+
+impl DownloadCollectionBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn variant(mut self, variant: DownloadVariant) -> Self {
+        self.variant = Some(variant);
+        self
+    }
+
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.created_at = Some(created_at);
+        self
+    }
+
+    /// Defaults to the creation time when not set.
+    pub fn updated_at(mut self, updated_at: DateTime<Utc>) -> Self {
+        self.updated_at = Some(updated_at);
+        self
+    }
+
+    pub fn download(mut self, download: Download) -> Self {
+        self.downloads.push(download);
+        self
+    }
+
+    pub fn downloads(mut self, downloads: impl IntoIterator<Item = Download>) -> Self {
+        self.downloads.extend(downloads);
+        self
+    }
+
+    /// Validate the collection.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the variant or creation time is missing, the title is empty or the
+    /// batch range is invalid.
+    pub fn build(self) -> Result<DownloadCollection, ValidationError> {
+        if self.title.trim().is_empty() {
+            return Err(ValidationError::EmptyTitle);
+        }
+        let variant = self
+            .variant
+            .ok_or(ValidationError::MissingField("variant"))?;
+        if let DownloadVariant::Batch(range) = &variant {
+            if range.start() > range.end() {
+                return Err(ValidationError::InvalidBatch(*range.start(), *range.end()));
+            }
+        }
+        let created_at = self
+            .created_at
+            .ok_or(ValidationError::MissingField("created_at"))?;
+        let mut downloads = self.downloads;
+        downloads.sort_by_key(|download| download.resolution);
+
+        Ok(DownloadCollection {
+            title: self.title,
+            variant,
+            created_at,
+            updated_at: self.updated_at.unwrap_or(created_at),
+            downloads,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn created_at() -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn download(resolution: u16) -> Download {
+        Download {
+            published_date: created_at(),
+            resolution,
+            comments: String::new(),
+            torrent: String::new(),
+            file_name: format!("Frieren - 01 ({resolution}p).mkv"),
+        }
+    }
+
+    #[test]
+    fn empty_title_is_rejected() {
+        let result = DownloadCollectionBuilder::new("  ")
+            .variant(DownloadVariant::Movie)
+            .created_at(created_at())
+            .build();
+        assert_eq!(result, Err(ValidationError::EmptyTitle));
+    }
+
+    #[test]
+    fn missing_fields_are_rejected() {
+        let result = DownloadCollectionBuilder::new("Frieren")
+            .created_at(created_at())
+            .build();
+        assert_eq!(result, Err(ValidationError::MissingField("variant")));
+        let result = DownloadCollectionBuilder::new("Frieren")
+            .variant(DownloadVariant::Movie)
+            .build();
+        assert_eq!(result, Err(ValidationError::MissingField("created_at")));
+    }
+
+    #[test]
+    fn reversed_batch_is_rejected() {
+        let result = DownloadCollectionBuilder::new("Frieren")
+            .variant(DownloadVariant::Batch(12..=1))
+            .created_at(created_at())
+            .build();
+        assert_eq!(result, Err(ValidationError::InvalidBatch(12, 1)));
+    }
+
+    #[test]
+    fn valid_collection_is_built() {
+        let collection = DownloadCollectionBuilder::new("Frieren")
+            .variant(DownloadVariant::Batch(1..=1))
+            .created_at(created_at())
+            .downloads([download(1080), download(480)])
+            .download(download(720))
+            .build()
+            .unwrap();
+        assert_eq!(collection.updated_at, collection.created_at);
+        let resolutions: Vec<_> = collection
+            .downloads
+            .iter()
+            .map(|download| download.resolution)
+            .collect();
+        assert_eq!(resolutions, vec![480, 720, 1080]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::cmp::min;
+use std::env;
+use std::num::ParseIntError;
+use std::time::Duration;
+
+use rand::Rng;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("Invalid resolution for {1}: {0}")]
+    InvalidResolution(String, &'static str),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+}
+
+/// Delays between connection attempts.
+///
+/// Every delay is randomly shortened or lengthened by up to `jitter_percent` percent, so multiple
+/// instances do not reconnect in lockstep.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BackoffConfig {
+    /// Delay after the first failed attempt, doubled after every next failure.
+    pub initial: Duration,
+    pub max: Duration,
+    /// Delay before reconnecting after an open stream was closed.
+    pub reconnect: Duration,
+    pub jitter_percent: u8,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(125),
+            max: Duration::from_secs(30),
+            reconnect: Duration::from_secs(5),
+            jitter_percent: 20,
+        }
+    }
+}
+
+impl BackoffConfig {
+    /// Read the backoff config from `ANIME_BACKOFF_INITIAL_MS`, `ANIME_BACKOFF_MAX_MS`,
+    /// `ANIME_RECONNECT_INTERVAL_MS` and `ANIME_BACKOFF_JITTER_PERCENT`, using the defaults for
+    /// missing keys.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when a key is set but is not a valid number.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let default = Self::default();
+        Ok(Self {
+            initial: parse_env("ANIME_BACKOFF_INITIAL_MS")?
+                .map_or(default.initial, Duration::from_millis),
+            max: parse_env("ANIME_BACKOFF_MAX_MS")?.map_or(default.max, Duration::from_millis),
+            reconnect: parse_env("ANIME_RECONNECT_INTERVAL_MS")?
+                .map_or(default.reconnect, Duration::from_millis),
+            jitter_percent: parse_env("ANIME_BACKOFF_JITTER_PERCENT")?
+                .unwrap_or(default.jitter_percent)
+                .min(100),
+        })
+    }
+
+    /// The delay after `current`, without jitter.
+    #[must_use]
+    pub fn next(self, current: Duration) -> Duration {
+        min(current * 2, self.max)
+    }
+
+    /// Randomly shorten or lengthen `delay` by up to the jitter percentage.
+    #[must_use]
+    pub fn jitter(self, delay: Duration) -> Duration {
+        let jitter = f64::from(self.jitter_percent) / 100.0;
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        delay.mul_f64(factor)
+    }
+}
+
+pub(crate) fn parse_env<T>(key: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T: std::str::FromStr<Err = ParseIntError>,
+{
+    env::var(key)
+        .ok()
+        .map(|value| value.parse())
+        .transpose()
+        .map_err(|err| ConfigError::ParseInt(err, key))
+}
+
+/// Settings for processing the collections received from the anime api.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// Skip collections until a download in this resolution is available, accept any collection
+    /// when `None`.
+    pub required_resolution: Option<u16>,
+    pub backoff: BackoffConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            required_resolution: Some(1080),
+            backoff: BackoffConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Read the config from `ANIME_REQUIRED_RESOLUTION` and the keys of
+    /// [`BackoffConfig::from_env`], using the defaults for missing keys. The resolution is a number
+    /// like `1080` or `any`.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when a key is set but has an invalid value.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let default = Self::default();
+        let required_resolution =
+            match env::var("ANIME_REQUIRED_RESOLUTION") {
+                Ok(value) if value.eq_ignore_ascii_case("any") => None,
+                Ok(value) => Some(value.parse().map_err(|_| {
+                    ConfigError::InvalidResolution(value, "ANIME_REQUIRED_RESOLUTION")
+                })?),
+                Err(_) => default.required_resolution,
+            };
+        Ok(Self {
+            required_resolution,
+            backoff: BackoffConfig::from_env()?,
+        })
+    }
+
+    pub(crate) fn is_complete(self, collection: &proto::api::v2::DownloadCollection) -> bool {
+        self.required_resolution.is_none_or(|resolution| {
+            collection
+                .downloads
+                .iter()
+                .any(|download| download.resolution == u32::from(resolution))
+        })
+    }
+}
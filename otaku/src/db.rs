@@ -1,15 +1,121 @@
+// The expansion of `sqlx::query_file!` contains a panic, so clippy asks for a `# Panics` section
+// on every query function, even though the query is checked at compile time.
+#![allow(clippy::missing_panics_doc)]
+
+use std::env;
+use std::num::{NonZeroU64, ParseIntError};
 use std::ops::Deref;
+use std::time::{Duration, Instant};
 
-use sqlx::migrate::{Migrate, MigrateError};
+use chrono::{DateTime, TimeDelta, Utc};
+use futures_util::TryFutureExt;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::sqlx_macros::migrate;
-use sqlx::{Acquire, Postgres};
+use sqlx::{Acquire, Connection, Postgres};
+use tracing::warn;
+
+use crate::db::metrics::One;
+use crate::{BackoffConfig, GuildId, SubscriptionPreferences, UserId};
+
+pub use guild_settings::{GuildSettings, GuildSettingsConnection};
+pub use memory::InMemoryDatabase;
+pub use metrics::{query_stats, QueryStats};
+pub use sqlx::Error;
+
+pub mod command_usage;
+pub mod gif_blocklist;
+pub mod gif_overrides;
+pub mod gif_usage;
+pub mod guild_games;
+pub mod guild_settings;
+pub mod hurry_escalations;
+mod memory;
+pub(crate) mod metrics;
+pub mod play_sessions;
+pub mod polls;
+pub mod reminders;
+pub mod scheduled_jobs;
+pub mod sleep_nights;
+pub mod user_timezones;
 
 pub type Pool = sqlx::Pool<Postgres>;
 
-fn opts(name: &str) -> (PgConnectOptions, PgPoolOptions) {
-    let connect_opts = PgConnectOptions::new().application_name(name);
-    let pool_opts = PgPoolOptions::new().max_connections(2);
+static MIGRATOR: Migrator = migrate!("./migrations");
+
+#[derive(thiserror::Error, Debug)]
+pub enum ConfigError {
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+}
+
+/// Sizing and timeouts of the connection pool.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub acquire_timeout: Duration,
+    /// Aborts any statement that takes longer than this, disabled when `None`.
+    pub statement_timeout: Option<Duration>,
+    /// Number of times [`connect_with_backoff`] tries to connect before giving up.
+    pub connect_attempts: u32,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 2,
+            acquire_timeout: Duration::from_secs(30),
+            statement_timeout: None,
+            connect_attempts: 10,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Read the pool config from `DATABASE_MAX_CONNECTIONS`, `DATABASE_ACQUIRE_TIMEOUT`,
+    /// `DATABASE_STATEMENT_TIMEOUT` and `DATABASE_CONNECT_ATTEMPTS`, using the defaults for missing
+    /// keys. Timeouts are in seconds.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when a key is set but is not a valid number.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        let default = Self::default();
+        let max_connections =
+            parse_env("DATABASE_MAX_CONNECTIONS")?.unwrap_or(default.max_connections);
+        let acquire_timeout = parse_env("DATABASE_ACQUIRE_TIMEOUT")?
+            .map_or(default.acquire_timeout, Duration::from_secs);
+        let statement_timeout = parse_env("DATABASE_STATEMENT_TIMEOUT")?.map(Duration::from_secs);
+        let connect_attempts =
+            parse_env("DATABASE_CONNECT_ATTEMPTS")?.unwrap_or(default.connect_attempts);
+        Ok(Self {
+            max_connections,
+            acquire_timeout,
+            statement_timeout,
+            connect_attempts,
+        })
+    }
+}
+
+fn parse_env<T>(key: &'static str) -> Result<Option<T>, ConfigError>
+where
+    T: std::str::FromStr<Err = ParseIntError>,
+{
+    env::var(key)
+        .ok()
+        .map(|value| value.parse())
+        .transpose()
+        .map_err(|err| ConfigError::ParseInt(err, key))
+}
+
+fn opts(name: &str, config: PoolConfig) -> (PgConnectOptions, PgPoolOptions) {
+    let mut connect_opts = PgConnectOptions::new().application_name(name);
+    if let Some(timeout) = config.statement_timeout {
+        connect_opts = connect_opts.options([("statement_timeout", timeout.as_millis())]);
+    }
+    let pool_opts = PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .acquire_timeout(config.acquire_timeout);
     (connect_opts, pool_opts)
 }
 
@@ -18,12 +124,41 @@ fn opts(name: &str) -> (PgConnectOptions, PgPoolOptions) {
 /// # Errors
 ///
 /// Will return an error when a connection cannot be established using the current config.
-pub async fn connect(name: &str) -> Result<sqlx::Pool<Postgres>, sqlx::Error> {
-    let (connect_opts, pool_opts) = opts(name);
+pub async fn connect(name: &str, config: PoolConfig) -> Result<sqlx::Pool<Postgres>, sqlx::Error> {
+    let (connect_opts, pool_opts) = opts(name, config);
     let pool = pool_opts.connect_with(connect_opts).await?;
     Ok(pool)
 }
 
+/// Connect to the database, retrying with an exponential backoff while the database is not
+/// available yet.
+///
+/// # Errors
+///
+/// Will return the last error when no connection could be established within
+/// [`PoolConfig::connect_attempts`] attempts.
+pub async fn connect_with_backoff(name: &str, config: PoolConfig) -> Result<Pool, sqlx::Error> {
+    let backoff_config = BackoffConfig::default();
+    let mut backoff = backoff_config.initial;
+    let mut attempt = 1;
+    loop {
+        match connect(name, config).await {
+            Ok(pool) => return Ok(pool),
+            Err(err) if attempt < config.connect_attempts => {
+                let delay = backoff_config.jitter(backoff);
+                warn!(
+                    "Failed to connect to the database with error: {err}. Retrying in {:.2} seconds",
+                    delay.as_secs_f32()
+                );
+                tokio::time::sleep(delay).await;
+                backoff = backoff_config.next(backoff);
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
 /// Migrate the database located in the migrations directory.
 ///
 /// # Errors
@@ -34,5 +169,307 @@ where
     A: Acquire<'a>,
     <A::Connection as Deref>::Target: Migrate,
 {
-    migrate!("./migrations").run(migrator).await
+    MIGRATOR.run(migrator).await
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Health {
+    /// Open connections in the pool, including idle connections.
+    pub connections: u32,
+    pub idle_connections: usize,
+    pub pending_migrations: usize,
+    /// Round trip time of a ping to the database.
+    pub ping: Duration,
+}
+
+/// Check the health of the database and the connection pool.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or the applied migrations cannot be
+/// read.
+pub async fn health(pool: &Pool) -> Result<Health, MigrateError> {
+    let mut connection = pool.acquire().await?;
+    let start = Instant::now();
+    connection.ping().await?;
+    let ping = start.elapsed();
+
+    let applied = connection.list_applied_migrations().await?;
+    let pending_migrations = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied.iter().any(|a| a.version == migration.version))
+        .count();
+
+    Ok(Health {
+        connections: pool.size(),
+        idle_connections: pool.num_idle(),
+        pending_migrations,
+        ping,
+    })
+}
+
+/// Subscribe a user to direct message notifications for titles matching `substring`.
+///
+/// Returns `true` when the subscription did not exist yet.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_user_subscription(
+    pool: &Pool,
+    user_id: UserId,
+    substring: &str,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_user_subscription.sql",
+        user_id.to_string(),
+        substring
+    );
+    let result = metrics::instrument("insert_user_subscription", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove a direct message subscription for `substring` from a user.
+///
+/// Returns `true` when a subscription was removed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn remove_user_subscription(
+    pool: &Pool,
+    user_id: UserId,
+    substring: &str,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/delete_user_subscription.sql",
+        user_id.to_string(),
+        substring
+    );
+    let result = metrics::instrument("delete_user_subscription", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// List the substrings a user is subscribed to.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_user_subscriptions(
+    pool: &Pool,
+    user_id: UserId,
+) -> Result<Vec<String>, sqlx::Error> {
+    let query = sqlx::query_file!("queries/find_user_subscriptions.sql", user_id.to_string());
+    let records = metrics::instrument("find_user_subscriptions", query.fetch_all(pool)).await?;
+    Ok(records.into_iter().map(|record| record.substring).collect())
+}
+
+/// Replace the notification preferences of a user's subscription to `substring`.
+///
+/// Returns `true` when the subscription exists.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn update_user_subscription_preferences(
+    pool: &Pool,
+    user_id: UserId,
+    substring: &str,
+    preferences: &SubscriptionPreferences,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/update_user_subscription_preferences.sql",
+        user_id.to_string(),
+        substring,
+        preferences.resolution.map(i32::from),
+        preferences.variants.episodes,
+        preferences.variants.batches,
+        preferences.variants.movies,
+        preferences.mute.map(|mute| mute.start),
+        preferences.mute.map(|mute| mute.end),
+    );
+    let result =
+        metrics::instrument("update_user_subscription_preferences", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record that an announcement for `title` and `variant` was sent to a channel.
+///
+/// For direct messages the id of the user is used as the channel.
+/// Returns `false` when the announcement was already recorded, so it should not be sent again.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn insert_sent_announcement(
+    pool: &Pool,
+    title: &str,
+    variant: &str,
+    channel_id: NonZeroU64,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_sent_announcement.sql",
+        title,
+        variant,
+        channel_id.to_string()
+    );
+    let result = metrics::instrument("insert_sent_announcement", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove the record of an announcement for `title` and `variant` to a channel that could not be
+/// delivered, so it can be sent again.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn delete_sent_announcement(
+    pool: &Pool,
+    title: &str,
+    variant: &str,
+    channel_id: NonZeroU64,
+) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/delete_sent_announcement.sql",
+        title,
+        variant,
+        channel_id.to_string()
+    );
+    metrics::instrument("delete_sent_announcement", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// Record that the collection `title` `variant` was processed in the version of `updated_at`.
+///
+/// Returns `false` when this version was already processed, for example before a reconnect.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn insert_processed_collection(
+    pool: &Pool,
+    title: &str,
+    variant: &str,
+    updated_at: DateTime<Utc>,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_processed_collection.sql",
+        title,
+        variant,
+        updated_at
+    );
+    let result = metrics::instrument("insert_processed_collection", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove the record that the collection `title` `variant` was processed in the version of
+/// `updated_at`, so it is processed again when it is received again.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn delete_processed_collection(
+    pool: &Pool,
+    title: &str,
+    variant: &str,
+    updated_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/delete_processed_collection.sql",
+        title,
+        variant,
+        updated_at
+    );
+    metrics::instrument("delete_processed_collection", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// How long data is kept before [`cleanup`] removes it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Sent announcements older than this are removed.
+    pub sent_announcements: Duration,
+    /// Processed collections older than this are removed.
+    pub processed_collections: Duration,
+    /// Remove subscriptions that reference an anime that no longer exists.
+    pub orphaned_subscriptions: bool,
+    /// The data of guilds that the bot left longer ago than this is removed.
+    pub removed_guilds: Duration,
+}
+
+/// The number of rows removed by [`cleanup`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct CleanupReport {
+    pub sent_announcements: u64,
+    pub processed_collections: u64,
+    pub subscriptions: u64,
+    pub guilds: u64,
+}
+
+/// Remove data that is no longer needed according to the retention `policy`.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn cleanup(pool: &Pool, policy: RetentionPolicy) -> Result<CleanupReport, sqlx::Error> {
+    let mut report = CleanupReport::default();
+    if let Some(cutoff) = retention_cutoff(policy.sent_announcements) {
+        let query = sqlx::query_file!("queries/delete_sent_announcements.sql", cutoff);
+        report.sent_announcements =
+            metrics::instrument("delete_sent_announcements", query.execute(pool))
+                .await?
+                .rows_affected();
+    }
+    if let Some(cutoff) = retention_cutoff(policy.processed_collections) {
+        let query = sqlx::query_file!("queries/delete_processed_collections.sql", cutoff);
+        report.processed_collections =
+            metrics::instrument("delete_processed_collections", query.execute(pool))
+                .await?
+                .rows_affected();
+    }
+    if policy.orphaned_subscriptions {
+        let query = sqlx::query_file!("queries/delete_orphaned_subscriptions.sql");
+        report.subscriptions =
+            metrics::instrument("delete_orphaned_subscriptions", query.execute(pool))
+                .await?
+                .rows_affected();
+    }
+    if let Some(cutoff) = retention_cutoff(policy.removed_guilds) {
+        let query = sqlx::query_file!("queries/delete_removed_guilds.sql", cutoff);
+        let removed =
+            metrics::instrument("delete_removed_guilds", query.fetch_one(pool).map_ok(One)).await?;
+        report.guilds = u64::try_from(removed.0.guilds).unwrap_or_default();
+    }
+    Ok(report)
+}
+
+/// Remove the data of `guild_id` in a future [`cleanup`], after the bot was removed from it.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn schedule_guild_cleanup(pool: &Pool, guild_id: GuildId) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!("queries/insert_guild_removal.sql", guild_id.to_string());
+    metrics::instrument("insert_guild_removal", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// Keep the data of `guild_id` after the bot joined it again.
+///
+/// Returns `false` when no cleanup was scheduled for the guild.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn cancel_guild_cleanup(pool: &Pool, guild_id: GuildId) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!("queries/delete_guild_removal.sql", guild_id.to_string());
+    let result = metrics::instrument("delete_guild_removal", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+fn retention_cutoff(age: Duration) -> Option<DateTime<Utc>> {
+    TimeDelta::from_std(age)
+        .ok()
+        .and_then(|age| Utc::now().checked_sub_signed(age))
 }
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::db::{metrics, Pool};
+use crate::{GuildId, UserId};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandUsage {
+    pub command: String,
+    pub invocations: i64,
+    /// Invocations that ended with an error.
+    pub errors: i64,
+    /// Number of different users that invoked the command.
+    pub users: i64,
+    pub average_latency_ms: i64,
+    pub last_used_at: DateTime<Utc>,
+}
+
+/// Record an invocation of `command`. Only a hash of `user_id` keyed with `key` is stored, so the
+/// usage can be counted per user without storing who used it. Without the key the hashes cannot
+/// be matched to users by hashing every known user id.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn record_command_usage(
+    pool: &Pool,
+    key: &[u8],
+    command: &str,
+    guild_id: Option<GuildId>,
+    user_id: UserId,
+    latency: Duration,
+    success: bool,
+) -> Result<(), sqlx::Error> {
+    let user_hash = user_hash(key, user_id);
+    let query = sqlx::query_file!(
+        "queries/insert_command_usage.sql",
+        command,
+        guild_id.map(|id| id.to_string()),
+        user_hash,
+        i64::try_from(latency.as_millis()).unwrap_or(i64::MAX),
+        success
+    );
+    metrics::instrument("insert_command_usage", query.execute(pool)).await?;
+    Ok(())
+}
+
+fn user_hash(key: &[u8], user_id: UserId) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(user_id.to_string().as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Summarize the invocations of each command since `since`, most invoked first.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_command_usage(
+    pool: &Pool,
+    since: DateTime<Utc>,
+) -> Result<Vec<CommandUsage>, sqlx::Error> {
+    let query = sqlx::query_file_as!(CommandUsage, "queries/find_command_usage.sql", since);
+    metrics::instrument("find_command_usage", query.fetch_all(pool)).await
+}
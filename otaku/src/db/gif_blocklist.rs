@@ -0,0 +1,113 @@
+use crate::db::{metrics, Pool};
+use crate::GuildId;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("Unknown blocked gif kind: {0}")]
+    UnknownKind(String),
+}
+
+/// GIFs a guild does not want the bot to post.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockedGif {
+    /// Every GIF found with this query.
+    Query(String),
+    /// This GIF.
+    Url(String),
+}
+
+impl BlockedGif {
+    fn kind(&self) -> &'static str {
+        match self {
+            BlockedGif::Query(_) => "query",
+            BlockedGif::Url(_) => "url",
+        }
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &str {
+        match self {
+            BlockedGif::Query(value) | BlockedGif::Url(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildBlockedGif {
+    pub id: i64,
+    pub value: BlockedGif,
+}
+
+struct BlockedGifRow {
+    id: i64,
+    kind: String,
+    value: String,
+}
+
+impl TryFrom<BlockedGifRow> for GuildBlockedGif {
+    type Error = Error;
+
+    fn try_from(row: BlockedGifRow) -> Result<Self, Self::Error> {
+        let value = match row.kind.as_str() {
+            "query" => BlockedGif::Query(row.value),
+            "url" => BlockedGif::Url(row.value),
+            _ => return Err(Error::UnknownKind(row.kind)),
+        };
+        Ok(GuildBlockedGif { id: row.id, value })
+    }
+}
+
+/// Add a GIF or query to the blocklist of a guild.
+///
+/// Returns `false` when it was already blocked.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn block_gif(pool: &Pool, guild_id: GuildId, value: &BlockedGif) -> Result<bool, Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_guild_gif_block.sql",
+        guild_id.to_string(),
+        value.kind(),
+        value.value()
+    );
+    let result = metrics::instrument("insert_guild_gif_block", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove an entry from the blocklist of a guild.
+///
+/// Returns `true` when an entry was removed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn unblock_gif(pool: &Pool, guild_id: GuildId, id: i64) -> Result<bool, Error> {
+    let query = sqlx::query_file!(
+        "queries/delete_guild_gif_block.sql",
+        id,
+        guild_id.to_string()
+    );
+    let result = metrics::instrument("delete_guild_gif_block", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// List the blocklist of a guild.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored entry is invalid.
+pub async fn get_blocked_gifs(
+    pool: &Pool,
+    guild_id: GuildId,
+) -> Result<Vec<GuildBlockedGif>, Error> {
+    let query = sqlx::query_file_as!(
+        BlockedGifRow,
+        "queries/find_guild_gif_blocklist.sql",
+        guild_id.to_string()
+    );
+    let rows = metrics::instrument("find_guild_gif_blocklist", query.fetch_all(pool)).await?;
+    rows.into_iter().map(GuildBlockedGif::try_from).collect()
+}
@@ -0,0 +1,146 @@
+use crate::db::{metrics, Pool};
+use crate::GuildId;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("Unknown gif override kind: {0}")]
+    UnknownKind(String),
+}
+
+/// A guild specific replacement for the GIFs of a command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GifOverride {
+    /// Search Tenor with this query.
+    Query(String),
+    /// Always consider this GIF.
+    Url(String),
+}
+
+impl GifOverride {
+    fn kind(&self) -> &'static str {
+        match self {
+            GifOverride::Query(_) => "query",
+            GifOverride::Url(_) => "url",
+        }
+    }
+
+    #[must_use]
+    pub fn value(&self) -> &str {
+        match self {
+            GifOverride::Query(value) | GifOverride::Url(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildGifOverride {
+    pub id: i64,
+    pub command: String,
+    pub value: GifOverride,
+}
+
+struct GifOverrideRow {
+    id: i64,
+    command: String,
+    kind: String,
+    value: String,
+}
+
+impl TryFrom<GifOverrideRow> for GuildGifOverride {
+    type Error = Error;
+
+    fn try_from(row: GifOverrideRow) -> Result<Self, Self::Error> {
+        let value = match row.kind.as_str() {
+            "query" => GifOverride::Query(row.value),
+            "url" => GifOverride::Url(row.value),
+            _ => return Err(Error::UnknownKind(row.kind)),
+        };
+        Ok(GuildGifOverride {
+            id: row.id,
+            command: row.command,
+            value,
+        })
+    }
+}
+
+/// Add a GIF override for `command` in a guild.
+///
+/// Returns `false` when the override already existed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_gif_override(
+    pool: &Pool,
+    guild_id: GuildId,
+    command: &str,
+    value: &GifOverride,
+) -> Result<bool, Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_guild_gif_override.sql",
+        guild_id.to_string(),
+        command,
+        value.kind(),
+        value.value()
+    );
+    let result = metrics::instrument("insert_guild_gif_override", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove a GIF override from a guild.
+///
+/// Returns `true` when an override was removed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn remove_gif_override(pool: &Pool, guild_id: GuildId, id: i64) -> Result<bool, Error> {
+    let query = sqlx::query_file!(
+        "queries/delete_guild_gif_override.sql",
+        id,
+        guild_id.to_string()
+    );
+    let result = metrics::instrument("delete_guild_gif_override", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// List all GIF overrides of a guild.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored override is invalid.
+pub async fn get_gif_overrides(
+    pool: &Pool,
+    guild_id: GuildId,
+) -> Result<Vec<GuildGifOverride>, Error> {
+    let query = sqlx::query_file_as!(
+        GifOverrideRow,
+        "queries/find_guild_gif_overrides.sql",
+        guild_id.to_string()
+    );
+    let rows = metrics::instrument("find_guild_gif_overrides", query.fetch_all(pool)).await?;
+    rows.into_iter().map(GuildGifOverride::try_from).collect()
+}
+
+/// List the GIF overrides of a guild for a single command.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored override is invalid.
+pub async fn get_command_gif_overrides(
+    pool: &Pool,
+    guild_id: GuildId,
+    command: &str,
+) -> Result<Vec<GuildGifOverride>, Error> {
+    let query = sqlx::query_file_as!(
+        GifOverrideRow,
+        "queries/find_guild_command_gif_overrides.sql",
+        guild_id.to_string(),
+        command
+    );
+    let rows =
+        metrics::instrument("find_guild_command_gif_overrides", query.fetch_all(pool)).await?;
+    rows.into_iter().map(GuildGifOverride::try_from).collect()
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+
+use crate::db::{metrics, Pool};
+use crate::GuildId;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryUsage {
+    pub query: String,
+    pub served: i64,
+    pub last_served_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GifUsage {
+    pub url: String,
+    pub served: i64,
+}
+
+/// Record that `url` was served for `query`.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn record_gif_usage(
+    pool: &Pool,
+    guild_id: Option<GuildId>,
+    query: &str,
+    url: &str,
+) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_gif_usage.sql",
+        guild_id.map(|id| id.to_string()),
+        query,
+        url
+    );
+    metrics::instrument("insert_gif_usage", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// Count how often each query was served since `since`, most served first.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_gif_usage_by_query(
+    pool: &Pool,
+    since: DateTime<Utc>,
+) -> Result<Vec<QueryUsage>, sqlx::Error> {
+    let query = sqlx::query_file_as!(QueryUsage, "queries/find_gif_usage_by_query.sql", since);
+    metrics::instrument("find_gif_usage_by_query", query.fetch_all(pool)).await
+}
+
+/// Get the `limit` most served GIFs since `since`, most served first.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_most_served_gifs(
+    pool: &Pool,
+    since: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<GifUsage>, sqlx::Error> {
+    let query = sqlx::query_file_as!(GifUsage, "queries/find_most_served_gifs.sql", since, limit);
+    metrics::instrument("find_most_served_gifs", query.fetch_all(pool)).await
+}
@@ -0,0 +1,65 @@
+use crate::db::{metrics, Pool};
+use crate::GuildId;
+
+/// A game a guild added to the games of the bot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildGame {
+    pub id: i64,
+    pub name: String,
+    /// Other names the game can be found with.
+    pub aliases: Vec<String>,
+    /// The query to search GIFs of the game with.
+    pub query: String,
+}
+
+/// Add a game to a guild.
+///
+/// Returns `false` when the guild already has a game with this name.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_game(
+    pool: &Pool,
+    guild_id: GuildId,
+    name: &str,
+    aliases: &[String],
+    query: &str,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_guild_game.sql",
+        guild_id.to_string(),
+        name,
+        aliases,
+        query
+    );
+    let result = metrics::instrument("insert_guild_game", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Remove the game called `name` from a guild, ignoring the case of the name.
+///
+/// Returns `true` when a game was removed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn remove_game(pool: &Pool, guild_id: GuildId, name: &str) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!("queries/delete_guild_game.sql", guild_id.to_string(), name);
+    let result = metrics::instrument("delete_guild_game", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// List the games of a guild by name.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_games(pool: &Pool, guild_id: GuildId) -> Result<Vec<GuildGame>, sqlx::Error> {
+    let query = sqlx::query_file_as!(
+        GuildGame,
+        "queries/find_guild_games.sql",
+        guild_id.to_string()
+    );
+    metrics::instrument("find_guild_games", query.fetch_all(pool)).await
+}
@@ -0,0 +1,385 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+
+use crate::db::{metrics, Pool};
+use crate::{ChannelId, GuildId, RoleId};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+    #[error("Unknown content filter: {0}")]
+    UnknownContentFilter(String),
+    #[error("Unknown permission level: {0}")]
+    UnknownPermissionLevel(String),
+    #[error("Invalid command permission: {0}")]
+    InvalidCommandPermission(String),
+    #[error("Unknown moderation level: {0}")]
+    UnknownModerationLevel(String),
+    #[error("Invalid novelty ratio: {0}")]
+    InvalidNoveltyRatio(i32),
+    #[error("Unknown GIF delivery: {0}")]
+    UnknownGifDelivery(String),
+}
+
+/// Content filter levels a guild can choose for GIF searches.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ContentFilterLevel {
+    High,
+    Medium,
+    Low,
+    Off,
+}
+
+impl ContentFilterLevel {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContentFilterLevel::High => "high",
+            ContentFilterLevel::Medium => "medium",
+            ContentFilterLevel::Low => "low",
+            ContentFilterLevel::Off => "off",
+        }
+    }
+}
+
+impl Display for ContentFilterLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContentFilterLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "high" => Ok(ContentFilterLevel::High),
+            "medium" => Ok(ContentFilterLevel::Medium),
+            "low" => Ok(ContentFilterLevel::Low),
+            "off" => Ok(ContentFilterLevel::Off),
+            other => Err(Error::UnknownContentFilter(other.to_string())),
+        }
+    }
+}
+
+/// How strictly the text that members give to the bot is moderated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ModerationLevel {
+    /// Also refuse mildly inappropriate text.
+    Strict,
+    Standard,
+    /// Only refuse severely inappropriate text.
+    Lenient,
+}
+
+impl ModerationLevel {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModerationLevel::Strict => "strict",
+            ModerationLevel::Standard => "standard",
+            ModerationLevel::Lenient => "lenient",
+        }
+    }
+}
+
+impl Display for ModerationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ModerationLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(ModerationLevel::Strict),
+            "standard" => Ok(ModerationLevel::Standard),
+            "lenient" => Ok(ModerationLevel::Lenient),
+            other => Err(Error::UnknownModerationLevel(other.to_string())),
+        }
+    }
+}
+
+/// How GIF commands post their GIF.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum GifDelivery {
+    /// Post the URL of the GIF and let Discord show its preview.
+    #[default]
+    Url,
+    /// Post the GIF in an embed with its query and attribution.
+    Embed,
+}
+
+impl GifDelivery {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GifDelivery::Url => "url",
+            GifDelivery::Embed => "embed",
+        }
+    }
+}
+
+impl Display for GifDelivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for GifDelivery {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "url" => Ok(GifDelivery::Url),
+            "embed" => Ok(GifDelivery::Embed),
+            other => Err(Error::UnknownGifDelivery(other.to_string())),
+        }
+    }
+}
+
+/// Replaces the seasonal GIF collections that follow the calendar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeasonOverride {
+    /// Never use a seasonal collection.
+    Disabled,
+    /// Always use the seasonal collection with this name.
+    Season(String),
+}
+
+impl SeasonOverride {
+    const DISABLED: &'static str = "off";
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        match self {
+            SeasonOverride::Disabled => Self::DISABLED,
+            SeasonOverride::Season(name) => name,
+        }
+    }
+}
+
+impl From<&str> for SeasonOverride {
+    fn from(s: &str) -> Self {
+        if s == Self::DISABLED {
+            SeasonOverride::Disabled
+        } else {
+            SeasonOverride::Season(s.to_string())
+        }
+    }
+}
+
+/// Who may use a command, every level includes the levels below it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PermissionLevel {
+    Everyone,
+    /// Members with one of the manager roles of the guild.
+    Manager,
+    /// Members that can manage the guild.
+    Admin,
+    /// The owners of the bot.
+    Owner,
+}
+
+impl PermissionLevel {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PermissionLevel::Everyone => "everyone",
+            PermissionLevel::Manager => "manager",
+            PermissionLevel::Admin => "admin",
+            PermissionLevel::Owner => "owner",
+        }
+    }
+}
+
+impl Display for PermissionLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PermissionLevel {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "everyone" => Ok(PermissionLevel::Everyone),
+            "manager" => Ok(PermissionLevel::Manager),
+            "admin" => Ok(PermissionLevel::Admin),
+            "owner" => Ok(PermissionLevel::Owner),
+            other => Err(Error::UnknownPermissionLevel(other.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuildSettings {
+    pub guild_id: GuildId,
+    /// Overrides the default content filter of the bot when set.
+    pub content_filter: Option<ContentFilterLevel>,
+    /// Channel used for bot announcements in this guild.
+    pub announcement_channel: Option<ChannelId>,
+    /// Display name for the guild currency.
+    pub currency_name: Option<String>,
+    pub gifs_enabled: bool,
+    pub announcements_enabled: bool,
+    /// Language code of the command replies in this guild, e.g. `nl`.
+    pub locale: Option<String>,
+    /// Names of the commands that cannot be used in this guild.
+    pub disabled_commands: Vec<String>,
+    /// Roles whose members have the [`PermissionLevel::Manager`] level.
+    pub manager_roles: Vec<RoleId>,
+    /// Levels that replace the default level of a command, by the name of the command.
+    pub command_permissions: BTreeMap<String, PermissionLevel>,
+    /// Overrides the default moderation of the bot when set.
+    pub moderation: Option<ModerationLevel>,
+    /// Replaces the seasonal GIF collections of the calendar when set.
+    pub season_override: Option<SeasonOverride>,
+    /// One in how many GIFs is a novelty GIF, `0` disables them. Uses the default of the
+    /// collection when not set.
+    pub novelty_ratio: Option<u32>,
+    pub gif_delivery: GifDelivery,
+}
+
+impl GuildSettings {
+    /// Create the default settings for a guild that has not configured anything.
+    #[must_use]
+    pub fn new(guild_id: GuildId) -> Self {
+        Self {
+            guild_id,
+            content_filter: None,
+            announcement_channel: None,
+            currency_name: None,
+            gifs_enabled: true,
+            announcements_enabled: true,
+            locale: None,
+            disabled_commands: Vec::new(),
+            manager_roles: Vec::new(),
+            command_permissions: BTreeMap::new(),
+            moderation: None,
+            season_override: None,
+            novelty_ratio: None,
+            gif_delivery: GifDelivery::default(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait GuildSettingsConnection {
+    /// Get the settings for a guild, falling back to the defaults when none are stored.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the database cannot be reached or the stored settings are invalid.
+    async fn get_guild_settings(&self, guild_id: GuildId) -> Result<GuildSettings, Error>;
+
+    /// Store the settings for a guild, replacing any existing settings.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the database cannot be reached.
+    async fn set_guild_settings(&self, settings: &GuildSettings) -> Result<(), Error>;
+}
+
+#[async_trait]
+impl GuildSettingsConnection for Pool {
+    async fn get_guild_settings(&self, guild_id: GuildId) -> Result<GuildSettings, Error> {
+        let query = sqlx::query_file!("queries/find_guild_settings.sql", guild_id.to_string());
+        let record = metrics::instrument("find_guild_settings", query.fetch_optional(self)).await?;
+        let Some(record) = record else {
+            return Ok(GuildSettings::new(guild_id));
+        };
+        Ok(GuildSettings {
+            guild_id,
+            content_filter: record
+                .content_filter
+                .as_deref()
+                .map(str::parse)
+                .transpose()?,
+            announcement_channel: record
+                .announcement_channel
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|err| Error::ParseInt(err, "announcement_channel"))?,
+            currency_name: record.currency_name,
+            gifs_enabled: record.gifs_enabled,
+            announcements_enabled: record.announcements_enabled,
+            locale: record.locale,
+            disabled_commands: record.disabled_commands,
+            manager_roles: record
+                .manager_roles
+                .iter()
+                .map(String::as_str)
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|err| Error::ParseInt(err, "manager_roles"))?,
+            command_permissions: record
+                .command_permissions
+                .iter()
+                .map(String::as_str)
+                .map(parse_command_permission)
+                .collect::<Result<_, _>>()?,
+            moderation: record.moderation.as_deref().map(str::parse).transpose()?,
+            season_override: record.season_override.as_deref().map(SeasonOverride::from),
+            novelty_ratio: record
+                .novelty_ratio
+                .map(|ratio| u32::try_from(ratio).map_err(|_| Error::InvalidNoveltyRatio(ratio)))
+                .transpose()?,
+            gif_delivery: record.gif_delivery.parse()?,
+        })
+    }
+
+    async fn set_guild_settings(&self, settings: &GuildSettings) -> Result<(), Error> {
+        let query = sqlx::query_file!(
+            "queries/upsert_guild_settings.sql",
+            settings.guild_id.to_string(),
+            settings.content_filter.map(ContentFilterLevel::as_str),
+            settings.announcement_channel.map(|id| id.to_string()),
+            settings.currency_name.as_deref(),
+            settings.gifs_enabled,
+            settings.announcements_enabled,
+            settings.locale.as_deref(),
+            &settings.disabled_commands,
+            &settings
+                .manager_roles
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            &settings
+                .command_permissions
+                .iter()
+                .map(|(command, level)| format!("{command}:{level}"))
+                .collect::<Vec<_>>(),
+            settings.moderation.map(ModerationLevel::as_str),
+            settings
+                .season_override
+                .as_ref()
+                .map(SeasonOverride::as_str),
+            settings
+                .novelty_ratio
+                .map(|ratio| i32::try_from(ratio).unwrap_or(i32::MAX)),
+            settings.gif_delivery.as_str(),
+        );
+        metrics::instrument("upsert_guild_settings", query.execute(self)).await?;
+        Ok(())
+    }
+}
+
+/// Parse a stored command permission, formatted as `command:level`.
+fn parse_command_permission(permission: &str) -> Result<(String, PermissionLevel), Error> {
+    let (command, level) = permission
+        .split_once(':')
+        .ok_or_else(|| Error::InvalidCommandPermission(permission.to_string()))?;
+    Ok((command.to_string(), level.parse()?))
+}
@@ -0,0 +1,124 @@
+use std::num::ParseIntError;
+
+use chrono::{DateTime, Utc};
+
+use crate::db::{metrics, Pool};
+use crate::{ChannelId, GuildId, MessageId, UserId};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+}
+
+/// A second, more urgent GIF for a member that was told to hurry up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HurryEscalation {
+    pub id: i64,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    /// The message that told the member to hurry up, the escalation is only posted when they have
+    /// not posted a message after it.
+    pub message_id: MessageId,
+    pub user_id: UserId,
+    pub query: String,
+    pub gif: String,
+    pub due_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewHurryEscalation<'a> {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub message_id: MessageId,
+    pub user_id: UserId,
+    pub query: &'a str,
+    pub gif: &'a str,
+    pub due_at: DateTime<Utc>,
+}
+
+struct HurryEscalationRow {
+    id: i64,
+    guild_id: Option<String>,
+    channel_id: String,
+    message_id: String,
+    user_id: String,
+    query: String,
+    gif: String,
+    due_at: DateTime<Utc>,
+}
+
+impl TryFrom<HurryEscalationRow> for HurryEscalation {
+    type Error = Error;
+
+    fn try_from(row: HurryEscalationRow) -> Result<Self, Self::Error> {
+        Ok(HurryEscalation {
+            id: row.id,
+            guild_id: row
+                .guild_id
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|err| Error::ParseInt(err, "guild_id"))?,
+            channel_id: row
+                .channel_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "channel_id"))?,
+            message_id: row
+                .message_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "message_id"))?,
+            user_id: row
+                .user_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "user_id"))?,
+            query: row.query,
+            gif: row.gif,
+            due_at: row.due_at,
+        })
+    }
+}
+
+/// Store an escalation that is posted at its due time.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_escalation(
+    pool: &Pool,
+    escalation: &NewHurryEscalation<'_>,
+) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_hurry_escalation.sql",
+        escalation.guild_id.map(|id| id.to_string()),
+        escalation.channel_id.to_string(),
+        escalation.message_id.to_string(),
+        escalation.user_id.to_string(),
+        escalation.query,
+        escalation.gif,
+        escalation.due_at,
+    );
+    metrics::instrument("insert_hurry_escalation", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// Claim and remove all escalations that are due at or before `now`, so an escalation is only
+/// returned once even when multiple instances poll concurrently.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored escalation is invalid.
+pub async fn take_due_escalations(
+    pool: &Pool,
+    now: DateTime<Utc>,
+) -> Result<Vec<HurryEscalation>, Error> {
+    let query = sqlx::query_file_as!(
+        HurryEscalationRow,
+        "queries/take_due_hurry_escalations.sql",
+        now
+    );
+    let rows = metrics::instrument("take_due_hurry_escalations", query.fetch_all(pool)).await?;
+    rows.into_iter().map(HurryEscalation::try_from).collect()
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use async_trait::async_trait;
+
+use crate::db::guild_settings::{Error, GuildSettings, GuildSettingsConnection};
+use crate::GuildId;
+
+/// A `HashMap` backed stand-in for the database, for testing code that depends on the db traits
+/// without a running Postgres instance.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryDatabase {
+    guild_settings: Arc<Mutex<HashMap<GuildId, GuildSettings>>>,
+}
+
+impl InMemoryDatabase {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl GuildSettingsConnection for InMemoryDatabase {
+    async fn get_guild_settings(&self, guild_id: GuildId) -> Result<GuildSettings, Error> {
+        let map = self
+            .guild_settings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        Ok(map
+            .get(&guild_id)
+            .cloned()
+            .unwrap_or_else(|| GuildSettings::new(guild_id)))
+    }
+
+    async fn set_guild_settings(&self, settings: &GuildSettings) -> Result<(), Error> {
+        let mut map = self
+            .guild_settings
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        map.insert(settings.guild_id, settings.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::db::guild_settings::{
+        ContentFilterLevel, GifDelivery, ModerationLevel, PermissionLevel, SeasonOverride,
+    };
+    use crate::RoleId;
+
+    use std::num::NonZeroU64;
+
+    const GUILD_ID: GuildId = match NonZeroU64::new(1234) {
+        Some(id) => GuildId::new(id),
+        None => unreachable!(),
+    };
+
+    #[tokio::test]
+    async fn returns_default_settings_for_unknown_guild() {
+        let db = InMemoryDatabase::new();
+        let settings = db.get_guild_settings(GUILD_ID).await.unwrap();
+        assert_eq!(settings, GuildSettings::new(GUILD_ID));
+    }
+
+    #[tokio::test]
+    async fn returns_stored_settings() {
+        let db = InMemoryDatabase::new();
+        let mut settings = GuildSettings::new(GUILD_ID);
+        settings.content_filter = Some(ContentFilterLevel::High);
+        settings.gifs_enabled = false;
+        settings.locale = Some("nl".to_string());
+        settings.disabled_commands = vec!["play".to_string()];
+        settings.manager_roles = vec![RoleId::new(NonZeroU64::new(2).unwrap())];
+        settings
+            .command_permissions
+            .insert("settings".to_string(), PermissionLevel::Manager);
+        settings.moderation = Some(ModerationLevel::Strict);
+        settings.season_override = Some(SeasonOverride::Disabled);
+        settings.novelty_ratio = Some(0);
+        settings.gif_delivery = GifDelivery::Embed;
+        db.set_guild_settings(&settings).await.unwrap();
+        assert_eq!(db.get_guild_settings(GUILD_ID).await.unwrap(), settings);
+    }
+}
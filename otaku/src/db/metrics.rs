@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{LazyLock, Mutex, PoisonError};
+use std::time::Instant;
+
+use sqlx::postgres::PgQueryResult;
+use tracing::{field, warn, Span};
+
+static QUERY_STATS: LazyLock<Mutex<HashMap<&'static str, QueryStats>>> =
+    LazyLock::new(Mutex::default);
+
+/// Outcome counters of a single statement.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    pub successes: u64,
+    pub failures: u64,
+}
+
+/// Snapshot of the query counters per statement name.
+#[must_use]
+pub fn query_stats() -> HashMap<&'static str, QueryStats> {
+    QUERY_STATS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+pub(crate) trait RowCount {
+    fn row_count(&self) -> u64;
+}
+
+impl RowCount for PgQueryResult {
+    fn row_count(&self) -> u64 {
+        self.rows_affected()
+    }
+}
+
+impl<T> RowCount for Vec<T> {
+    fn row_count(&self) -> u64 {
+        u64::try_from(self.len()).unwrap_or(u64::MAX)
+    }
+}
+
+/// Wrapper for results of queries that always return exactly one row.
+pub(crate) struct One<T>(pub T);
+
+impl<T> RowCount for One<T> {
+    fn row_count(&self) -> u64 {
+        1
+    }
+}
+
+impl<T> RowCount for Option<T> {
+    fn row_count(&self) -> u64 {
+        u64::from(self.is_some())
+    }
+}
+
+/// Run a query inside a span that records the elapsed time and the number of rows, and count the
+/// outcome for `statement`.
+#[tracing::instrument(
+    name = "query",
+    level = "debug",
+    skip(query),
+    fields(rows = field::Empty, elapsed_ms = field::Empty)
+)]
+pub(crate) async fn instrument<T, E>(
+    statement: &'static str,
+    query: impl Future<Output = Result<T, E>>,
+) -> Result<T, E>
+where
+    T: RowCount,
+    E: Display,
+{
+    let start = Instant::now();
+    let result = query.await;
+    let span = Span::current();
+    span.record("elapsed_ms", start.elapsed().as_secs_f64() * 1000.0);
+
+    let mut stats = QUERY_STATS.lock().unwrap_or_else(PoisonError::into_inner);
+    let stats = stats.entry(statement).or_default();
+    match &result {
+        Ok(rows) => {
+            span.record("rows", rows.row_count());
+            stats.successes += 1;
+        }
+        Err(err) => {
+            warn!("Query {statement} failed: {err}");
+            stats.failures += 1;
+        }
+    }
+    result
+}
@@ -0,0 +1,137 @@
+use std::num::ParseIntError;
+
+use chrono::{DateTime, Utc};
+use futures_util::TryFutureExt;
+
+use crate::db::metrics::{self, One};
+use crate::db::Pool;
+use crate::{ChannelId, GuildId, UserId};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+}
+
+/// A planned session of /play that is due.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlaySession {
+    pub id: i64,
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub host_id: UserId,
+    pub game: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    /// The members that joined the session, including the host unless they declined.
+    pub attendees: Vec<UserId>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPlaySession<'a> {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub host_id: UserId,
+    pub game: Option<&'a str>,
+    pub starts_at: DateTime<Utc>,
+}
+
+struct PlaySessionRow {
+    id: i64,
+    guild_id: Option<String>,
+    channel_id: String,
+    host_id: String,
+    game: Option<String>,
+    starts_at: DateTime<Utc>,
+    attendees: Vec<String>,
+}
+
+impl TryFrom<PlaySessionRow> for PlaySession {
+    type Error = Error;
+
+    fn try_from(row: PlaySessionRow) -> Result<Self, Self::Error> {
+        Ok(PlaySession {
+            id: row.id,
+            guild_id: row
+                .guild_id
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|err| Error::ParseInt(err, "guild_id"))?,
+            channel_id: row
+                .channel_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "channel_id"))?,
+            host_id: row
+                .host_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "host_id"))?,
+            game: row.game,
+            starts_at: row.starts_at,
+            attendees: row
+                .attendees
+                .iter()
+                .map(String::as_str)
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|err| Error::ParseInt(err, "attendees"))?,
+        })
+    }
+}
+
+/// Store a new session, the host joins it right away.
+///
+/// Returns the id of the new session.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_session(pool: &Pool, session: &NewPlaySession<'_>) -> Result<i64, Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_play_session.sql",
+        session.guild_id.map(|id| id.to_string()),
+        session.channel_id.to_string(),
+        session.host_id.to_string(),
+        session.game,
+        session.starts_at,
+    );
+    let One(record) =
+        metrics::instrument("insert_play_session", query.fetch_one(pool).map_ok(One)).await?;
+    Ok(record.id)
+}
+
+/// Store whether `user_id` joins the session, replacing their earlier answer.
+///
+/// Returns `false` when the session no longer exists.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn set_rsvp(
+    pool: &Pool,
+    session_id: i64,
+    user_id: UserId,
+    attending: bool,
+) -> Result<bool, Error> {
+    let query = sqlx::query_file!(
+        "queries/upsert_play_session_rsvp.sql",
+        session_id,
+        user_id.to_string(),
+        attending
+    );
+    let result = metrics::instrument("upsert_play_session_rsvp", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim and remove all sessions that start at or before `now`, so a session is only returned
+/// once even when multiple instances poll concurrently.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored session is invalid.
+pub async fn take_due_sessions(pool: &Pool, now: DateTime<Utc>) -> Result<Vec<PlaySession>, Error> {
+    let query = sqlx::query_file_as!(PlaySessionRow, "queries/take_due_play_sessions.sql", now);
+    let rows = metrics::instrument("take_due_play_sessions", query.fetch_all(pool)).await?;
+    rows.into_iter().map(PlaySession::try_from).collect()
+}
@@ -0,0 +1,160 @@
+use std::num::{ParseIntError, TryFromIntError};
+
+use chrono::{DateTime, Utc};
+use futures_util::TryFutureExt;
+
+use crate::db::metrics::{self, One};
+use crate::db::Pool;
+use crate::{ChannelId, GuildId, MessageId, UserId};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+    #[error(transparent)]
+    TryFromInt(#[from] TryFromIntError),
+}
+
+/// A poll that closed, with the votes on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Poll {
+    pub id: i64,
+    pub channel_id: ChannelId,
+    /// The message with the poll, `None` when it could not be sent.
+    pub message_id: Option<MessageId>,
+    pub question: String,
+    pub options: Vec<String>,
+    /// The code of the language of the poll.
+    pub locale: String,
+    pub closes_at: DateTime<Utc>,
+    /// The index of the option of every vote.
+    pub votes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewPoll<'a> {
+    pub channel_id: ChannelId,
+    /// The guild of the channel, so the poll is removed with the data of the guild.
+    pub guild_id: Option<GuildId>,
+    pub question: &'a str,
+    pub options: &'a [String],
+    pub locale: &'a str,
+    pub closes_at: DateTime<Utc>,
+}
+
+struct PollRow {
+    id: i64,
+    channel_id: String,
+    message_id: Option<String>,
+    question: String,
+    options: Vec<String>,
+    locale: String,
+    closes_at: DateTime<Utc>,
+    votes: Vec<i32>,
+}
+
+impl TryFrom<PollRow> for Poll {
+    type Error = Error;
+
+    fn try_from(row: PollRow) -> Result<Self, Self::Error> {
+        Ok(Poll {
+            id: row.id,
+            channel_id: row
+                .channel_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "channel_id"))?,
+            message_id: row
+                .message_id
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|err| Error::ParseInt(err, "message_id"))?,
+            question: row.question,
+            options: row.options,
+            locale: row.locale,
+            closes_at: row.closes_at,
+            votes: row
+                .votes
+                .into_iter()
+                .map(usize::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Store a new poll, its message is stored with [`set_message`] once it is sent.
+///
+/// Returns the id of the new poll.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_poll(pool: &Pool, new_poll: &NewPoll<'_>) -> Result<i64, Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_poll.sql",
+        new_poll.channel_id.to_string(),
+        new_poll.guild_id.map(|id| id.to_string()),
+        new_poll.question,
+        new_poll.options,
+        new_poll.locale,
+        new_poll.closes_at,
+    );
+    let One(record) = metrics::instrument("insert_poll", query.fetch_one(pool).map_ok(One)).await?;
+    Ok(record.id)
+}
+
+/// Store the message that shows the poll, so it can be closed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn set_message(
+    pool: &Pool,
+    poll_id: i64,
+    message_id: MessageId,
+) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/update_poll_message.sql",
+        poll_id,
+        message_id.to_string()
+    );
+    metrics::instrument("update_poll_message", query.execute(pool)).await?;
+    Ok(())
+}
+
+/// Store the vote of `user_id` for the option at `option`, replacing their earlier vote.
+///
+/// Returns `false` when the poll no longer exists.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn set_vote(
+    pool: &Pool,
+    poll_id: i64,
+    user_id: UserId,
+    option: usize,
+) -> Result<bool, Error> {
+    let query = sqlx::query_file!(
+        "queries/upsert_poll_vote.sql",
+        poll_id,
+        user_id.to_string(),
+        i32::try_from(option)?
+    );
+    let result = metrics::instrument("upsert_poll_vote", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim and remove all polls that close at or before `now`, so a poll is only returned once even
+/// when multiple instances poll concurrently.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored poll is invalid.
+pub async fn take_due_polls(pool: &Pool, now: DateTime<Utc>) -> Result<Vec<Poll>, Error> {
+    let query = sqlx::query_file_as!(PollRow, "queries/take_due_polls.sql", now);
+    let rows = metrics::instrument("take_due_polls", query.fetch_all(pool)).await?;
+    rows.into_iter().map(Poll::try_from).collect()
+}
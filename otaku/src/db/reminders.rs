@@ -0,0 +1,135 @@
+use std::num::{ParseIntError, TryFromIntError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures_util::TryFutureExt;
+
+use crate::db::metrics::{self, One};
+use crate::db::Pool;
+use crate::{ChannelId, UserId};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+    #[error(transparent)]
+    TryFromInt(#[from] TryFromIntError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reminder {
+    pub id: i64,
+    pub user_id: UserId,
+    /// Deliver the reminder in this channel, or by direct message when `None`.
+    pub channel_id: Option<ChannelId>,
+    pub message: String,
+    pub due_at: DateTime<Utc>,
+    /// Schedule the reminder again after this interval once it is due.
+    pub repeat: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewReminder<'a> {
+    pub user_id: UserId,
+    pub channel_id: Option<ChannelId>,
+    pub message: &'a str,
+    pub due_at: DateTime<Utc>,
+    pub repeat: Option<Duration>,
+}
+
+struct ReminderRow {
+    id: i64,
+    user_id: String,
+    channel_id: Option<String>,
+    message: String,
+    due_at: DateTime<Utc>,
+    repeat_seconds: Option<i64>,
+}
+
+impl TryFrom<ReminderRow> for Reminder {
+    type Error = Error;
+
+    fn try_from(row: ReminderRow) -> Result<Self, Self::Error> {
+        Ok(Reminder {
+            id: row.id,
+            user_id: row
+                .user_id
+                .parse()
+                .map_err(|err| Error::ParseInt(err, "user_id"))?,
+            channel_id: row
+                .channel_id
+                .as_deref()
+                .map(str::parse)
+                .transpose()
+                .map_err(|err| Error::ParseInt(err, "channel_id"))?,
+            message: row.message,
+            due_at: row.due_at,
+            repeat: row
+                .repeat_seconds
+                .map(u64::try_from)
+                .transpose()?
+                .map(Duration::from_secs),
+        })
+    }
+}
+
+/// Store a new reminder.
+///
+/// Returns the id of the new reminder.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn add_reminder(pool: &Pool, reminder: &NewReminder<'_>) -> Result<i64, Error> {
+    let repeat_seconds = reminder
+        .repeat
+        .map(|repeat| i64::try_from(repeat.as_secs()))
+        .transpose()?;
+    let query = sqlx::query_file!(
+        "queries/insert_reminder.sql",
+        reminder.user_id.to_string(),
+        reminder.channel_id.map(|id| id.to_string()),
+        reminder.message,
+        reminder.due_at,
+        repeat_seconds,
+    );
+    let One(record) =
+        metrics::instrument("insert_reminder", query.fetch_one(pool).map_ok(One)).await?;
+    Ok(record.id)
+}
+
+/// Remove a reminder owned by `user_id`.
+///
+/// Returns `true` when a reminder was removed.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn remove_reminder(pool: &Pool, id: i64, user_id: UserId) -> Result<bool, Error> {
+    let query = sqlx::query_file!("queries/delete_reminder.sql", id, user_id.to_string());
+    let result = metrics::instrument("delete_reminder", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Claim all reminders that are due at `now`.
+///
+/// One-off reminders are removed and recurring reminders are moved to their next occurrence after
+/// `now`, so a reminder is only returned once even when multiple instances poll concurrently.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored reminder is invalid.
+pub async fn take_due_reminders(pool: &Pool, now: DateTime<Utc>) -> Result<Vec<Reminder>, Error> {
+    let mut tx = pool.begin().await?;
+    let query = sqlx::query_file_as!(ReminderRow, "queries/take_due_reminders.sql", now);
+    let mut rows = metrics::instrument("take_due_reminders", query.fetch_all(&mut *tx)).await?;
+    let query = sqlx::query_file_as!(ReminderRow, "queries/reschedule_due_reminders.sql", now);
+    let rescheduled =
+        metrics::instrument("reschedule_due_reminders", query.fetch_all(&mut *tx)).await?;
+    tx.commit().await?;
+
+    rows.extend(rescheduled);
+    rows.into_iter().map(Reminder::try_from).collect()
+}
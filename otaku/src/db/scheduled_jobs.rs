@@ -0,0 +1,29 @@
+use chrono::{DateTime, Utc};
+
+use crate::db::{metrics, Pool};
+
+/// Get the time the job `name` should run next, or `None` when it has never run.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_next_run(pool: &Pool, name: &str) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    let query = sqlx::query_file!("queries/find_scheduled_job.sql", name);
+    let record = metrics::instrument("find_scheduled_job", query.fetch_optional(pool)).await?;
+    Ok(record.map(|record| record.next_run_at))
+}
+
+/// Store the time the job `name` should run next, after it ran now.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn set_next_run(
+    pool: &Pool,
+    name: &str,
+    next_run_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    let query = sqlx::query_file!("queries/upsert_scheduled_job.sql", name, next_run_at);
+    metrics::instrument("upsert_scheduled_job", query.execute(pool)).await?;
+    Ok(())
+}
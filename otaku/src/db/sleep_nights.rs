@@ -0,0 +1,96 @@
+use std::num::ParseIntError;
+
+use chrono::NaiveDate;
+
+use crate::db::{metrics, Pool};
+use crate::{GuildId, UserId};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    Sqlx(#[from] sqlx::Error),
+    #[error("{0} for {1}")]
+    ParseInt(#[source] ParseIntError, &'static str),
+}
+
+/// A member and the number of nights they said good night.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Sleeper {
+    pub user_id: UserId,
+    pub nights: i64,
+}
+
+/// Record that a member said good night in a guild on `night`.
+///
+/// Returns `false` when the night was already recorded.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn record_night(
+    pool: &Pool,
+    guild_id: GuildId,
+    user_id: UserId,
+    night: NaiveDate,
+) -> Result<bool, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/insert_sleep_night.sql",
+        guild_id.to_string(),
+        user_id.to_string(),
+        night
+    );
+    let result = metrics::instrument("insert_sleep_night", query.execute(pool)).await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The nights a member said good night in a guild, the last night first.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_nights(
+    pool: &Pool,
+    guild_id: GuildId,
+    user_id: UserId,
+) -> Result<Vec<NaiveDate>, sqlx::Error> {
+    let query = sqlx::query_file!(
+        "queries/find_sleep_nights.sql",
+        guild_id.to_string(),
+        user_id.to_string()
+    );
+    let records = metrics::instrument("find_sleep_nights", query.fetch_all(pool)).await?;
+    Ok(records.into_iter().map(|record| record.night).collect())
+}
+
+/// The members of a guild that said good night on the most nights since `since`, at most
+/// `limit` of them.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached or a stored user id is invalid.
+pub async fn get_consistent_sleepers(
+    pool: &Pool,
+    guild_id: GuildId,
+    since: NaiveDate,
+    limit: i64,
+) -> Result<Vec<Sleeper>, Error> {
+    let query = sqlx::query_file!(
+        "queries/find_consistent_sleepers.sql",
+        guild_id.to_string(),
+        since,
+        limit
+    );
+    let records = metrics::instrument("find_consistent_sleepers", query.fetch_all(pool)).await?;
+    records
+        .into_iter()
+        .map(|record| {
+            Ok(Sleeper {
+                user_id: record
+                    .user_id
+                    .parse()
+                    .map_err(|err| Error::ParseInt(err, "user_id"))?,
+                nights: record.nights,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,37 @@
+use crate::db::{metrics, Pool};
+use crate::UserId;
+
+/// Get the offset from UTC in minutes that a user stored, or `None` when they did not.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn get_utc_offset(pool: &Pool, user_id: UserId) -> Result<Option<i32>, sqlx::Error> {
+    let query = sqlx::query_file!("queries/find_user_timezone.sql", user_id.to_string());
+    let record = metrics::instrument("find_user_timezone", query.fetch_optional(pool)).await?;
+    Ok(record.map(|record| record.utc_offset_minutes))
+}
+
+/// Store the offset from UTC in minutes of a user, `None` removes it.
+///
+/// # Errors
+///
+/// Will return an error when the database cannot be reached.
+pub async fn set_utc_offset(
+    pool: &Pool,
+    user_id: UserId,
+    utc_offset_minutes: Option<i32>,
+) -> Result<(), sqlx::Error> {
+    if let Some(minutes) = utc_offset_minutes {
+        let query = sqlx::query_file!(
+            "queries/upsert_user_timezone.sql",
+            user_id.to_string(),
+            minutes
+        );
+        metrics::instrument("upsert_user_timezone", query.execute(pool)).await?;
+    } else {
+        let query = sqlx::query_file!("queries/delete_user_timezone.sql", user_id.to_string());
+        metrics::instrument("delete_user_timezone", query.execute(pool)).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::num::{NonZeroU64, ParseIntError};
+use std::str::FromStr;
+
+macro_rules! discord_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[cfg_attr(feature = "serde", serde(transparent))]
+        pub struct $name(NonZeroU64);
+
+        impl $name {
+            #[must_use]
+            pub const fn new(id: NonZeroU64) -> Self {
+                Self(id)
+            }
+
+            #[must_use]
+            pub const fn get(self) -> NonZeroU64 {
+                self.0
+            }
+        }
+
+        impl From<NonZeroU64> for $name {
+            fn from(id: NonZeroU64) -> Self {
+                Self(id)
+            }
+        }
+
+        impl From<$name> for NonZeroU64 {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIntError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse().map(Self)
+            }
+        }
+
+        #[cfg(feature = "serenity")]
+        impl From<serenity::model::id::$name> for $name {
+            fn from(id: serenity::model::id::$name) -> Self {
+                Self(id.into())
+            }
+        }
+
+        #[cfg(feature = "serenity")]
+        impl From<$name> for serenity::model::id::$name {
+            fn from(id: $name) -> Self {
+                id.0.into()
+            }
+        }
+    };
+}
+
+discord_id!(
+    /// The id of a Discord guild.
+    GuildId
+);
+discord_id!(
+    /// The id of a Discord channel.
+    ChannelId
+);
+discord_id!(
+    /// The id of a Discord user.
+    UserId
+);
+discord_id!(
+    /// The id of a Discord role.
+    RoleId
+);
+discord_id!(
+    /// The id of a Discord message.
+    MessageId
+);
@@ -1,26 +1,51 @@
-use std::cmp::min;
+use std::cmp::Ordering;
 use std::fmt::Display;
-use std::num::{NonZeroU64, ParseIntError, TryFromIntError};
+use std::hash::{Hash, Hasher};
+use std::num::{ParseIntError, TryFromIntError};
 use std::ops::RangeInclusive;
+use std::sync::{Mutex, PoisonError};
 use std::time::Duration;
 
+use futures_util::future::join_all;
 use futures_util::TryStreamExt;
 use prost_types::Timestamp;
 use sqlx::pool::Pool;
-use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::types::chrono::{DateTime, NaiveTime, Utc};
 use sqlx::Postgres;
 use tokio::sync::mpsc::error::SendError;
 use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
 use tonic::codec::CompressionEncoding;
-use tracing::{debug, error, info, instrument};
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::Channel;
+use tonic::Code;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+use tracing::{debug, error, info, instrument, warn};
 
 use proto::api::v2::downloads_client::DownloadsClient;
 
+mod api;
+mod builder;
+mod config;
 pub mod db;
+mod ids;
+mod metrics;
+mod source;
 
-const MAX_BACKOFF: Duration = Duration::from_secs(30);
-const BACKOFF_INTERVAL: Duration = Duration::from_millis(125);
-const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+use api::AuthInterceptor;
+pub use api::{Api, ChannelConfig, TlsConfig, TlsConfigError};
+pub use builder::{DownloadCollectionBuilder, ValidationError};
+pub use config::{BackoffConfig, Config, ConfigError};
+pub use ids::{ChannelId, GuildId, MessageId, RoleId, UserId};
+pub use metrics::{stream_stats, StreamStats};
+pub use source::{DownloadSource, GrpcSource};
+
+const DOWNLOADS_SERVICE: &str = "api.v2.Downloads";
+
+type Client = DownloadsClient<InterceptedService<Channel, AuthInterceptor>>;
 
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -34,8 +59,12 @@ pub enum Error {
 
 #[derive(thiserror::Error, Debug)]
 enum ConnectionError {
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
     #[error(transparent)]
     Status(#[from] tonic::Status),
+    #[error("The service is not serving, status: {0:?}")]
+    NotServing(ServingStatus),
     #[error("The connection was closed by the remote")]
     Closed,
 }
@@ -50,6 +79,18 @@ pub enum ConversionError {
     MissingField(&'static str),
     #[error("Encounter invalid timestamp")]
     InvalidTimeStamp(Timestamp),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum SearchError {
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+    #[error(transparent)]
+    Status(#[from] tonic::Status),
+    #[error(transparent)]
+    Conversion(#[from] ConversionError),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -58,15 +99,20 @@ pub enum SubscriptionError {
     Sqlx(#[from] sqlx::Error),
     #[error("{0} for {1}")]
     ParseInt(#[source] ParseIntError, &'static str),
+    #[error(transparent)]
+    TryFromInt(#[from] TryFromIntError),
     #[error("Found no subscriptions")]
     Empty,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DownloadVariant {
     Batch(RangeInclusive<u32>),
     Episode(Episode),
     Movie,
+    /// A variant that was added to the api after this version.
+    Unknown,
 }
 
 impl Display for DownloadVariant {
@@ -89,11 +135,17 @@ impl Display for DownloadVariant {
                 write!(f, "]")
             }
             DownloadVariant::Movie => write!(f, "[Movie]"),
+            DownloadVariant::Unknown => write!(f, "[Release]"),
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// An episode of a show.
+///
+/// Episodes are compared by number, decimal and version, the `extra` label does not identify an
+/// episode. A missing version is the first version.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Episode {
     pub number: u32,
     pub decimal: Option<u32>,
@@ -101,7 +153,52 @@ pub struct Episode {
     pub extra: Option<String>,
 }
 
+impl Episode {
+    fn key(&self) -> (u32, u32, u32) {
+        (
+            self.number,
+            self.decimal.unwrap_or(0),
+            self.version.unwrap_or(1),
+        )
+    }
+
+    /// Whether this is the same episode as `other` and replaces it with a newer version.
+    #[must_use]
+    pub fn supersedes(&self, other: &Episode) -> bool {
+        let (number, decimal, version) = self.key();
+        let (other_number, other_decimal, other_version) = other.key();
+        number == other_number && decimal == other_decimal && version > other_version
+    }
+}
+
+impl PartialEq for Episode {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Episode {}
+
+impl Hash for Episode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for Episode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Episode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Download {
     pub published_date: DateTime<Utc>,
     pub resolution: u16,
@@ -111,6 +208,7 @@ pub struct Download {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DownloadCollection {
     pub title: String,
     pub variant: DownloadVariant,
@@ -119,57 +217,294 @@ pub struct DownloadCollection {
     pub downloads: Vec<Download>,
 }
 
+impl DownloadCollection {
+    pub fn builder(title: impl Into<String>) -> DownloadCollectionBuilder {
+        DownloadCollectionBuilder::new(title)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Subscribed<T: Clone + PartialEq> {
     pub content: T,
-    pub subscribers: Vec<Subscriber>,
+    pub subscribers: Vec<Subscription>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Subscription {
+    pub subscriber: Subscriber,
+    pub preferences: SubscriptionPreferences,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubscriptionPreferences {
+    /// Only show downloads in this resolution when it is available.
+    pub resolution: Option<u16>,
+    pub variants: VariantFilter,
+    /// Skip announcements during this window.
+    pub mute: Option<MuteWindow>,
+}
+
+impl SubscriptionPreferences {
+    /// Whether `collection` should be announced at `now`.
+    #[must_use]
+    pub fn accepts(&self, collection: &DownloadCollection, now: DateTime<Utc>) -> bool {
+        self.variants.allows(&collection.variant)
+            && !self.mute.is_some_and(|mute| mute.contains(now.time()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::struct_excessive_bools)]
+pub struct VariantFilter {
+    pub episodes: bool,
+    pub batches: bool,
+    pub movies: bool,
+}
+
+impl VariantFilter {
+    #[must_use]
+    pub fn allows(self, variant: &DownloadVariant) -> bool {
+        match variant {
+            DownloadVariant::Batch(_) => self.batches,
+            DownloadVariant::Episode(_) => self.episodes,
+            DownloadVariant::Movie => self.movies,
+            DownloadVariant::Unknown => self.episodes || self.batches || self.movies,
+        }
+    }
+}
+
+impl Default for VariantFilter {
+    fn default() -> Self {
+        Self {
+            episodes: true,
+            batches: true,
+            movies: true,
+        }
+    }
+}
+
+/// A daily window in UTC, windows that end before they start pass midnight.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MuteWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl MuteWindow {
+    #[must_use]
+    pub fn contains(self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Subscriber {
-    User(NonZeroU64),
+    User(UserId),
     Channel {
-        channel_id: NonZeroU64,
-        guild_id: NonZeroU64,
+        channel_id: ChannelId,
+        guild_id: GuildId,
     },
 }
 
+/// The state of the connection to the anime api.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Connected,
+    Disconnected,
+}
+
+impl ConnectionState {
+    /// The state of multiple connections, connected while any connection is connected.
+    fn combine(states: &[ConnectionState]) -> ConnectionState {
+        if states.contains(&ConnectionState::Connected) {
+            ConnectionState::Connected
+        } else if states.contains(&ConnectionState::Connecting) {
+            ConnectionState::Connecting
+        } else {
+            ConnectionState::Disconnected
+        }
+    }
+}
+
+/// Forward new download collections from all `apis` to `sender`, reconnecting to an api whenever
+/// its connection is lost. A collection that is received from multiple apis is only forwarded once.
+///
+/// The combined connection state is published on `state`. Returns once `shutdown` is cancelled.
 pub async fn subscribe(
-    endpoint: &'static str,
+    apis: Vec<Api>,
+    config: Config,
     pool: Pool<Postgres>,
     sender: Sender<Subscribed<DownloadCollection>>,
+    state: watch::Sender<ConnectionState>,
+    shutdown: CancellationToken,
 ) {
+    let states = Mutex::new(vec![ConnectionState::Disconnected; apis.len()]);
+    let set_state = |index: usize, new_state: ConnectionState| {
+        let mut states = states.lock().unwrap_or_else(PoisonError::into_inner);
+        states[index] = new_state;
+        state.send_replace(ConnectionState::combine(&states));
+    };
+    let set_state = &set_state;
+    let streams = apis.iter().enumerate().map(|(index, api)| {
+        subscribe_api(api, config, &pool, &sender, &shutdown, move |new_state| {
+            set_state(index, new_state);
+        })
+    });
+    join_all(streams).await;
+    state.send_replace(ConnectionState::Disconnected);
+    info!("Stopped anime subscription");
+}
+
+async fn subscribe_api(
+    api: &Api,
+    config: Config,
+    pool: &Pool<Postgres>,
+    sender: &Sender<Subscribed<DownloadCollection>>,
+    shutdown: &CancellationToken,
+    set_state: impl Fn(ConnectionState),
+) {
+    let mut connected_before = false;
     loop {
-        let client = connect_with_backoff(endpoint).await;
-        if let Err(err) = handle_stream(client, pool.clone(), sender.clone()).await {
-            error!("Closed anime subscription with {err}, Reconnecting in 5 seconds");
-            tokio::time::sleep(RECONNECT_INTERVAL).await;
+        set_state(ConnectionState::Connecting);
+        let client = tokio::select! {
+            () = shutdown.cancelled() => break,
+            client = connect_with_backoff(api, config.backoff) => client,
+        };
+        if connected_before {
+            metrics::update(|stats| stats.reconnects += 1);
         }
+        connected_before = true;
+        set_state(ConnectionState::Connected);
+        let result = tokio::select! {
+            () = shutdown.cancelled() => break,
+            result = handle_stream(client, config, pool.clone(), sender.clone()) => result,
+        };
+        set_state(ConnectionState::Disconnected);
+        if let Err(err) = result {
+            let delay = config.backoff.jitter(config.backoff.reconnect);
+            error!(
+                "Closed anime subscription to {} with {err}, Reconnecting in {:.2} seconds",
+                api.url(),
+                delay.as_secs_f32()
+            );
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                () = tokio::time::sleep(delay) => {}
+            }
+        }
+    }
+    set_state(ConnectionState::Disconnected);
+}
+
+/// Replay the collections published since `since`, so announcements that were missed while the bot
+/// was offline are still sent. Collections that were already announced are skipped.
+///
+/// # Errors
+///
+/// Will return an error when the api closes the stream with an error.
+pub async fn backfill(
+    api: &Api,
+    config: Config,
+    pool: Pool<Postgres>,
+    sender: Sender<Subscribed<DownloadCollection>>,
+    since: DateTime<Utc>,
+) -> Result<(), tonic::Status> {
+    let mut client = connect_with_backoff(api, config.backoff).await;
+    let request = proto::api::v2::ListRecentRequest {
+        since: Some(to_timestamp(since)),
+    };
+    let mut stream = client.list_recent(request).await?.into_inner();
+    info!("Backfilling downloads since {since}");
+    while let Some(incoming_message) = stream.message().await? {
+        process_message(config, pool.clone(), sender.clone(), incoming_message).await;
     }
+    Ok(())
 }
 
-async fn connect_with_backoff(
-    endpoint: &'static str,
-) -> DownloadsClient<tonic::transport::Channel> {
-    let mut backoff = BACKOFF_INTERVAL;
+/// Find the latest downloads for titles matching `title`.
+///
+/// # Errors
+///
+/// Will return an error when the api cannot be reached or returns an invalid collection.
+pub async fn search(api: &Api, title: &str) -> Result<Vec<DownloadCollection>, SearchError> {
+    let channel = api.endpoint()?.connect().await?;
+    let mut client = DownloadsClient::with_interceptor(channel, api.interceptor())
+        .accept_compressed(CompressionEncoding::Gzip);
+    let request = proto::api::v2::SearchRequest {
+        title: title.to_string(),
+    };
+    let response = client.search(request).await?.into_inner();
+    let collections = response
+        .collections
+        .into_iter()
+        .map(DownloadCollection::try_from)
+        .collect::<Result<_, _>>()?;
+    Ok(collections)
+}
+
+/// Connect to the api and check that the downloads service is serving.
+///
+/// An api without the health service is assumed to be serving.
+async fn connect(api: &Api) -> Result<Client, ConnectionError> {
+    let channel = api.endpoint()?.connect().await?;
+    let request = HealthCheckRequest {
+        service: DOWNLOADS_SERVICE.to_string(),
+    };
+    let status = match HealthClient::with_interceptor(channel.clone(), api.interceptor())
+        .check(request)
+        .await
+    {
+        Ok(response) => response.into_inner().status(),
+        Err(status) if status.code() == Code::Unimplemented => ServingStatus::Serving,
+        Err(status) => return Err(status.into()),
+    };
+    if status != ServingStatus::Serving {
+        return Err(ConnectionError::NotServing(status));
+    }
+    Ok(
+        DownloadsClient::with_interceptor(channel, api.interceptor())
+            .accept_compressed(CompressionEncoding::Gzip),
+    )
+}
+
+async fn connect_with_backoff(api: &Api, config: BackoffConfig) -> Client {
+    let mut backoff = config.initial;
 
     loop {
-        match DownloadsClient::connect(endpoint).await {
-            Ok(client) => return client.accept_compressed(CompressionEncoding::Gzip),
+        match connect(api).await {
+            Ok(client) => {
+                metrics::update(|stats| stats.backoff = Duration::ZERO);
+                return client;
+            }
             Err(err) => {
+                let delay = config.jitter(backoff);
+                metrics::update(|stats| stats.backoff = delay);
                 error!(
-                    "Failed to connect to {endpoint} with error: {err}. Retrying in {:.2} seconds",
-                    backoff.as_secs_f32()
+                    "Failed to connect to {} with error: {err}. Retrying in {:.2} seconds",
+                    api.url(),
+                    delay.as_secs_f32()
                 );
-                tokio::time::sleep(backoff).await;
-                backoff = min(backoff * 2, MAX_BACKOFF);
+                tokio::time::sleep(delay).await;
+                backoff = config.next(backoff);
             }
         }
     }
 }
 
 async fn handle_stream(
-    mut client: DownloadsClient<tonic::transport::Channel>,
+    mut client: Client,
+    config: Config,
     pool: Pool<Postgres>,
     sender: Sender<Subscribed<DownloadCollection>>,
 ) -> Result<(), ConnectionError> {
@@ -179,25 +514,24 @@ async fn handle_stream(
         let Some(incoming_message) = stream.get_mut().message().await? else {
             return Err(ConnectionError::Closed);
         };
-        process_message(pool.clone(), sender.clone(), incoming_message).await;
+        process_message(config, pool.clone(), sender.clone(), incoming_message).await;
     }
 }
 
 #[instrument(skip_all)]
 async fn process_message(
+    config: Config,
     pool: Pool<Postgres>,
     sender: Sender<Subscribed<DownloadCollection>>,
     incoming_message: proto::api::v2::DownloadCollection,
 ) {
     debug!("Got message: {incoming_message:?}");
+    metrics::update(|stats| stats.messages_received += 1);
 
     // Filter incomplete messages
-    if !incoming_message
-        .downloads
-        .iter()
-        .any(|download| download.resolution == 1080)
-    {
+    if !config.is_complete(&incoming_message) {
         debug!("Message was incomplete, skipping");
+        metrics::update(|stats| stats.messages_filtered += 1);
         return;
     }
 
@@ -205,20 +539,69 @@ async fn process_message(
         Ok(collection) => collection,
         Err(err) => {
             error!("Failed to convert message to DownloadCollection: {err}");
+            metrics::update(|stats| stats.conversion_errors += 1);
             return;
         }
     };
 
-    let Ok(subscribers) = get_subscribers(pool, &collection.title).await else {
+    let variant = collection.variant.to_string();
+    match db::insert_processed_collection(&pool, &collection.title, &variant, collection.updated_at)
+        .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            debug!("Message was already processed, skipping");
+            metrics::update(|stats| stats.messages_filtered += 1);
+            return;
+        }
+        Err(err) => error!("Failed to record processed message: {err}"),
+    }
+
+    let Ok(mut subscribers) = get_subscribers(pool.clone(), &collection.title).await else {
+        forget_collection(&pool, &collection, &[]).await;
         return;
     };
+    let now = Utc::now();
+    subscribers.retain(|subscription| subscription.preferences.accepts(&collection, now));
+    let subscribers = filter_announced(&pool, &collection, subscribers).await;
+    if subscribers.is_empty() {
+        debug!("All subscribers were already notified, skipping");
+        metrics::update(|stats| stats.messages_filtered += 1);
+        return;
+    }
 
     let outbound_message = Subscribed {
         content: collection,
         subscribers,
     };
-    if let Err(err) = sender.send(outbound_message).await {
-        error!("Failed to forward incoming message: {err}");
+    if let Err(SendError(message)) = sender.send(outbound_message).await {
+        error!("Failed to forward incoming message, the receiver is closed");
+        forget_collection(&pool, &message.content, &message.subscribers).await;
+    }
+}
+
+/// Remove the records that `collection` was processed and announced to `subscribers`, so it is
+/// announced when it is received again.
+async fn forget_collection(
+    pool: &Pool<Postgres>,
+    collection: &DownloadCollection,
+    subscribers: &[Subscription],
+) {
+    let variant = collection.variant.to_string();
+    let title = &collection.title;
+    if let Err(err) =
+        db::delete_processed_collection(pool, title, &variant, collection.updated_at).await
+    {
+        error!("Failed to forget processed message: {err}");
+    }
+    for subscription in subscribers {
+        let channel_id = match subscription.subscriber {
+            Subscriber::User(user_id) => user_id.get(),
+            Subscriber::Channel { channel_id, .. } => channel_id.get(),
+        };
+        if let Err(err) = db::delete_sent_announcement(pool, title, &variant, channel_id).await {
+            error!("Failed to forget announcement to {channel_id}: {err}");
+        }
     }
 }
 
@@ -226,12 +609,12 @@ async fn process_message(
 async fn get_subscribers(
     pool: Pool<Postgres>,
     title: &str,
-) -> Result<Vec<Subscriber>, SubscriptionError> {
-    let channels: Vec<_> = sqlx::query_file!("queries/find_subscribed_channels.sql", title)
+) -> Result<Vec<Subscription>, SubscriptionError> {
+    let channels = sqlx::query_file!("queries/find_subscribed_channels.sql", title)
         .fetch(&pool)
         .err_into::<SubscriptionError>()
         .and_then(|record| async move {
-            Ok(Subscriber::Channel {
+            let subscriber = Subscriber::Channel {
                 channel_id: record
                     .channel_id
                     .parse()
@@ -240,44 +623,126 @@ async fn get_subscribers(
                     .guild_id
                     .parse()
                     .map_err(|err| SubscriptionError::ParseInt(err, "guild_id"))?,
+            };
+            let preferences = SubscriptionPreferences {
+                resolution: record.resolution.map(u16::try_from).transpose()?,
+                variants: VariantFilter {
+                    episodes: record.announce_episodes,
+                    batches: record.announce_batches,
+                    movies: record.announce_movies,
+                },
+                mute: mute_window(record.mute_start, record.mute_end),
+            };
+            Ok(Subscription {
+                subscriber,
+                preferences,
             })
         })
-        .try_collect()
-        .await?;
+        .try_collect::<Vec<_>>();
+    let mut subscribers = db::metrics::instrument("find_subscribed_channels", channels).await?;
 
-    if channels.is_empty() {
+    let users = sqlx::query_file!("queries/find_subscribed_users.sql", title)
+        .fetch(&pool)
+        .err_into::<SubscriptionError>()
+        .and_then(|record| async move {
+            let user_id = record
+                .user_id
+                .parse()
+                .map_err(|err| SubscriptionError::ParseInt(err, "user_id"))?;
+            let preferences = SubscriptionPreferences {
+                resolution: record.resolution.map(u16::try_from).transpose()?,
+                variants: VariantFilter {
+                    episodes: record.announce_episodes,
+                    batches: record.announce_batches,
+                    movies: record.announce_movies,
+                },
+                mute: mute_window(record.mute_start, record.mute_end),
+            };
+            Ok(Subscription {
+                subscriber: Subscriber::User(user_id),
+                preferences,
+            })
+        })
+        .try_collect::<Vec<_>>();
+    subscribers.extend(db::metrics::instrument("find_subscribed_users", users).await?);
+
+    if subscribers.is_empty() {
         let error = SubscriptionError::Empty;
         info!("{error}");
         return Err(error);
     }
-    Ok(channels)
+    Ok(subscribers)
+}
+
+fn mute_window(start: Option<NaiveTime>, end: Option<NaiveTime>) -> Option<MuteWindow> {
+    Some(MuteWindow {
+        start: start?,
+        end: end?,
+    })
+}
+
+/// Remove the subscribers that were already notified of this collection.
+///
+/// Subscribers are kept when the announcement could not be recorded, as a duplicate message is
+/// preferred over a missed one. For the same reason the record is removed again when the
+/// announcement cannot be delivered, see [`db::delete_sent_announcement`].
+async fn filter_announced(
+    pool: &Pool<Postgres>,
+    collection: &DownloadCollection,
+    subscribers: Vec<Subscription>,
+) -> Vec<Subscription> {
+    let variant = collection.variant.to_string();
+    let mut unannounced = Vec::with_capacity(subscribers.len());
+    for subscription in subscribers {
+        let channel_id = match subscription.subscriber {
+            Subscriber::User(user_id) => user_id.get(),
+            Subscriber::Channel { channel_id, .. } => channel_id.get(),
+        };
+        match db::insert_sent_announcement(pool, &collection.title, &variant, channel_id).await {
+            Ok(true) => unannounced.push(subscription),
+            Ok(false) => debug!("Already announced to {channel_id}"),
+            Err(err) => {
+                error!("Failed to record announcement to {channel_id}: {err}");
+                unannounced.push(subscription);
+            }
+        }
+    }
+    unannounced
 }
 
 impl TryFrom<proto::api::v2::DownloadCollection> for DownloadCollection {
     type Error = ConversionError;
 
+    /// Missing metadata does not fail the conversion, so messages of newer api versions can still be
+    /// announced. An unset or unknown variant becomes [`DownloadVariant::Unknown`] and missing
+    /// timestamps fall back to the time of conversion.
     fn try_from(value: proto::api::v2::DownloadCollection) -> Result<Self, Self::Error> {
-        let download_variant = value
-            .variant
-            .ok_or(ConversionError::MissingField("variant"))?;
-        let created_at_timestamp = value
-            .created_at
-            .ok_or(ConversionError::MissingField("created_at"))?;
-        let updated_at_timestamp = value
-            .updated_at
-            .ok_or(ConversionError::MissingField("updated_at"))?;
-
-        Ok(DownloadCollection {
-            title: value.title,
-            variant: download_variant.into(),
-            created_at: from_timestamp(created_at_timestamp)?,
-            updated_at: from_timestamp(updated_at_timestamp)?,
-            downloads: value
-                .downloads
-                .into_iter()
-                .map(Download::try_from)
-                .collect::<Result<Vec<_>, _>>()?,
-        })
+        let variant = value.variant.map_or_else(
+            || {
+                warn!(title = value.title, "Message has an unknown variant");
+                DownloadVariant::Unknown
+            },
+            DownloadVariant::from,
+        );
+        let created_at = if let Some(timestamp) = value.created_at {
+            from_timestamp(timestamp)?
+        } else {
+            warn!(title = value.title, "Message is missing created_at");
+            Utc::now()
+        };
+        let mut builder = DownloadCollection::builder(value.title)
+            .variant(variant)
+            .created_at(created_at);
+        if let Some(timestamp) = value.updated_at {
+            builder = builder.updated_at(from_timestamp(timestamp)?);
+        }
+
+        let downloads = value
+            .downloads
+            .into_iter()
+            .map(Download::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(builder.downloads(downloads).build()?)
     }
 }
 
@@ -310,11 +775,17 @@ impl TryFrom<proto::api::v2::Download> for Download {
     type Error = ConversionError;
 
     fn try_from(value: proto::api::v2::Download) -> Result<Self, Self::Error> {
-        let published_date_timestamp = value
-            .published_date
-            .ok_or(ConversionError::MissingField("published_date"))?;
+        let published_date = if let Some(timestamp) = value.published_date {
+            from_timestamp(timestamp)?
+        } else {
+            warn!(
+                file_name = value.file_name,
+                "Download is missing published_date"
+            );
+            Utc::now()
+        };
         Ok(Download {
-            published_date: from_timestamp(published_date_timestamp)?,
+            published_date,
             resolution: u16::try_from(value.resolution)?,
             comments: value.comments,
             torrent: value.torrent,
@@ -323,8 +794,218 @@ impl TryFrom<proto::api::v2::Download> for Download {
     }
 }
 
+impl From<DownloadCollection> for proto::api::v2::DownloadCollection {
+    fn from(value: DownloadCollection) -> Self {
+        Self {
+            title: value.title,
+            downloads: value.downloads.into_iter().map(Into::into).collect(),
+            created_at: Some(to_timestamp(value.created_at)),
+            updated_at: Some(to_timestamp(value.updated_at)),
+            variant: proto_variant(value.variant),
+        }
+    }
+}
+
+fn proto_variant(variant: DownloadVariant) -> Option<proto::api::v2::download_collection::Variant> {
+    use proto::api::v2::download_collection::Variant;
+    match variant {
+        DownloadVariant::Batch(range) => Some(Variant::Batch(proto::api::v2::Batch {
+            start: *range.start(),
+            end: *range.end(),
+        })),
+        DownloadVariant::Episode(episode) => Some(Variant::Episode(episode.into())),
+        DownloadVariant::Movie => Some(Variant::Movie(proto::api::v2::Movie {})),
+        DownloadVariant::Unknown => None,
+    }
+}
+
+impl From<Episode> for proto::api::v2::Episode {
+    fn from(val: Episode) -> Self {
+        Self {
+            number: val.number,
+            decimal: val.decimal.unwrap_or_default(),
+            version: val.version.unwrap_or_default(),
+            extra: val.extra.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Download> for proto::api::v2::Download {
+    fn from(value: Download) -> Self {
+        Self {
+            published_date: Some(to_timestamp(value.published_date)),
+            resolution: u32::from(value.resolution),
+            comments: value.comments,
+            torrent: value.torrent,
+            file_name: value.file_name,
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_wrap)]
+fn to_timestamp(date_time: DateTime<Utc>) -> Timestamp {
+    Timestamp {
+        seconds: date_time.timestamp(),
+        nanos: date_time.timestamp_subsec_nanos() as i32,
+    }
+}
+
 #[allow(clippy::cast_sign_loss)]
 fn from_timestamp(timestamp: Timestamp) -> Result<DateTime<Utc>, ConversionError> {
     DateTime::from_timestamp(timestamp.seconds, timestamp.nanos as u32)
         .ok_or(ConversionError::InvalidTimeStamp(timestamp))
 }
+
+#[cfg(test)]
+mod test {
+    use prost::Message;
+
+    use super::*;
+
+    fn date_time(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(seconds, 123_456_789).unwrap()
+    }
+
+    fn collection(variant: DownloadVariant) -> DownloadCollection {
+        DownloadCollection::builder("Frieren")
+            .variant(variant)
+            .created_at(date_time(1_700_000_000))
+            .updated_at(date_time(1_700_000_600))
+            .download(Download {
+                published_date: date_time(1_700_000_300),
+                resolution: 1080,
+                comments: "https://example.com/comments".to_string(),
+                torrent: "https://example.com/torrent".to_string(),
+                file_name: "Frieren - 01 (1080p).mkv".to_string(),
+            })
+            .download(Download {
+                published_date: date_time(1_700_000_200),
+                resolution: 720,
+                comments: "https://example.com/comments".to_string(),
+                torrent: "https://example.com/torrent".to_string(),
+                file_name: "Frieren - 01 (720p).mkv".to_string(),
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn round_trip(collection: DownloadCollection) {
+        let message = proto::api::v2::DownloadCollection::from(collection.clone());
+        let converted = DownloadCollection::try_from(message).unwrap();
+        assert_eq!(converted, collection);
+    }
+
+    #[test]
+    fn episode_round_trip() {
+        round_trip(collection(DownloadVariant::Episode(Episode {
+            number: 12,
+            decimal: Some(5),
+            version: Some(2),
+            extra: Some("END".to_string()),
+        })));
+        round_trip(collection(DownloadVariant::Episode(Episode {
+            number: 1,
+            decimal: None,
+            version: None,
+            extra: None,
+        })));
+    }
+
+    #[test]
+    fn batch_round_trip() {
+        round_trip(collection(DownloadVariant::Batch(1..=28)));
+    }
+
+    #[test]
+    fn movie_round_trip() {
+        round_trip(collection(DownloadVariant::Movie));
+    }
+
+    #[test]
+    fn unknown_variant_is_kept() {
+        let mut bytes =
+            proto::api::v2::DownloadCollection::from(collection(DownloadVariant::Movie))
+                .encode_to_vec();
+        // Replace the movie variant with an empty message in a field this version does not know.
+        let movie = [(7 << 3) | 2, 0];
+        let start = bytes
+            .windows(movie.len())
+            .rposition(|window| window == movie)
+            .unwrap();
+        bytes.splice(start..start + movie.len(), [(12 << 3) | 2, 0]);
+
+        let message = proto::api::v2::DownloadCollection::decode(bytes.as_slice()).unwrap();
+        let converted = DownloadCollection::try_from(message).unwrap();
+        assert_eq!(converted.variant, DownloadVariant::Unknown);
+        assert_eq!(converted.downloads.len(), 2);
+    }
+
+    fn episode(number: u32, decimal: Option<u32>, version: Option<u32>) -> Episode {
+        Episode {
+            number,
+            decimal,
+            version,
+            extra: None,
+        }
+    }
+
+    fn hash(episode: &Episode) -> u64 {
+        let mut hasher = std::hash::DefaultHasher::new();
+        episode.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn episodes_are_ordered_by_number_decimal_and_version() {
+        let mut episodes = vec![
+            episode(2, None, None),
+            episode(1, Some(5), None),
+            episode(1, None, Some(2)),
+            episode(1, None, None),
+        ];
+        episodes.sort();
+        assert_eq!(
+            episodes,
+            vec![
+                episode(1, None, None),
+                episode(1, None, Some(2)),
+                episode(1, Some(5), None),
+                episode(2, None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_decimal_and_version_equal_their_defaults() {
+        let implicit = episode(3, None, None);
+        let explicit = Episode {
+            extra: Some("END".to_string()),
+            ..episode(3, Some(0), Some(1))
+        };
+        assert_eq!(implicit, explicit);
+        assert_eq!(hash(&implicit), hash(&explicit));
+        assert_ne!(implicit, episode(3, None, Some(2)));
+    }
+
+    #[test]
+    fn newer_version_supersedes_same_episode() {
+        assert!(episode(4, None, Some(2)).supersedes(&episode(4, None, None)));
+        assert!(!episode(4, None, None).supersedes(&episode(4, None, Some(2))));
+        assert!(!episode(4, None, None).supersedes(&episode(4, None, Some(1))));
+        assert!(!episode(5, None, Some(2)).supersedes(&episode(4, None, None)));
+        assert!(!episode(4, Some(5), Some(2)).supersedes(&episode(4, None, None)));
+    }
+
+    #[test]
+    fn missing_timestamps_are_tolerated() {
+        let mut message =
+            proto::api::v2::DownloadCollection::from(collection(DownloadVariant::Movie));
+        message.created_at = None;
+        message.updated_at = None;
+        for download in &mut message.downloads {
+            download.published_date = None;
+        }
+        let converted = DownloadCollection::try_from(message).unwrap();
+        assert_eq!(converted.created_at, converted.updated_at);
+    }
+}
@@ -0,0 +1,33 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+static STREAM_STATS: Mutex<StreamStats> = Mutex::new(StreamStats {
+    reconnects: 0,
+    backoff: Duration::ZERO,
+    messages_received: 0,
+    messages_filtered: 0,
+    conversion_errors: 0,
+});
+
+/// Counters of the download stream from the anime api.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Times the stream was opened again after it was closed.
+    pub reconnects: u64,
+    /// Current delay before the next connection attempt, zero while connected.
+    pub backoff: Duration,
+    pub messages_received: u64,
+    /// Messages that were skipped because they were incomplete or had no subscribers left.
+    pub messages_filtered: u64,
+    pub conversion_errors: u64,
+}
+
+/// Snapshot of the stream counters.
+#[must_use]
+pub fn stream_stats() -> StreamStats {
+    *STREAM_STATS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) fn update(f: impl FnOnce(&mut StreamStats)) {
+    f(&mut STREAM_STATS.lock().unwrap_or_else(PoisonError::into_inner));
+}
@@ -0,0 +1,89 @@
+use sqlx::types::chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use tokio::sync::mpsc::Sender;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+use crate::{backfill, subscribe, Api, Config, ConnectionState, DownloadCollection, Subscribed};
+
+/// A producer of download collections for the announcement pipeline.
+pub trait DownloadSource {
+    /// Start sending collections with their subscribers to `sender` in the background.
+    fn run(self, sender: Sender<Subscribed<DownloadCollection>>) -> JoinHandle<()>;
+}
+
+/// Streams downloads from one or more anime api endpoints over gRPC.
+#[derive(Debug, Clone)]
+pub struct GrpcSource {
+    apis: Vec<Api>,
+    config: Config,
+    pool: Pool<Postgres>,
+    state: watch::Sender<ConnectionState>,
+    shutdown: CancellationToken,
+    backfill_since: Option<DateTime<Utc>>,
+}
+
+impl GrpcSource {
+    /// ### Arguments
+    ///
+    /// - `apis` - the locations of the anime api, the first one is used for the backfill
+    /// - `config` - how the collections from the anime api are processed
+    /// - `pool` - the database connection pool
+    /// - `state` - receives the state of the connection to the anime api
+    /// - `shutdown` - stops the subscription when cancelled
+    #[must_use]
+    pub fn new(
+        apis: Vec<Api>,
+        config: Config,
+        pool: Pool<Postgres>,
+        state: watch::Sender<ConnectionState>,
+        shutdown: CancellationToken,
+    ) -> Self {
+        Self {
+            apis,
+            config,
+            pool,
+            state,
+            shutdown,
+            backfill_since: None,
+        }
+    }
+
+    /// Also send the collections released since `since` when the source starts.
+    #[must_use]
+    pub fn with_backfill(mut self, since: DateTime<Utc>) -> Self {
+        self.backfill_since = Some(since);
+        self
+    }
+}
+
+impl DownloadSource for GrpcSource {
+    fn run(self, sender: Sender<Subscribed<DownloadCollection>>) -> JoinHandle<()> {
+        if let (Some(since), Some(api)) = (self.backfill_since, self.apis.first().cloned()) {
+            let pool = self.pool.clone();
+            let sender = sender.clone();
+            let shutdown = self.shutdown.clone();
+            let config = self.config;
+            tokio::spawn(async move {
+                tokio::select! {
+                    () = shutdown.cancelled() => {}
+                    result = backfill(&api, config, pool, sender, since) => {
+                        if let Err(err) = result {
+                            error!("Failed to backfill anime downloads: {err}");
+                        }
+                    }
+                }
+            });
+        }
+        tokio::spawn(subscribe(
+            self.apis,
+            self.config,
+            self.pool,
+            sender,
+            self.state,
+            self.shutdown,
+        ))
+    }
+}
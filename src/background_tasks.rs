@@ -1,110 +1,571 @@
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
 
-use anyhow::anyhow;
-use chrono::{DateTime, Utc};
-use serenity::all::{CacheHttp, CreateMessage, Message, UserId};
+use chrono::Utc;
+use serenity::all::{
+    CacheHttp, CreateMessage, EditMessage, GetMessages, Mentionable, Message, MessageFlags,
+    MessageId, UserId,
+};
 use serenity::builder::{Builder, CreateEmbed};
 use serenity::cache::Cache;
+use serenity::gateway::ShardManager;
 use serenity::http::Http;
 use serenity::model::id::GuildId;
 use serenity::model::prelude::ChannelId;
-use tokio::sync::mpsc::{channel, Receiver};
-use tokio::time::{interval_at, Instant, Interval};
-use tracing::{error, info, instrument};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender, WeakSender};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{timeout_at, Instant};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::{error, info, instrument, warn};
 use url::Url;
 
-use otaku::db::Pool;
-use otaku::{Download, DownloadCollection, Subscribed, Subscriber};
+use otaku::db::guild_settings::GifDelivery;
+use otaku::db::{GuildSettingsConnection, Pool};
+use otaku::{Download, DownloadCollection, DownloadSource, Subscribed, Subscriber};
 
 use crate::cache;
-use crate::commands::gifs;
-use crate::consts::SHORT_CACHE_LIFETIME;
-
-fn interval_at_previous_period(period: Duration) -> anyhow::Result<Interval> {
-    let start = Instant::now();
-    let now: DateTime<Utc> = Utc::now();
-    let seconds = u64::try_from(now.timestamp())?;
-    let sub_seconds = seconds % period.as_secs();
-    let minute = DateTime::from_timestamp(i64::try_from(seconds - sub_seconds)?, 0)
-        .ok_or(anyhow!("failed to create new date time"))?;
-    let offset = (now - minute).to_std()?;
-    let best_effort_start = start.checked_sub(offset).unwrap_or(start);
-    Ok(interval_at(best_effort_start, period))
-}
-
-pub(crate) fn start_gif_updater(
-    tenor: tenor::Client<'static>,
+use crate::commands::{gifs, poll};
+use crate::config::{self, SharedConfig};
+use crate::consts::{
+    ANNOUNCEMENT_BATCH_WINDOW, ANNOUNCEMENT_RETRY_ATTEMPTS, ANNOUNCEMENT_RETRY_INTERVAL,
+    ANNOUNCEMENT_RETRY_QUEUE_SIZE, DATABASE_CLEANUP_INTERVAL, ERROR_REPORT_INTERVAL,
+    GIF_REFRESH_INTERVAL, HURRY_ESCALATION_INTERVAL, HURRY_ESCALATION_STALE_AFTER,
+    MAX_EMBEDS_PER_MESSAGE, MAX_MESSAGE_LENGTH, PLAY_SESSION_INTERVAL, PLAY_SESSION_STALE_AFTER,
+    POLL_INTERVAL, REMINDER_INTERVAL, RETENTION_POLICY, SHORT_CACHE_LIFETIME,
+};
+use crate::error_report::ErrorReports;
+use crate::gif_provider::Providers;
+use crate::i18n::{Locale, Text};
+use crate::presence;
+use crate::scheduler::{Schedule, Scheduler};
+
+/// Add the refresh of the gifs in the cache and the trim of the cache to `scheduler`.
+///
+/// ### Arguments
+///
+/// - `scheduler` - runs the jobs
+/// - `gif_providers` - the providers to search the gifs on
+/// - `gif_cache` - the cache of GIFs
+/// - `config` - the lifetimes of the refreshed gifs
+/// - `cold_cache` - refresh the gifs right away instead of at the stored next run
+pub(crate) fn schedule_gif_jobs(
+    scheduler: &mut Scheduler,
+    gif_providers: Providers<'static>,
     gif_cache: cache::Memory<[Url]>,
+    config: SharedConfig,
+    cold_cache: bool,
+) {
+    let context = (gif_providers, gif_cache.clone(), config);
+    scheduler.add(
+        "gif_refresh",
+        Schedule::Aligned(GIF_REFRESH_INTERVAL),
+        move || {
+            let context = context.clone();
+            async move {
+                gifs::update_gif_cache(&context).await;
+                Ok(())
+            }
+        },
+    );
+    if cold_cache {
+        scheduler.run_at_start("gif_refresh");
+    }
+    scheduler.add(
+        "gif_cache_trim",
+        Schedule::Every(SHORT_CACHE_LIFETIME),
+        move || {
+            let gif_cache = gif_cache.clone();
+            async move {
+                gif_cache.trim();
+                Ok(())
+            }
+        },
+    );
+}
+
+/// Cache the GIFs the commands fall back on.
+///
+/// Returns whether the cache still misses GIFs, then the `gif_refresh` job should run right away
+/// instead of at its stored next run.
+///
+/// ### Arguments
+///
+/// - `gif_providers` - the providers to search the gifs on
+/// - `gif_cache` - the cache of GIFs
+/// - `config` - the lifetimes of the refreshed gifs
+pub(crate) async fn warm_gif_cache(
+    gif_providers: Providers<'static>,
+    gif_cache: cache::Memory<[Url]>,
+    config: SharedConfig,
+) -> bool {
+    let context = (gif_providers, gif_cache, config);
+    gifs::warm_gif_cache(&context).await;
+    gifs::is_cache_cold(&context).await
+}
+
+/// Launch reloading of the config whenever the process receives `SIGHUP`.
+///
+/// ### Arguments
+///
+/// - `config` - the config to replace
+/// - `shutdown` - stops listening for the signal when cancelled
+#[cfg(unix)]
+pub(crate) fn start_config_reload(
+    config: SharedConfig,
+    shutdown: CancellationToken,
 ) -> anyhow::Result<()> {
-    let context = (tenor, gif_cache);
-    let mut interval = interval_at_previous_period(Duration::from_secs(6 * 3600))?;
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = signal(SignalKind::hangup())?;
     tokio::spawn(async move {
         loop {
-            interval.tick().await;
-            gifs::update_gif_cache(&context).await;
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                _ = hangup.recv() => {}
+            }
+            match config::reload(&config) {
+                Ok(runtime_config) => info!(?runtime_config, "Reloaded config"),
+                Err(err) => error!("Failed to reload config: {err}"),
+            }
         }
     });
     Ok(())
 }
 
-/// Launch periodic trim of the GIF cache.
+/// Launch the rotation of the activity of the bot, showing each activity of
+/// [`presence::activities`] for the configured interval.
 ///
 /// ### Arguments
 ///
-/// - `gif_cache` - the cache of GIFs
-pub(crate) fn start_cache_trim(gif_cache: cache::Memory<[Url]>) {
-    let mut interval = tokio::time::interval(SHORT_CACHE_LIFETIME);
+/// - `shard_manager` - the shards to set the activity on
+/// - `discord_cache` - counts the guilds of the bot
+/// - `config` - the interval between the activities
+/// - `latest_release` - the title of the last announced anime release
+/// - `shutdown` - stops the rotation when cancelled
+pub(crate) fn start_presence_rotation(
+    shard_manager: Arc<ShardManager>,
+    discord_cache: Arc<Cache>,
+    config: SharedConfig,
+    latest_release: watch::Receiver<Option<String>>,
+    shutdown: CancellationToken,
+) {
     tokio::spawn(async move {
-        loop {
-            interval.tick().await;
-            gif_cache.trim().await;
+        for index in 0usize.. {
+            let interval = config.borrow().presence_interval;
+            tokio::select! {
+                () = shutdown.cancelled() => break,
+                () = tokio::time::sleep(interval) => {}
+            }
+            let activities = presence::activities(
+                discord_cache.guild_count(),
+                latest_release.borrow().as_deref(),
+                Utc::now().date_naive(),
+            );
+            let Some(activity) = activities.get(index % activities.len()) else {
+                continue;
+            };
+            for runner in shard_manager.runners.lock().await.values() {
+                runner.runner_tx.set_activity(Some(activity.clone()));
+            }
         }
     });
 }
 
-/// Subscribe to announcements of new anime episodes from the anime api.
+/// Add the removal of database rows that are past their retention, the delivery of due
+/// reminders, the pings of the sessions of /play, the closing of polls and the follow-ups of
+/// /hurry to `scheduler`.
 ///
 /// ### Arguments
 ///
+/// - `scheduler` - runs the jobs
 /// - `pool` - the database connection pool
-/// - `anime_url` - the base url of the anime api
+/// - `discord_http` - the discord http client
+pub(crate) fn schedule_database_jobs(
+    scheduler: &mut Scheduler,
+    pool: Pool,
+    discord_http: Arc<Http>,
+) {
+    let cleanup_pool = pool.clone();
+    scheduler.add(
+        "database_cleanup",
+        Schedule::Every(DATABASE_CLEANUP_INTERVAL),
+        move || {
+            let pool = cleanup_pool.clone();
+            async move {
+                let report = otaku::db::cleanup(&pool, RETENTION_POLICY).await?;
+                info!(
+                    sent_announcements = report.sent_announcements,
+                    processed_collections = report.processed_collections,
+                    subscriptions = report.subscriptions,
+                    guilds = report.guilds,
+                    "Cleaned up database"
+                );
+                Ok(())
+            }
+        },
+    );
+    let reminder_pool = pool.clone();
+    let reminder_http = discord_http.clone();
+    scheduler.add("reminders", Schedule::Every(REMINDER_INTERVAL), move || {
+        deliver_reminders(reminder_pool.clone(), reminder_http.clone())
+    });
+    let session_pool = pool.clone();
+    let session_http = discord_http.clone();
+    scheduler.add(
+        "play_sessions",
+        Schedule::Every(PLAY_SESSION_INTERVAL),
+        move || ping_play_sessions(session_pool.clone(), session_http.clone()),
+    );
+    let poll_pool = pool.clone();
+    let poll_http = discord_http.clone();
+    scheduler.add("polls", Schedule::Every(POLL_INTERVAL), move || {
+        close_polls(poll_pool.clone(), poll_http.clone())
+    });
+    scheduler.add(
+        "hurry_escalations",
+        Schedule::Every(HURRY_ESCALATION_INTERVAL),
+        move || post_hurry_escalations(pool.clone(), discord_http.clone()),
+    );
+}
+
+/// Send the reminders that are due, in their channel or else by direct message.
+async fn deliver_reminders(pool: Pool, discord_http: Arc<Http>) -> anyhow::Result<()> {
+    let reminders = otaku::db::reminders::take_due_reminders(&pool, Utc::now()).await?;
+    for reminder in reminders {
+        let user_id = UserId::from(reminder.user_id);
+        let message = CreateMessage::new().content(format!("<@{user_id}> {}", reminder.message));
+        let result = match reminder.channel_id {
+            Some(channel_id) => {
+                ChannelId::from(channel_id)
+                    .send_message(&discord_http, message)
+                    .await
+            }
+            None => user_id.direct_message(&discord_http, message).await,
+        };
+        if let Err(err) = result {
+            warn!(reminder = reminder.id, "Failed to deliver reminder: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// Ping the members that joined the sessions of /play that are starting, the sessions that
+/// started more than [`PLAY_SESSION_STALE_AFTER`] ago are removed without a ping.
+async fn ping_play_sessions(pool: Pool, discord_http: Arc<Http>) -> anyhow::Result<()> {
+    let now = Utc::now();
+    let sessions = otaku::db::play_sessions::take_due_sessions(&pool, now).await?;
+    for session in sessions {
+        let stale = (now - session.starts_at)
+            .to_std()
+            .is_ok_and(|late| late > PLAY_SESSION_STALE_AFTER);
+        if stale || session.attendees.is_empty() {
+            info!(
+                session = session.id,
+                stale, "Removed play session without pinging"
+            );
+            continue;
+        }
+        let locale = match session.guild_id {
+            Some(guild_id) => match pool.get_guild_settings(guild_id).await {
+                Ok(settings) => settings.locale.as_deref().and_then(Locale::from_code),
+                Err(err) => {
+                    warn!(
+                        session = session.id,
+                        "Failed to get the guild settings: {err}"
+                    );
+                    None
+                }
+            },
+            None => None,
+        }
+        .unwrap_or(Locale::English);
+        let mentions = session
+            .attendees
+            .iter()
+            .map(|user_id| format!("<@{user_id}>"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let content = match &session.game {
+            Some(game) => Text::SessionStarting.format(
+                locale,
+                &[("mentions", mentions.as_str()), ("game", game.as_str())],
+            ),
+            None => Text::SessionStartingAnyGame.format(locale, &[("mentions", mentions.as_str())]),
+        };
+        let message = CreateMessage::new().content(content);
+        if let Err(err) = ChannelId::from(session.channel_id)
+            .send_message(&discord_http, message)
+            .await
+        {
+            warn!(session = session.id, "Failed to ping play session: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// Show the final tallies of the polls that closed and remove their vote buttons.
+async fn close_polls(pool: Pool, discord_http: Arc<Http>) -> anyhow::Result<()> {
+    let polls = otaku::db::polls::take_due_polls(&pool, Utc::now()).await?;
+    for poll in polls {
+        let Some(message_id) = poll.message_id else {
+            info!(poll = poll.id, "Removed poll without message");
+            continue;
+        };
+        let edit = EditMessage::new()
+            .embed(poll::closed_embed(&poll))
+            .components(Vec::new());
+        if let Err(err) = ChannelId::from(poll.channel_id)
+            .edit_message(&discord_http, MessageId::from(message_id), edit)
+            .await
+        {
+            warn!(poll = poll.id, "Failed to close the poll: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// Post the follow-ups of /hurry that are due to the members that stayed silent since they were
+/// told to hurry up, the follow-ups that were due more than [`HURRY_ESCALATION_STALE_AFTER`] ago
+/// are removed without posting.
+async fn post_hurry_escalations(pool: Pool, discord_http: Arc<Http>) -> anyhow::Result<()> {
+    let now = Utc::now();
+    let escalations = otaku::db::hurry_escalations::take_due_escalations(&pool, now).await?;
+    for escalation in escalations {
+        let stale = (now - escalation.due_at)
+            .to_std()
+            .is_ok_and(|late| late > HURRY_ESCALATION_STALE_AFTER);
+        if stale {
+            info!(
+                escalation = escalation.id,
+                "Removed stale hurry up follow-up"
+            );
+            continue;
+        }
+        let channel_id = ChannelId::from(escalation.channel_id);
+        let user_id = UserId::from(escalation.user_id);
+        let after = GetMessages::new()
+            .after(MessageId::from(escalation.message_id))
+            .limit(100);
+        match channel_id.messages(&discord_http, after).await {
+            Ok(messages) if messages.iter().any(|message| message.author.id == user_id) => continue,
+            Ok(_) => {}
+            Err(err) => {
+                warn!(
+                    escalation = escalation.id,
+                    "Failed to check the messages since hurry up: {err}"
+                );
+                continue;
+            }
+        }
+        let settings = match escalation.guild_id {
+            Some(guild_id) => match pool.get_guild_settings(guild_id).await {
+                Ok(settings) => Some(settings),
+                Err(err) => {
+                    warn!(
+                        escalation = escalation.id,
+                        "Failed to get the guild settings: {err}"
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        let locale = settings
+            .as_ref()
+            .and_then(|settings| settings.locale.as_deref())
+            .and_then(Locale::from_code)
+            .unwrap_or(Locale::English);
+        let mention = user_id.mention().to_string();
+        let content = Text::HurryEscalation.format(locale, &[("mention", &mention)]);
+        let gif_message = CreateMessage::new().flags(MessageFlags::SUPPRESS_NOTIFICATIONS);
+        let gif_message = match settings.map(|settings| settings.gif_delivery) {
+            Some(GifDelivery::Embed) => {
+                gif_message.embed(gifs::gif_embed(locale, &escalation.query, &escalation.gif))
+            }
+            Some(GifDelivery::Url) | None => gif_message.content(escalation.gif),
+        };
+        let message = CreateMessage::new().content(content);
+        if let Err(err) = channel_id.send_message(&discord_http, message).await {
+            warn!(
+                escalation = escalation.id,
+                "Failed to post hurry up follow-up: {err}"
+            );
+            continue;
+        }
+        if let Err(err) = channel_id.send_message(&discord_http, gif_message).await {
+            warn!(
+                escalation = escalation.id,
+                "Failed to post hurry up follow-up GIF: {err}"
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Launch posting of the reported errors in the operator channel. The errors reported within
+/// [`ERROR_REPORT_INTERVAL`] of each other are combined into as few messages as possible.
+///
+/// ### Arguments
+///
+/// - `discord_http` - the discord http client
+/// - `reports` - the reported errors and the channel to post them in
+/// - `shutdown` - stops the reporting when cancelled
+pub(crate) fn start_error_reporter(
+    discord_http: Arc<Http>,
+    reports: ErrorReports,
+    shutdown: CancellationToken,
+) {
+    let ErrorReports {
+        channel_id,
+        mut receiver,
+    } = reports;
+    tokio::spawn(async move {
+        loop {
+            let report = tokio::select! {
+                () = shutdown.cancelled() => break,
+                report = receiver.recv() => match report {
+                    Some(report) => report,
+                    None => break,
+                },
+            };
+            tokio::select! {
+                () = shutdown.cancelled() => {}
+                () = tokio::time::sleep(ERROR_REPORT_INTERVAL) => {}
+            }
+            let mut reports = vec![report];
+            while let Ok(report) = receiver.try_recv() {
+                reports.push(report);
+            }
+            for content in error_report_messages(reports) {
+                let message = CreateMessage::new().content(content);
+                if let Err(err) = channel_id.send_message(&*discord_http, message).await {
+                    // Logged as a warning, because an error would be reported again.
+                    warn!("Failed to post error report in {channel_id}: {err}");
+                }
+            }
+        }
+    });
+}
+
+/// Combine the reports into messages that fit in discord, shortening the reports that are too
+/// long on their own.
+fn error_report_messages(reports: Vec<String>) -> Vec<String> {
+    // Leave room for the list marker and newline of every report.
+    const MAX_REPORT_LENGTH: usize = MAX_MESSAGE_LENGTH - 3;
+    let mut messages = Vec::new();
+    let mut message = String::new();
+    for mut report in reports {
+        if let Some((index, _)) = report.char_indices().nth(MAX_REPORT_LENGTH - 1) {
+            report.truncate(index);
+            report.push('…');
+        }
+        if !message.is_empty() && message.len() + report.len() + 3 > MAX_MESSAGE_LENGTH {
+            messages.push(std::mem::take(&mut message));
+        }
+        message.push_str("- ");
+        message.push_str(&report);
+        message.push('\n');
+    }
+    if !message.is_empty() {
+        messages.push(message);
+    }
+    messages
+}
+
+/// Announce the download collections produced by `source` to their subscribers.
+///
+/// The returned handle completes once `source` has stopped and every announcement was sent or
+/// dropped. Failed announcements are retried once more without delay after `shutdown` is
+/// cancelled.
+///
+/// ### Arguments
+///
+/// - `source` - produces the collections and their subscribers
+/// - `pool` - forgets the announcements that could not be delivered, so they can be sent again
 /// - `discord` - the discord http client and cache
+/// - `latest_release` - receives the title of every announced collection
+/// - `shutdown` - stops waiting between retries when cancelled
 pub(crate) fn start_anime_subscription(
+    source: impl DownloadSource,
     pool: Pool,
-    anime_url: &'static str,
     discord_cache: Arc<Cache>,
     discord_http: Arc<Http>,
-) {
+    latest_release: watch::Sender<Option<String>>,
+    shutdown: CancellationToken,
+) -> JoinHandle<()> {
     let (tx, rx) = channel(16);
-
-    tokio::spawn(otaku::subscribe(anime_url, pool, tx));
-    tokio::spawn(embed_sender(discord_cache, discord_http, rx));
+    source.run(tx);
+    let (retry_tx, retry_rx) = channel(ANNOUNCEMENT_RETRY_QUEUE_SIZE);
+    let retry_handle = tokio::spawn(retry_sender(
+        pool.clone(),
+        discord_cache.clone(),
+        discord_http.clone(),
+        retry_tx.downgrade(),
+        retry_rx,
+        shutdown,
+    ));
+    tokio::spawn(embed_sender(
+        pool,
+        discord_cache,
+        discord_http,
+        retry_tx,
+        latest_release,
+        rx,
+    ));
+    retry_handle
 }
 
 async fn embed_sender(
+    pool: Pool,
     discord_cache: Arc<Cache>,
     discord_http: Arc<Http>,
+    retry_tx: Sender<FailedDelivery>,
+    latest_release: watch::Sender<Option<String>>,
     mut rx: Receiver<Subscribed<DownloadCollection>>,
 ) {
-    loop {
-        if let Some(message) = rx.recv().await {
-            tokio::spawn(process_downloads_subscription(
-                discord_cache.clone(),
-                discord_http.clone(),
-                message,
-            ));
+    let tasks = TaskTracker::new();
+    while let Some(message) = rx.recv().await {
+        latest_release.send_replace(Some(message.content.title.clone()));
+        // Collect the collections that arrive shortly after this one, so a burst of releases is
+        // announced in one message per channel.
+        let mut messages = vec![message];
+        let deadline = Instant::now() + ANNOUNCEMENT_BATCH_WINDOW;
+        while let Ok(Some(message)) = timeout_at(deadline, rx.recv()).await {
+            messages.push(message);
         }
+        tasks.spawn(process_downloads_subscriptions(
+            pool.clone(),
+            discord_cache.clone(),
+            discord_http.clone(),
+            retry_tx.clone(),
+            messages,
+        ));
     }
+    // Keep the retry queue open until the last announcements have been attempted.
+    tasks.close();
+    tasks.wait().await;
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum MessageChannelId {
     User(UserId),
     Guild(GuildId, ChannelId),
 }
 
 impl MessageChannelId {
+    /// The channel the announcements are recorded for, the user for direct messages.
+    fn announcement_channel(self) -> NonZeroU64 {
+        match self {
+            MessageChannelId::User(id) => id.into(),
+            MessageChannelId::Guild(_, channel_id) => channel_id.into(),
+        }
+    }
+
     async fn send_message(
         self,
         cache_http: impl CacheHttp,
@@ -120,12 +581,12 @@ impl MessageChannelId {
         }
     }
 
-    async fn send_embed(
+    async fn send_embeds(
         self,
         cache_http: impl CacheHttp,
-        embed: CreateEmbed,
+        embeds: Vec<CreateEmbed>,
     ) -> Result<Message, serenity::Error> {
-        self.send_message(cache_http, CreateMessage::new().embed(embed))
+        self.send_message(cache_http, CreateMessage::new().embeds(embeds))
             .await
     }
 
@@ -147,32 +608,195 @@ impl MessageChannelId {
     }
 }
 
-#[instrument(skip_all, fields(title))]
-async fn process_downloads_subscription(
+#[instrument(skip_all, fields(collections = messages.len()))]
+async fn process_downloads_subscriptions(
+    pool: Pool,
     discord_cache: Arc<Cache>,
     discord_http: Arc<Http>,
-    message: Subscribed<DownloadCollection>,
+    retry_tx: Sender<FailedDelivery>,
+    messages: Vec<Subscribed<DownloadCollection>>,
 ) {
-    let title = format!("{} {}", message.content.title, message.content.variant);
-    tracing::Span::current().record("title", &title);
+    let mut channel_embeds: HashMap<MessageChannelId, Vec<(Announcement, CreateEmbed)>> =
+        HashMap::new();
+    for message in &messages {
+        let variant = message.content.variant.to_string();
+        let title = format!("{} {variant}", message.content.title);
+        for subscription in &message.subscribers {
+            let embed = download_embed(
+                &title,
+                &message.content,
+                subscription.preferences.resolution,
+            );
+            let announcement = Announcement {
+                title: message.content.title.clone(),
+                variant: variant.clone(),
+            };
+            channel_embeds
+                .entry(channel_id(subscription.subscriber))
+                .or_default()
+                .push((announcement, embed));
+        }
+    }
 
-    let embed = CreateEmbed::new()
-        .title(title)
-        .timestamp(message.content.created_at)
-        .fields(download_fields(message.content.downloads));
+    info!("Notifying {} channels", channel_embeds.len());
+    for (channel_id, embeds) in channel_embeds {
+        for chunk in embeds.chunks(MAX_EMBEDS_PER_MESSAGE) {
+            let (announcements, embeds): (Vec<_>, Vec<_>) = chunk.iter().cloned().unzip();
+            if let Err(err) = channel_id.send_embeds(&discord_http, embeds.clone()).await {
+                error!(
+                    channel_id = channel_id.format(&discord_cache),
+                    "Failed to send embeds to, {err}",
+                );
+                let delivery = FailedDelivery {
+                    channel_id,
+                    announcements,
+                    embeds,
+                    attempt: 1,
+                };
+                queue_retry(&pool, &retry_tx, delivery).await;
+            }
+        }
+    }
+}
+
+/// The collection an announcement is recorded for, see [`otaku::db::insert_sent_announcement`].
+#[derive(Debug, Clone)]
+struct Announcement {
+    title: String,
+    variant: String,
+}
 
-    let channel_ids = channel_ids(&message.subscribers);
-    info!("Notifying {} channels", channel_ids.len());
-    for channel_id in channel_ids {
-        if let Err(err) = channel_id.send_embed(&discord_http, embed.clone()).await {
+/// An announcement that could not be delivered yet.
+#[derive(Debug, Clone)]
+struct FailedDelivery {
+    channel_id: MessageChannelId,
+    /// The collections of the embeds.
+    announcements: Vec<Announcement>,
+    embeds: Vec<CreateEmbed>,
+    /// The number of failed attempts so far.
+    attempt: u32,
+}
+
+/// Number of announcements that were dropped after failing to deliver them.
+static DROPPED_ANNOUNCEMENTS: AtomicU64 = AtomicU64::new(0);
+
+async fn queue_retry(pool: &Pool, retry_tx: &Sender<FailedDelivery>, delivery: FailedDelivery) {
+    if let Err(TrySendError::Full(delivery) | TrySendError::Closed(delivery)) =
+        retry_tx.try_send(delivery)
+    {
+        let dropped = DROPPED_ANNOUNCEMENTS.fetch_add(1, Ordering::Relaxed) + 1;
+        error!(
+            dropped,
+            "Announcement retry queue is full, dropping announcement"
+        );
+        forget_delivery(pool, &delivery).await;
+    }
+}
+
+/// Remove the records of the announcements in `delivery` after it was dropped, so they are not
+/// treated as sent when the collections are announced again or backfilled.
+async fn forget_delivery(pool: &Pool, delivery: &FailedDelivery) {
+    let channel_id = delivery.channel_id.announcement_channel();
+    for Announcement { title, variant } in &delivery.announcements {
+        let result = otaku::db::delete_sent_announcement(pool, title, variant, channel_id).await;
+        if let Err(err) = result {
+            warn!("Failed to forget the announcement of {title} {variant} to {channel_id}: {err}");
+        }
+    }
+}
+
+/// Retry the failed deliveries until every sender of the queue is gone.
+///
+/// Only a weak sender is kept to requeue deliveries, so the queue closes once the announcements
+/// and the retries in progress are done.
+async fn retry_sender(
+    pool: Pool,
+    discord_cache: Arc<Cache>,
+    discord_http: Arc<Http>,
+    retry_tx: WeakSender<FailedDelivery>,
+    mut retry_rx: Receiver<FailedDelivery>,
+    shutdown: CancellationToken,
+) {
+    let tasks = TaskTracker::new();
+    while let Some(delivery) = retry_rx.recv().await {
+        tasks.spawn(retry_delivery(
+            pool.clone(),
+            discord_cache.clone(),
+            discord_http.clone(),
+            retry_tx.upgrade(),
+            delivery,
+            shutdown.clone(),
+        ));
+    }
+    tasks.close();
+    tasks.wait().await;
+}
+
+/// Send a failed announcement again after a delay that doubles with every attempt.
+///
+/// After `shutdown` is cancelled the delay is skipped and a failed delivery is not requeued.
+async fn retry_delivery(
+    pool: Pool,
+    discord_cache: Arc<Cache>,
+    discord_http: Arc<Http>,
+    retry_tx: Option<Sender<FailedDelivery>>,
+    mut delivery: FailedDelivery,
+    shutdown: CancellationToken,
+) {
+    let delay = ANNOUNCEMENT_RETRY_INTERVAL * 2u32.saturating_pow(delivery.attempt - 1);
+    tokio::select! {
+        () = shutdown.cancelled() => {}
+        () = tokio::time::sleep(delay) => {}
+    }
+
+    let channel_id = delivery.channel_id;
+    let Err(err) = channel_id
+        .send_embeds(&discord_http, delivery.embeds.clone())
+        .await
+    else {
+        return;
+    };
+    match retry_tx {
+        Some(retry_tx)
+            if !shutdown.is_cancelled() && delivery.attempt < ANNOUNCEMENT_RETRY_ATTEMPTS =>
+        {
+            delivery.attempt += 1;
+            queue_retry(&pool, &retry_tx, delivery).await;
+        }
+        _ => {
+            let dropped = DROPPED_ANNOUNCEMENTS.fetch_add(1, Ordering::Relaxed) + 1;
             error!(
                 channel_id = channel_id.format(&discord_cache),
-                "Failed to send embed to, {err}",
+                dropped, "Dropping announcement after {} attempts, {err}", delivery.attempt,
             );
+            forget_delivery(&pool, &delivery).await;
         }
     }
 }
 
+/// Build the announcement embed, only listing `resolution` when the collection contains it.
+pub(crate) fn download_embed(
+    title: &str,
+    collection: &DownloadCollection,
+    resolution: Option<u16>,
+) -> CreateEmbed {
+    let preferred = resolution.filter(|&resolution| {
+        collection
+            .downloads
+            .iter()
+            .any(|download| download.resolution == resolution)
+    });
+    let downloads = collection
+        .downloads
+        .iter()
+        .filter(|download| preferred.is_none_or(|resolution| download.resolution == resolution))
+        .cloned();
+    CreateEmbed::new()
+        .title(title)
+        .timestamp(collection.created_at)
+        .fields(download_fields(downloads))
+}
+
 fn download_fields<I>(downloads: I) -> impl IntoIterator<Item = (String, String, bool)>
 where
     I: IntoIterator<Item = Download>,
@@ -189,12 +813,12 @@ where
     })
 }
 
-fn channel_ids(subscribers: &[Subscriber]) -> impl ExactSizeIterator<Item = MessageChannelId> + '_ {
-    subscribers.iter().map(|&s| match s {
+fn channel_id(subscriber: Subscriber) -> MessageChannelId {
+    match subscriber {
         Subscriber::User(id) => MessageChannelId::User(id.into()),
         Subscriber::Channel {
             guild_id,
             channel_id,
         } => MessageChannelId::Guild(guild_id.into(), channel_id.into()),
-    })
+    }
 }
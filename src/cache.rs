@@ -1,24 +1,123 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant};
 
-use tokio::sync::RwLock;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::thread_rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
 
 use crate::consts;
 
-#[derive(Debug, Clone)]
-pub struct Key<T: ?Sized>(Instant, Arc<T>);
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(feature = "redis")]
+pub use redis::RedisCache;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// A cache entry as it is stored on disk, with the time it has left instead of its expiration.
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotEntry<V> {
+    key: String,
+    remaining: Duration,
+    value: V,
+}
+
+/// A cached value with its expiration and the tick of its last use.
+#[derive(Debug)]
+pub struct Key<T: ?Sized>(Instant, Arc<T>, AtomicU64);
+
+/// Bounds on the size of a [`Memory`] cache, the least recently used entries are evicted when
+/// an insert exceeds them.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub max_entries: Option<usize>,
+    /// Maximum of the summed weights of the entries, see [`Memory::with_limits`].
+    pub max_weight: Option<usize>,
+}
+
+/// A store shared between multiple instances of the bot, see [`Memory::with_remote`].
+#[async_trait]
+pub trait RemoteStore<T: ?Sized>: Debug + Send + Sync {
+    /// The value of `key` and the time it has left.
+    async fn get(&self, key: &str) -> Option<(Arc<T>, Duration)>;
+    async fn set(&self, key: &str, value: Arc<T>, duration: Duration);
+    async fn remove(&self, key: &str);
+}
+
+/// Result of looking up a key with [`Memory::lookup`].
+#[derive(Debug)]
+pub enum CacheLookup<T: ?Sized> {
+    Hit(Arc<T>),
+    Miss,
+    /// The last load of the key found nothing, see [`Memory::insert_empty`].
+    KnownEmpty,
+}
+
+/// Usage counters of a single cache key.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct KeyStats {
+    pub hits: u64,
+    /// Lookups of a missing or expired value, stale values served while refreshing included.
+    pub misses: u64,
+    pub last_refresh: Option<DateTime<Utc>>,
+}
+
+/// The hashes of the values last chosen per channel, with the time the channel last chose one.
+type RecentChoices = HashMap<u64, (Instant, VecDeque<u64>)>;
 
 #[derive(Debug)]
 pub struct Memory<T: ?Sized> {
-    map: Arc<RwLock<HashMap<Cow<'static, str>, Key<T>>>>,
+    /// Sharded, so reads of one key never wait for inserts of another.
+    map: Arc<DashMap<Cow<'static, str>, Key<T>>>,
+    /// Serializes evictions, which scan every shard.
+    evicting: Arc<Mutex<()>>,
+    clock: Arc<AtomicU64>,
+    limits: Limits,
+    weigher: fn(&T) -> usize,
+    /// Keys that are being reloaded in the background by [`Memory::get_or_refresh`].
+    refreshing: Arc<Mutex<HashSet<Cow<'static, str>>>>,
+    stats: Arc<Mutex<HashMap<Cow<'static, str>, KeyStats>>>,
+    /// Expiration of the keys that are known to have no value.
+    empty: Arc<Mutex<HashMap<Cow<'static, str>, Instant>>>,
+    remote: Option<Arc<dyn RemoteStore<T>>>,
+    /// The values last chosen by [`Memory::choose_for`].
+    recent: Arc<Mutex<RecentChoices>>,
 }
 
 impl<T: ?Sized> Clone for Memory<T> {
     fn clone(&self) -> Self {
         Self {
             map: self.map.clone(),
+            evicting: self.evicting.clone(),
+            clock: self.clock.clone(),
+            limits: self.limits,
+            weigher: self.weigher,
+            refreshing: self.refreshing.clone(),
+            stats: self.stats.clone(),
+            empty: self.empty.clone(),
+            remote: self.remote.clone(),
+            recent: self.recent.clone(),
         }
     }
 }
@@ -26,21 +125,131 @@ impl<T: ?Sized> Clone for Memory<T> {
 impl<T: ?Sized> Default for Memory<T> {
     fn default() -> Self {
         Self {
-            map: Arc::new(RwLock::new(HashMap::new())),
+            map: Arc::new(DashMap::new()),
+            evicting: Arc::new(Mutex::new(())),
+            clock: Arc::new(AtomicU64::new(0)),
+            limits: Limits::default(),
+            weigher: |_| 1,
+            refreshing: Arc::new(Mutex::new(HashSet::new())),
+            stats: Arc::new(Mutex::new(HashMap::new())),
+            empty: Arc::new(Mutex::new(HashMap::new())),
+            remote: None,
+            recent: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
 
 impl<T: ?Sized> Memory<T> {
+    #[expect(dead_code)]
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// A cache that stays within `limits`, where `weigher` gives the weight of a single value.
+    pub fn with_limits(limits: Limits, weigher: fn(&T) -> usize) -> Self {
+        Self {
+            limits,
+            weigher,
+            ..Self::default()
+        }
+    }
+
+    /// Read missing values from `remote` and write every inserted value through to it.
+    #[must_use]
+    #[cfg_attr(not(feature = "redis"), expect(dead_code))]
+    pub fn with_remote(mut self, remote: impl RemoteStore<T> + 'static) -> Self {
+        self.remote = Some(Arc::new(remote));
+        self
+    }
+
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn update_stats(&self, key: &str, f: impl FnOnce(&mut KeyStats)) {
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(key_stats) = stats.get_mut(key) {
+            f(key_stats);
+        } else {
+            f(stats.entry(Cow::Owned(key.to_string())).or_default());
+        }
+    }
+
+    fn record_lookup(&self, key: &str, hit: bool) {
+        self.update_stats(key, |stats| {
+            if hit {
+                stats.hits += 1;
+            } else {
+                stats.misses += 1;
+            }
+        });
+    }
+
+    /// Snapshot of the usage counters of every key, sorted by key.
+    pub fn stats(&self) -> Vec<(String, KeyStats)> {
+        let stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut stats: Vec<_> = stats
+            .iter()
+            .map(|(key, stats)| (key.to_string(), *stats))
+            .collect();
+        stats.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        stats
+    }
+
+    /// The number of cached entries, expired entries included until they are trimmed.
+    pub fn entry_count(&self) -> usize {
+        self.map.len()
+    }
+
+    /// The summed weight of the cached entries, expired entries included until they are trimmed.
+    pub fn total_weight(&self) -> usize {
+        self.map
+            .iter()
+            .map(|entry| (self.weigher)(&entry.value().1))
+            .sum()
+    }
+
     pub async fn get(&self, key: &str) -> Option<Arc<T>> {
-        let map = self.map.read().await;
-        map.get(key)
-            .filter(|&&Key(instant, _)| instant >= Instant::now())
-            .map(|Key(_, value)| value.clone())
+        let value = self
+            .map
+            .get(key)
+            .filter(|entry| entry.0 >= Instant::now())
+            .map(|entry| {
+                let Key(_, value, last_used) = entry.value();
+                last_used.store(self.tick(), Ordering::Relaxed);
+                value.clone()
+            });
+        let value = match value {
+            Some(value) => Some(value),
+            None => self.get_remote(key).await,
+        };
+        self.record_lookup(key, value.is_some());
+        value
+    }
+
+    async fn get_remote(&self, key: &str) -> Option<Arc<T>> {
+        let (value, remaining) = self.remote.as_ref()?.get(key).await?;
+        let expiration = Instant::now() + remaining;
+        self.insert_local(key.to_string().into(), value.clone(), expiration);
+        Some(value)
+    }
+
+    /// Like [`Memory::get`], but tells a missing key apart from one that is known to be empty.
+    pub async fn lookup(&self, key: &str) -> CacheLookup<T> {
+        if let Some(value) = self.get(key).await {
+            return CacheLookup::Hit(value);
+        }
+        let empty = self.empty.lock().unwrap_or_else(PoisonError::into_inner);
+        match empty.get(key) {
+            Some(&expiration) if expiration >= Instant::now() => CacheLookup::KnownEmpty,
+            _ => CacheLookup::Miss,
+        }
+    }
+
+    /// Remember for `duration` that `key` has no value, until a value is inserted.
+    pub fn insert_empty(&self, key: impl Into<Cow<'static, str>>, duration: Duration) {
+        let mut empty = self.empty.lock().unwrap_or_else(PoisonError::into_inner);
+        empty.insert(key.into(), Instant::now() + duration);
     }
 
     #[expect(dead_code)]
@@ -65,13 +274,438 @@ impl<T: ?Sized> Memory<T> {
         value: impl Into<Arc<T>>,
         expiration: Instant,
     ) {
-        let mut map = self.map.write().await;
-        map.insert(key.into(), Key(expiration, value.into()));
+        let key = key.into();
+        let value = value.into();
+        self.update_stats(&key, |stats| stats.last_refresh = Some(Utc::now()));
+        if let Some(remote) = &self.remote {
+            let remaining = expiration.saturating_duration_since(Instant::now());
+            if !remaining.is_zero() {
+                remote.set(&key, value.clone(), remaining).await;
+            }
+        }
+        self.insert_local(key, value, expiration);
+    }
+
+    fn insert_local(&self, key: Cow<'static, str>, value: Arc<T>, expiration: Instant) {
+        self.empty
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&key);
+        self.map
+            .insert(key, Key(expiration, value, AtomicU64::new(self.tick())));
+        self.evict();
+    }
+
+    /// Remove the value of `key`, returns whether there was one.
+    pub async fn invalidate(&self, key: &str) -> bool {
+        self.empty
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+        if let Some(remote) = &self.remote {
+            remote.remove(key).await;
+        }
+        self.map.remove(key).is_some()
+    }
+
+    /// Remove the values of all keys starting with `prefix`, returns the number of removed values.
+    pub async fn invalidate_prefix(&self, prefix: &str) -> usize {
+        self.empty
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|key, _| !key.starts_with(prefix));
+        let keys: Vec<_> = self
+            .map
+            .iter()
+            .filter(|entry| entry.key().starts_with(prefix))
+            .map(|entry| entry.key().clone())
+            .collect();
+        let mut count = 0;
+        for key in keys {
+            if self.invalidate(&key).await {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Remove the entries that expired more than [`consts::STALE_CACHE_LIFETIME`] ago, newer
+    /// entries are kept to be served by [`Memory::get_or_refresh`].
+    pub fn trim(&self) {
+        let now = Instant::now();
+        self.map
+            .retain(|_, Key(expiration, ..)| *expiration + consts::STALE_CACHE_LIFETIME >= now);
+        self.recent
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|_, (last_used, _)| *last_used + consts::STALE_CACHE_LIFETIME >= now);
+        let mut empty = self.empty.lock().unwrap_or_else(PoisonError::into_inner);
+        empty.retain(|_, expiration| *expiration >= now);
+        // Forget the counters of keys that were looked up but never cached.
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        stats.retain(|key, _| self.map.contains_key(key) || empty.contains_key(key));
+    }
+
+    /// Remove entries until the cache is within its limits, expired entries first and then the
+    /// least recently used ones.
+    fn evict(&self) {
+        let _evicting = self.evicting.lock().unwrap_or_else(PoisonError::into_inner);
+        let map = &self.map;
+        let weight_of = |Key(_, value, _): &Key<T>| (self.weigher)(value);
+        let exceeds = |entries: usize, weight: usize| {
+            self.limits.max_entries.is_some_and(|max| entries > max)
+                || self.limits.max_weight.is_some_and(|max| weight > max)
+        };
+        let mut weight: usize = map.iter().map(|entry| weight_of(entry.value())).sum();
+        if !exceeds(map.len(), weight) {
+            return;
+        }
+        let now = Instant::now();
+        map.retain(|_, key| {
+            let expired = key.0 < now;
+            if expired {
+                weight -= weight_of(key);
+            }
+            !expired
+        });
+        while exceeds(map.len(), weight) {
+            let Some(key) = map
+                .iter()
+                .min_by_key(|entry| entry.2.load(Ordering::Relaxed))
+                .map(|entry| entry.key().clone())
+            else {
+                break;
+            };
+            if let Some((_, removed)) = map.remove(&key) {
+                weight -= weight_of(&removed);
+            }
+        }
+    }
+
+    /// Write the entries that have not expired to `path`, replacing the previous snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the entries cannot be serialized or the file cannot be written.
+    pub async fn save(&self, path: &Path) -> Result<usize, SnapshotError>
+    where
+        T: Serialize,
+    {
+        let now = Instant::now();
+        let entries: Vec<_> = self
+            .map
+            .iter()
+            .filter(|entry| entry.0 >= now)
+            .map(|entry| {
+                let Key(expiration, value, _) = entry.value();
+                SnapshotEntry {
+                    key: entry.key().to_string(),
+                    remaining: expiration.duration_since(now),
+                    value: value.clone(),
+                }
+            })
+            .collect();
+        let bytes = serde_json::to_vec(&entries)?;
+        let count = entries.len();
+        // Write next to the target first, so a crash never leaves a truncated snapshot behind.
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, bytes).await?;
+        fs::rename(&temp_path, path).await?;
+        Ok(count)
     }
 
-    pub async fn trim(&self) {
+    /// Insert the entries of the snapshot at `path`, keeping the time they had left when they
+    /// were saved. Returns the number of loaded entries, zero when there is no snapshot.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the file cannot be read or does not contain a valid snapshot.
+    pub async fn load(&self, path: &Path) -> Result<usize, SnapshotError>
+    where
+        Box<T>: DeserializeOwned,
+    {
+        let bytes = match fs::read(path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+        let entries: Vec<SnapshotEntry<Box<T>>> = serde_json::from_slice(&bytes)?;
         let now = Instant::now();
-        let mut map = self.map.write().await;
-        map.retain(|_, &mut Key(expiration, _)| expiration >= now);
+        let count = entries.len();
+        for entry in entries {
+            self.map.insert(
+                entry.key.into(),
+                Key(
+                    now + entry.remaining,
+                    Arc::from(entry.value),
+                    AtomicU64::new(self.tick()),
+                ),
+            );
+        }
+        self.evict();
+        Ok(count)
+    }
+}
+
+impl<T: Clone + Hash> Memory<[T]> {
+    /// A random value of `values`, avoiding the last [`consts::RECENT_VALUES_PER_CHANNEL`] values
+    /// chosen for `channel` from any collection while there are others.
+    pub fn choose_for(&self, values: &[T], channel: u64) -> Option<T> {
+        let mut recent = self.recent.lock().unwrap_or_else(PoisonError::into_inner);
+        let (last_used, history) = recent
+            .entry(channel)
+            .or_insert_with(|| (Instant::now(), VecDeque::new()));
+        *last_used = Instant::now();
+        let mut rng = thread_rng();
+        let value = values
+            .iter()
+            .filter(|value| !history.contains(&hash_of(value)))
+            .choose(&mut rng)
+            .or_else(|| values.choose(&mut rng))?
+            .clone();
+        if history.len() >= consts::RECENT_VALUES_PER_CHANNEL {
+            history.pop_front();
+        }
+        history.push_back(hash_of(&value));
+        Some(value)
+    }
+}
+
+fn hash_of(value: &impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl<T: ?Sized + Send + Sync + 'static> Memory<T> {
+    /// Get the value of `key`, even when it has expired.
+    ///
+    /// An expired value is returned immediately while `loader` refreshes it in the background,
+    /// a missing value is taken from the remote store or loaded before returning. The loaded value
+    /// is cached for `duration`.
+    pub async fn get_or_refresh<F, Fut>(
+        &self,
+        key: impl Into<Cow<'static, str>>,
+        duration: Duration,
+        loader: F,
+    ) -> Option<Arc<T>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Option<Arc<T>>> + Send + 'static,
+    {
+        let key = key.into();
+        let cached = self.map.get(&key).map(|entry| {
+            let Key(expiration, value, last_used) = entry.value();
+            last_used.store(self.tick(), Ordering::Relaxed);
+            (*expiration >= Instant::now(), value.clone())
+        });
+        self.record_lookup(&key, matches!(cached, Some((true, _))));
+        match cached {
+            Some((true, value)) => Some(value),
+            Some((false, value)) => {
+                let mut refreshing = self
+                    .refreshing
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                if refreshing.insert(key.clone()) {
+                    let cache = self.clone();
+                    let guard = RefreshGuard(self.refreshing.clone(), key.clone());
+                    let load = loader();
+                    tokio::spawn(async move {
+                        let _guard = guard;
+                        if let Some(value) = load.await {
+                            cache.insert_with_duration(key, value, duration).await;
+                        }
+                    });
+                }
+                Some(value)
+            }
+            None => {
+                if let Some(value) = self.get_remote(&key).await {
+                    return Some(value);
+                }
+                let value = loader().await?;
+                self.insert_with_duration(key, value.clone(), duration)
+                    .await;
+                Some(value)
+            }
+        }
+    }
+}
+
+/// Removes its key from [`Memory::refreshing`] when the refresh ends, also when the loader panics.
+struct RefreshGuard(Arc<Mutex<HashSet<Cow<'static, str>>>>, Cow<'static, str>);
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn bounded(max_entries: Option<usize>, max_weight: Option<usize>) -> Memory<[u32]> {
+        let limits = Limits {
+            max_entries,
+            max_weight,
+        };
+        Memory::with_limits(limits, <[u32]>::len)
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_entry() {
+        let cache = bounded(Some(2), None);
+        cache.insert_with_duration("a", vec![1], HOUR).await;
+        cache.insert_with_duration("b", vec![2], HOUR).await;
+        cache.get("a").await;
+        cache.insert_with_duration("c", vec![3], HOUR).await;
+
+        assert!(cache.get("a").await.is_some());
+        assert!(cache.get("b").await.is_none());
+        assert!(cache.get("c").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn evicts_until_within_max_weight() {
+        let cache = bounded(None, Some(4));
+        cache.insert_with_duration("a", vec![1, 2], HOUR).await;
+        cache.insert_with_duration("b", vec![3, 4], HOUR).await;
+        cache.insert_with_duration("c", vec![5, 6, 7], HOUR).await;
+
+        assert!(cache.get("a").await.is_none());
+        assert!(cache.get("b").await.is_none());
+        assert_eq!(cache.get("c").await.as_deref(), Some(&[5, 6, 7][..]));
+    }
+
+    #[tokio::test]
+    async fn evicts_expired_entries_first() {
+        let cache = bounded(Some(2), None);
+        cache
+            .insert_with_expiration(
+                "expired",
+                vec![1],
+                Instant::now().checked_sub(HOUR).expect("an hour ago"),
+            )
+            .await;
+        cache.insert_with_duration("a", vec![2], HOUR).await;
+        cache.get("expired").await;
+        cache.insert_with_duration("b", vec![3], HOUR).await;
+
+        assert!(cache.get("a").await.is_some());
+        assert!(cache.get("b").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn get_or_refresh_loads_missing_value() {
+        let cache = bounded(None, None);
+        let value = cache
+            .get_or_refresh("a", HOUR, || async { Some(Arc::from(vec![1])) })
+            .await;
+
+        assert_eq!(value.as_deref(), Some(&[1][..]));
+        assert_eq!(cache.get("a").await.as_deref(), Some(&[1][..]));
+    }
+
+    #[tokio::test]
+    async fn get_or_refresh_serves_stale_value_while_refreshing() {
+        let cache = bounded(None, None);
+        cache
+            .insert_with_expiration(
+                "a",
+                vec![1],
+                Instant::now().checked_sub(HOUR).expect("an hour ago"),
+            )
+            .await;
+        let value = cache
+            .get_or_refresh("a", HOUR, || async { Some(Arc::from(vec![2])) })
+            .await;
+        assert_eq!(value.as_deref(), Some(&[1][..]));
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(cache.get("a").await.as_deref(), Some(&[2][..]));
+    }
+
+    #[tokio::test]
+    async fn get_or_refresh_retries_after_a_panicking_loader() {
+        let cache = bounded(None, None);
+        let expired = Instant::now().checked_sub(HOUR).expect("an hour ago");
+        cache.insert_with_expiration("a", vec![1], expired).await;
+        cache
+            .get_or_refresh("a", HOUR, || async { panic!("loader failed") })
+            .await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        cache
+            .get_or_refresh("a", HOUR, || async { Some(Arc::from(vec![2])) })
+            .await;
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(cache.get("a").await.as_deref(), Some(&[2][..]));
+    }
+
+    #[tokio::test]
+    async fn lookup_tells_missing_and_empty_keys_apart() {
+        let cache = bounded(None, None);
+        cache.insert_empty("empty", HOUR);
+        cache.insert_with_duration("a", vec![1], HOUR).await;
+
+        assert!(matches!(cache.lookup("a").await, CacheLookup::Hit(_)));
+        assert!(matches!(
+            cache.lookup("empty").await,
+            CacheLookup::KnownEmpty
+        ));
+        assert!(matches!(cache.lookup("missing").await, CacheLookup::Miss));
+
+        cache.insert_with_duration("empty", vec![2], HOUR).await;
+        assert!(matches!(cache.lookup("empty").await, CacheLookup::Hit(_)));
+    }
+
+    #[test]
+    fn choose_for_avoids_recent_values_per_channel() {
+        let cache = bounded(None, None);
+
+        let first = cache.choose_for(&[1, 2], 1).unwrap();
+        let second = cache.choose_for(&[1, 2], 1).unwrap();
+        assert_ne!(first, second);
+        assert!(cache.choose_for(&[1, 2], 1).is_some());
+    }
+
+    #[test]
+    fn choose_for_avoids_recent_values_of_other_collections() {
+        let cache = bounded(None, None);
+
+        assert_eq!(cache.choose_for(&[1], 1), Some(1));
+        assert_eq!(cache.choose_for(&[1, 2], 1), Some(2));
+        assert!(cache.choose_for(&[1, 2], 2).is_some());
+    }
+
+    #[tokio::test]
+    async fn invalidate_prefix_removes_matching_keys() {
+        let cache = bounded(None, None);
+        cache.insert_with_duration("sleep", vec![1], HOUR).await;
+        cache.insert_with_duration("sleep_cat", vec![2], HOUR).await;
+        cache.insert_with_duration("play", vec![3], HOUR).await;
+
+        assert_eq!(cache.invalidate_prefix("sleep").await, 2);
+        assert!(cache.get("sleep_cat").await.is_none());
+        assert!(cache.get("play").await.is_some());
+        assert!(cache.invalidate("play").await);
+        assert!(!cache.invalidate("play").await);
+    }
+
+    #[tokio::test]
+    async fn counts_entries_and_weight() {
+        let cache = bounded(None, None);
+        cache.insert_with_duration("a", vec![1, 2], HOUR).await;
+        cache.insert_with_duration("b", vec![3], HOUR).await;
+
+        assert_eq!(cache.entry_count(), 2);
+        assert_eq!(cache.total_weight(), 3);
     }
 }
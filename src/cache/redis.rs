@@ -0,0 +1,115 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ::redis::aio::ConnectionManager;
+use ::redis::{Client, RedisError};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::warn;
+
+use super::RemoteStore;
+
+/// Stores cached values in redis as json, so multiple instances of the bot share them and they
+/// survive restarts.
+pub struct RedisCache<T: ?Sized> {
+    connection: ConnectionManager,
+    prefix: &'static str,
+    value: PhantomData<fn() -> Box<T>>,
+}
+
+impl<T: ?Sized> std::fmt::Debug for RedisCache<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisCache")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: ?Sized> RedisCache<T> {
+    /// Connect to the redis server at `url`, all keys are stored with `prefix` in front.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the url is invalid or the server cannot be reached.
+    pub async fn connect(url: &str, prefix: &'static str) -> Result<Self, RedisError> {
+        let client = Client::open(url)?;
+        let connection = ConnectionManager::new(client).await?;
+        Ok(Self {
+            connection,
+            prefix,
+            value: PhantomData,
+        })
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}:{key}", self.prefix)
+    }
+}
+
+#[async_trait]
+impl<T> RemoteStore<T> for RedisCache<T>
+where
+    T: ?Sized + Serialize + Send + Sync,
+    Box<T>: DeserializeOwned,
+{
+    async fn get(&self, key: &str) -> Option<(Arc<T>, Duration)> {
+        let mut connection = self.connection.clone();
+        let result: Result<(Option<Vec<u8>>, i64), RedisError> = ::redis::pipe()
+            .get(self.key(key))
+            .pttl(self.key(key))
+            .query_async(&mut connection)
+            .await;
+        let (bytes, ttl) = match result {
+            Ok((Some(bytes), ttl)) => (bytes, ttl),
+            Ok((None, _)) => return None,
+            Err(err) => {
+                warn!("Failed to read {key} from redis: {err}");
+                return None;
+            }
+        };
+        // A negative ttl means the key has no expiration or has expired in the meantime.
+        let remaining = Duration::from_millis(u64::try_from(ttl).ok()?);
+        match serde_json::from_slice::<Box<T>>(&bytes) {
+            Ok(value) => Some((Arc::from(value), remaining)),
+            Err(err) => {
+                warn!("Failed to parse {key} from redis: {err}");
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &str, value: Arc<T>, duration: Duration) {
+        let bytes = match serde_json::to_vec(&*value) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                warn!("Failed to serialize {key} for redis: {err}");
+                return;
+            }
+        };
+        let mut connection = self.connection.clone();
+        let millis = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+        let result: Result<(), RedisError> = ::redis::cmd("SET")
+            .arg(self.key(key))
+            .arg(bytes)
+            .arg("PX")
+            .arg(millis)
+            .query_async(&mut connection)
+            .await;
+        if let Err(err) = result {
+            warn!("Failed to write {key} to redis: {err}");
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        let mut connection = self.connection.clone();
+        let result: Result<(), RedisError> = ::redis::cmd("DEL")
+            .arg(self.key(key))
+            .query_async(&mut connection)
+            .await;
+        if let Err(err) = result {
+            warn!("Failed to remove {key} from redis: {err}");
+        }
+    }
+}
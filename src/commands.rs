@@ -1,13 +1,47 @@
+use otaku::db::guild_settings::PermissionLevel;
 use tracing::error;
 
+use crate::commands::anime::AnimeError;
 use crate::commands::gifs::GifError;
 
+pub mod admin;
+pub mod anime;
+pub mod cache;
+pub mod config;
+pub mod dice;
+pub mod games;
+pub mod gifconfig;
 pub mod gifs;
+pub mod help;
+pub mod poll;
+pub mod sessions;
+pub mod settings;
+pub mod sleep_stats;
+pub mod status;
+pub mod timezone;
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum CommandError {
     #[error(transparent)]
     GifError(#[from] GifError),
     #[error(transparent)]
+    AnimeError(#[from] AnimeError),
+    #[error(transparent)]
+    Settings(#[from] otaku::db::guild_settings::Error),
+    #[error(transparent)]
+    Database(#[from] otaku::db::Error),
+    #[error(transparent)]
+    GifOverrides(#[from] otaku::db::gif_overrides::Error),
+    #[error(transparent)]
+    GifBlocklist(#[from] otaku::db::gif_blocklist::Error),
+    #[error(transparent)]
+    PlaySessions(#[from] otaku::db::play_sessions::Error),
+    #[error(transparent)]
+    Polls(#[from] otaku::db::polls::Error),
+    #[error(transparent)]
+    SleepNights(#[from] otaku::db::sleep_nights::Error),
+    #[error(transparent)]
     Serenity(#[from] serenity::Error),
+    #[error("The {0} permission level is required")]
+    MissingPermission(PermissionLevel),
 }
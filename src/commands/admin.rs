@@ -0,0 +1,61 @@
+use std::fmt::Write;
+
+use chrono::{TimeDelta, Utc};
+use poise::CreateReply;
+use tracing::instrument;
+
+use crate::commands::CommandError;
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Text};
+
+const DEFAULT_USAGE_DAYS: u32 = 30;
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, owners_only, category = "Owner", subcommands("usage"))]
+/// Inspect how the bot is used
+pub(crate) async fn admin(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, owners_only)]
+/// Show how often each command was used
+pub(crate) async fn usage(
+    ctx: Context<'_, '_>,
+    #[description = "Number of days to look back, 30 by default"]
+    #[min = 1]
+    days: Option<u32>,
+) -> Result<(), CommandError> {
+    let days = days.unwrap_or(DEFAULT_USAGE_DAYS);
+    let since = Utc::now() - TimeDelta::days(i64::from(days));
+    let usage = otaku::db::command_usage::get_command_usage(ctx.database(), since).await?;
+    let content = if usage.is_empty() {
+        let days = days.to_string();
+        Text::UsageEmpty.format(i18n::locale(ctx).await, &[("days", days.as_str())])
+    } else {
+        let mut table =
+            String::from("```\ncommand              uses  errors  users  avg ms  last used\n");
+        for usage in usage {
+            // Stay below the message limit of discord.
+            if table.len() > 1900 {
+                table.push_str("...\n");
+                break;
+            }
+            let _ = writeln!(
+                table,
+                "{:<20} {:>4} {:>7} {:>6} {:>7}  {}",
+                usage.command,
+                usage.invocations,
+                usage.errors,
+                usage.users,
+                usage.average_latency_ms,
+                usage.last_used_at.format("%F %R")
+            );
+        }
+        table.push_str("```");
+        table
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
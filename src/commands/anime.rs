@@ -0,0 +1,74 @@
+use crate::background_tasks::download_embed;
+use crate::commands::CommandError;
+use crate::consts::MAX_EMBEDS_PER_MESSAGE;
+use crate::context::{AnimeExt, Context};
+use crate::i18n::{self, Text};
+use otaku::ConnectionState;
+use poise::CreateReply;
+use tracing::instrument;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AnimeError {
+    #[error(transparent)]
+    Search(#[from] otaku::SearchError),
+    #[error("The anime api is not configured")]
+    Unavailable,
+    #[error("No downloads found for \"{0}\"")]
+    NotFound(String),
+}
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, category = "Anime", subcommands("downloads", "status"))]
+/// Anime releases
+pub(crate) async fn anime(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command)]
+/// Show the latest available downloads of a show
+pub(crate) async fn downloads(
+    ctx: Context<'_, '_>,
+    #[description = "The title of the show"] title: String,
+) -> Result<(), CommandError> {
+    let anime_api = ctx.anime_api().ok_or(AnimeError::Unavailable)?;
+    ctx.defer().await?;
+    let collections = otaku::search(anime_api, &title)
+        .await
+        .map_err(AnimeError::from)?;
+    if collections.is_empty() {
+        return Err(AnimeError::NotFound(title).into());
+    }
+
+    let reply = collections
+        .iter()
+        .take(MAX_EMBEDS_PER_MESSAGE)
+        .map(|collection| {
+            let title = format!("{} {}", collection.title, collection.variant);
+            download_embed(&title, collection, None)
+        })
+        .fold(CreateReply::default(), CreateReply::embed);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+#[poise::command(slash_command)]
+/// Show whether new releases are being announced
+pub(crate) async fn status(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let text = if ctx.anime_api().is_none() {
+        Text::AnimeUnavailable
+    } else {
+        match ctx.anime_state() {
+            ConnectionState::Connected => Text::AnimeConnected,
+            ConnectionState::Connecting => Text::AnimeConnecting,
+            ConnectionState::Disconnected => Text::AnimeDisconnected,
+        }
+    };
+    let locale = i18n::locale(ctx).await;
+    let reply = CreateReply::default()
+        .ephemeral(true)
+        .content(text.get(locale));
+    ctx.send(reply).await?;
+    Ok(())
+}
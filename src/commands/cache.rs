@@ -0,0 +1,137 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use poise::CreateReply;
+use tracing::instrument;
+use url::Url;
+
+use crate::commands::CommandError;
+use crate::consts::MODAL_TIMEOUT;
+use crate::context::{ApplicationContext, ConfigExt, Context, GifCacheExt};
+use crate::i18n::{self, Text};
+
+/// A collection of gifs entered by an owner.
+#[derive(Debug, poise::Modal)]
+#[name = "Add gifs to the cache"]
+struct GifCollection {
+    #[name = "Query"]
+    #[placeholder = "good night"]
+    #[max_length = 100]
+    key: String,
+    #[name = "Gifs"]
+    #[placeholder = "One url per line"]
+    #[paragraph]
+    gifs: String,
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    slash_command,
+    owners_only,
+    category = "Owner",
+    subcommands("stats", "add", "invalidate")
+)]
+/// Inspect the bot caches
+pub(crate) async fn cache(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, owners_only)]
+/// Show the hits and misses of the gif cache
+pub(crate) async fn stats(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let stats = ctx.gif_cache().stats();
+    let content = if stats.is_empty() {
+        Text::CacheUnused.get(i18n::locale(ctx).await).to_string()
+    } else {
+        let mut table = String::from("```\nkey                  hits  misses  last refresh\n");
+        for (key, stats) in stats {
+            // Stay below the message limit of discord.
+            if table.len() > 1900 {
+                table.push_str("...\n");
+                break;
+            }
+            let last_refresh = stats.last_refresh.map_or_else(
+                || "never".to_string(),
+                |time| time.format("%F %R").to_string(),
+            );
+            let _ = writeln!(
+                table,
+                "{key:<20} {:>5} {:>7}  {last_refresh}",
+                stats.hits, stats.misses
+            );
+        }
+        table.push_str("```");
+        table
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, owners_only)]
+/// Add your own gifs to the gif cache
+pub(crate) async fn add(ctx: ApplicationContext<'_, '_>) -> Result<(), CommandError> {
+    let collection = poise::execute_modal::<_, _, GifCollection>(ctx, None, Some(MODAL_TIMEOUT));
+    let Some(collection) = collection.await? else {
+        // The modal was closed or timed out.
+        return Ok(());
+    };
+    let ctx = Context::Application(ctx);
+    let locale = i18n::locale(ctx).await;
+    let key = collection.key.trim().to_lowercase();
+    let mut gifs = Vec::new();
+    let mut invalid = None;
+    for line in collection
+        .gifs
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+    {
+        let Ok(url) = Url::parse(line) else {
+            invalid = Some(line);
+            break;
+        };
+        gifs.push(url);
+    }
+    let content = if let Some(url) = invalid {
+        Text::CacheInvalidUrl.format(locale, &[("url", url)])
+    } else if key.is_empty() || gifs.is_empty() {
+        Text::NoGifs.get(locale).to_string()
+    } else {
+        let count = gifs.len().to_string();
+        let lifetime = ctx.config().update_cache_lifetime;
+        let gifs: Arc<[Url]> = gifs.into();
+        ctx.gif_cache()
+            .insert_with_duration(key.clone(), gifs, lifetime)
+            .await;
+        Text::CacheAdded.format(locale, &[("count", count.as_str()), ("key", key.as_str())])
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, owners_only)]
+/// Remove gifs from the gif cache
+pub(crate) async fn invalidate(
+    ctx: Context<'_, '_>,
+    #[description = "The cached query"] key: String,
+    #[description = "Remove every query starting with the key"] prefix: Option<bool>,
+) -> Result<(), CommandError> {
+    let gif_cache = ctx.gif_cache();
+    let locale = i18n::locale(ctx).await;
+    let content = if prefix.unwrap_or(false) {
+        let count = gif_cache.invalidate_prefix(&key).await.to_string();
+        Text::CacheRemovedPrefix.format(locale, &[("count", count.as_str()), ("key", key.as_str())])
+    } else if gif_cache.invalidate(&key).await {
+        Text::CacheRemoved.format(locale, &[("key", key.as_str())])
+    } else {
+        Text::CacheNotCached.format(locale, &[("key", key.as_str())])
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
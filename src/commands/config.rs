@@ -0,0 +1,31 @@
+use poise::CreateReply;
+use tracing::{error, instrument};
+
+use crate::commands::CommandError;
+use crate::config;
+use crate::context::Context;
+use crate::i18n::{self, Text};
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, owners_only, category = "Owner", subcommands("reload"))]
+/// Manage the configuration of the bot
+pub(crate) async fn config(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, owners_only)]
+/// Read the configuration again and apply it without a restart
+pub(crate) async fn reload(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let locale = i18n::locale(ctx).await;
+    let content = match config::reload(&ctx.framework().user_data.config) {
+        Ok(_) => Text::ConfigReloaded.get(locale).to_string(),
+        Err(err) => {
+            error!("Failed to reload config: {err}");
+            Text::ConfigReloadFailed.format(locale, &[("error", err.to_string().as_str())])
+        }
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
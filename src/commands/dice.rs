@@ -0,0 +1,457 @@
+use std::fmt::{self, Display, Write};
+use std::str::FromStr;
+
+use poise::CreateReply;
+use rand::{thread_rng, Rng};
+use tracing::instrument;
+
+use crate::commands::CommandError;
+use crate::consts::MAX_MESSAGE_LENGTH;
+use crate::context::Context;
+use crate::i18n::{self, Text};
+
+const MAX_DICE: u32 = 100;
+const MAX_SIDES: u32 = 1000;
+const MAX_MODIFIER: i32 = 1000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("invalid dice notation")]
+pub(crate) struct InvalidNotation;
+
+/// Which dice count towards the total.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Keep {
+    Highest(u32),
+    Lowest(u32),
+}
+
+/// Roll the dice twice and keep one of the totals.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Mode {
+    /// Keep the higher total.
+    Advantage,
+    /// Keep the lower total.
+    Disadvantage,
+}
+
+/// Dice in the notation `[count]d<sides>[kh<n>|kl<n>][+<modifier>|-<modifier>][adv|dis]`, for
+/// example `2d20+3`, `4d6kh3` or `d20 adv`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Dice {
+    count: u32,
+    sides: u32,
+    keep: Option<Keep>,
+    modifier: i32,
+    mode: Option<Mode>,
+}
+
+impl FromStr for Dice {
+    type Err = InvalidNotation;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let notation: String = s
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_lowercase();
+        let (notation, mode) = if let Some(notation) = notation.strip_suffix("adv") {
+            (notation, Some(Mode::Advantage))
+        } else if let Some(notation) = notation.strip_suffix("dis") {
+            (notation, Some(Mode::Disadvantage))
+        } else {
+            (notation.as_str(), None)
+        };
+
+        let (count, rest) = notation.split_once('d').ok_or(InvalidNotation)?;
+        let count = match count {
+            "" => 1,
+            count => parse_number(count)?,
+        };
+        let (sides, rest) = take_number(rest)?;
+        let (keep, rest) = if let Some(rest) = rest.strip_prefix("kl") {
+            let (keep, rest) = take_number(rest)?;
+            (Some(Keep::Lowest(keep)), rest)
+        } else if let Some(rest) = rest.strip_prefix("kh").or_else(|| rest.strip_prefix('k')) {
+            let (keep, rest) = take_number(rest)?;
+            (Some(Keep::Highest(keep)), rest)
+        } else {
+            (None, rest)
+        };
+        let modifier = if let Some(rest) = rest.strip_prefix('+') {
+            parse_modifier(rest)?
+        } else if let Some(rest) = rest.strip_prefix('-') {
+            -parse_modifier(rest)?
+        } else if rest.is_empty() {
+            0
+        } else {
+            return Err(InvalidNotation);
+        };
+
+        let kept = match keep {
+            None => count,
+            Some(Keep::Highest(kept) | Keep::Lowest(kept)) => kept,
+        };
+        if !(1..=MAX_DICE).contains(&count)
+            || !(2..=MAX_SIDES).contains(&sides)
+            || !(1..=count).contains(&kept)
+        {
+            return Err(InvalidNotation);
+        }
+        Ok(Self {
+            count,
+            sides,
+            keep,
+            modifier,
+            mode,
+        })
+    }
+}
+
+/// Split the leading digits of `s` off as a number.
+fn take_number(s: &str) -> Result<(u32, &str), InvalidNotation> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, rest) = s.split_at(end);
+    Ok((parse_number(number)?, rest))
+}
+
+fn parse_number(s: &str) -> Result<u32, InvalidNotation> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(InvalidNotation);
+    }
+    s.parse().map_err(|_| InvalidNotation)
+}
+
+fn parse_modifier(s: &str) -> Result<i32, InvalidNotation> {
+    let modifier = i32::try_from(parse_number(s)?).map_err(|_| InvalidNotation)?;
+    if modifier > MAX_MODIFIER {
+        return Err(InvalidNotation);
+    }
+    Ok(modifier)
+}
+
+impl Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        match self.keep {
+            Some(Keep::Highest(kept)) => write!(f, "kh{kept}")?,
+            Some(Keep::Lowest(kept)) => write!(f, "kl{kept}")?,
+            None => {}
+        }
+        if self.modifier != 0 {
+            write!(f, "{:+}", self.modifier)?;
+        }
+        match self.mode {
+            Some(Mode::Advantage) => f.write_str(" adv"),
+            Some(Mode::Disadvantage) => f.write_str(" dis"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The outcome of rolling [`Dice`] once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Roll {
+    /// The value of every die and whether it counts towards the total, in the rolled order.
+    dice: Vec<(u32, bool)>,
+    modifier: i32,
+    total: i64,
+}
+
+impl Dice {
+    /// Roll the dice once, ignoring the mode.
+    fn roll_once<R: Rng + ?Sized>(&self, rng: &mut R) -> Roll {
+        let values: Vec<u32> = (0..self.count)
+            .map(|_| rng.gen_range(1..=self.sides))
+            .collect();
+        let mut order: Vec<usize> = (0..values.len()).collect();
+        order.sort_by_key(|&index| values[index]);
+        let kept = match self.keep {
+            None => &order[..],
+            Some(Keep::Highest(kept)) => &order[order.len() - kept as usize..],
+            Some(Keep::Lowest(kept)) => &order[..kept as usize],
+        };
+        let dice: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| (value, kept.contains(&index)))
+            .collect();
+        let total = dice
+            .iter()
+            .filter(|(_, kept)| *kept)
+            .map(|&(value, _)| i64::from(value))
+            .sum::<i64>()
+            + i64::from(self.modifier);
+        Roll {
+            dice,
+            modifier: self.modifier,
+            total,
+        }
+    }
+
+    /// Roll the dice, twice with a mode. Returns the kept roll and the discarded roll.
+    pub(crate) fn roll<R: Rng + ?Sized>(&self, rng: &mut R) -> (Roll, Option<Roll>) {
+        let first = self.roll_once(rng);
+        let Some(mode) = self.mode else {
+            return (first, None);
+        };
+        let second = self.roll_once(rng);
+        let first_is_kept = match mode {
+            Mode::Advantage => first.total >= second.total,
+            Mode::Disadvantage => first.total <= second.total,
+        };
+        if first_is_kept {
+            (first, Some(second))
+        } else {
+            (second, Some(first))
+        }
+    }
+}
+
+impl Display for Roll {
+    /// Formats the roll as `[14, ~~7~~] + 3 = **17**`, with the dice that do not count struck
+    /// through.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_char('[')?;
+        for (index, &(value, kept)) in self.dice.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            if kept {
+                write!(f, "{value}")?;
+            } else {
+                write!(f, "~~{value}~~")?;
+            }
+        }
+        f.write_char(']')?;
+        self.fmt_outcome(f)
+    }
+}
+
+impl Roll {
+    /// Formats the modifier and the total, like ` + 3 = **17**`.
+    fn fmt_outcome(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.modifier {
+            0 => {}
+            modifier if modifier > 0 => write!(f, " + {modifier}")?,
+            modifier => write!(f, " - {}", modifier.unsigned_abs())?,
+        }
+        write!(f, " = **{}**", self.total)
+    }
+}
+
+/// Formats a [`Roll`] without the dice, like `[…] + 3 = **17**`.
+struct Summary<'a>(&'a Roll);
+
+impl Display for Summary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[…]")?;
+        self.0.fmt_outcome(f)
+    }
+}
+
+/// The reply to a roll. The dice are left out when they do not fit in a single message.
+fn roll_message(header: &str, kept: &Roll, discarded: Option<&Roll>) -> String {
+    let content = format_rolls(header, kept, discarded.map(|roll| roll as &dyn Display));
+    if content.chars().count() <= MAX_MESSAGE_LENGTH {
+        return content;
+    }
+    let discarded = discarded.map(Summary);
+    format_rolls(
+        header,
+        &Summary(kept),
+        discarded.as_ref().map(|roll| roll as &dyn Display),
+    )
+}
+
+fn format_rolls(header: &str, kept: &dyn Display, discarded: Option<&dyn Display>) -> String {
+    let mut content = format!("{header}\n{kept}");
+    if let Some(discarded) = discarded {
+        let _ = write!(content, "\n~~{discarded}~~");
+    }
+    content
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, category = "Fun", user_cooldown = 2)]
+/// Roll some dice
+pub(crate) async fn roll(
+    ctx: Context<'_, '_>,
+    #[description = "The dice to roll, like 2d20+3, 4d6kh3 or d20 adv"] dice: String,
+) -> Result<(), CommandError> {
+    let locale = i18n::locale(ctx).await;
+    let Ok(parsed) = dice.parse::<Dice>() else {
+        let content = Text::InvalidDice.format(locale, &[("dice", dice.as_str())]);
+        let reply = CreateReply::default().ephemeral(true).content(content);
+        ctx.send(reply).await?;
+        return Ok(());
+    };
+    let (kept, discarded) = parsed.roll(&mut thread_rng());
+    let notation = format!("`{parsed}`");
+    let header = Text::DiceRolled.format(
+        locale,
+        &[
+            ("user", ctx.author().display_name()),
+            ("dice", notation.as_str()),
+        ],
+    );
+    ctx.say(roll_message(&header, &kept, discarded.as_ref()))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::i18n::Locale;
+    use proptest::prelude::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn dice(count: u32, sides: u32, keep: Option<Keep>, modifier: i32) -> Dice {
+        Dice {
+            count,
+            sides,
+            keep,
+            modifier,
+            mode: None,
+        }
+    }
+
+    #[test]
+    fn parses_notation() {
+        assert_eq!("2d20+3".parse(), Ok(dice(2, 20, None, 3)));
+        assert_eq!("d6".parse(), Ok(dice(1, 6, None, 0)));
+        assert_eq!("4d6kh3".parse(), Ok(dice(4, 6, Some(Keep::Highest(3)), 0)));
+        assert_eq!(
+            "2D8 kl1 - 2".parse(),
+            Ok(dice(2, 8, Some(Keep::Lowest(1)), -2))
+        );
+        let advantage: Dice = "d20 adv".parse().unwrap();
+        assert_eq!(advantage.mode, Some(Mode::Advantage));
+    }
+
+    #[test]
+    fn rejects_invalid_notation() {
+        for notation in [
+            "", "d", "20", "0d6", "2d1", "101d6", "2d6kh3", "+2d6", "2d6+", "d6x",
+        ] {
+            assert_eq!(notation.parse::<Dice>(), Err(InvalidNotation), "{notation}");
+        }
+    }
+
+    #[test]
+    fn formats_roll() {
+        let roll = Roll {
+            dice: vec![(14, true), (7, false)],
+            modifier: -3,
+            total: 11,
+        };
+        assert_eq!(roll.to_string(), "[14, ~~7~~] - 3 = **11**");
+    }
+
+    #[test]
+    fn formats_summary() {
+        let roll = Roll {
+            dice: vec![(14, true), (7, false)],
+            modifier: 3,
+            total: 17,
+        };
+        assert_eq!(Summary(&roll).to_string(), "[…] + 3 = **17**");
+    }
+
+    #[test]
+    fn roll_message_fits_at_the_limits() {
+        let roll = |kept| Roll {
+            dice: (0..MAX_DICE)
+                .map(|index| (MAX_SIDES, index < kept))
+                .collect(),
+            modifier: -MAX_MODIFIER,
+            total: i64::from(MAX_SIDES) - i64::from(MAX_MODIFIER),
+        };
+        let dice = Dice {
+            count: MAX_DICE,
+            sides: MAX_SIDES,
+            keep: Some(Keep::Lowest(1)),
+            modifier: -MAX_MODIFIER,
+            mode: Some(Mode::Disadvantage),
+        };
+        let notation = format!("`{dice}`");
+        let user = "x".repeat(32);
+        for locale in [Locale::English, Locale::Dutch] {
+            let header = Text::DiceRolled.format(
+                locale,
+                &[("user", user.as_str()), ("dice", notation.as_str())],
+            );
+            let message = roll_message(&header, &roll(1), Some(&roll(1)));
+            assert!(message.chars().count() <= MAX_MESSAGE_LENGTH, "{message}");
+            let message = roll_message(&header, &roll(MAX_DICE), None);
+            assert!(message.chars().count() <= MAX_MESSAGE_LENGTH, "{message}");
+        }
+    }
+
+    #[test]
+    fn roll_message_keeps_dice_that_fit() {
+        let roll = Roll {
+            dice: vec![(14, true), (7, false)],
+            modifier: 0,
+            total: 14,
+        };
+        assert_eq!(
+            roll_message("header", &roll, None),
+            "header\n[14, ~~7~~] = **14**"
+        );
+    }
+
+    fn any_dice() -> impl Strategy<Value = Dice> {
+        (1..=MAX_DICE, 2..=MAX_SIDES)
+            .prop_flat_map(|(count, sides)| {
+                let keep = prop_oneof![
+                    Just(None),
+                    (1..=count).prop_map(|kept| Some(Keep::Highest(kept))),
+                    (1..=count).prop_map(|kept| Some(Keep::Lowest(kept))),
+                ];
+                let modifier = -MAX_MODIFIER..=MAX_MODIFIER;
+                let mode = prop_oneof![
+                    Just(None),
+                    Just(Some(Mode::Advantage)),
+                    Just(Some(Mode::Disadvantage)),
+                ];
+                (Just(count), Just(sides), keep, modifier, mode)
+            })
+            .prop_map(|(count, sides, keep, modifier, mode)| Dice {
+                count,
+                sides,
+                keep,
+                modifier,
+                mode,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn parse_never_panics(notation in "\\PC*") {
+            let _ = notation.parse::<Dice>();
+        }
+
+        #[test]
+        fn formatted_dice_parse_back(dice in any_dice()) {
+            prop_assert_eq!(dice.to_string().parse::<Dice>(), Ok(dice));
+        }
+
+        #[test]
+        fn roll_stays_within_bounds(dice in any_dice(), seed: u64) {
+            let (roll, discarded) = dice.roll(&mut StdRng::seed_from_u64(seed));
+            prop_assert_eq!(discarded.is_some(), dice.mode.is_some());
+            prop_assert_eq!(roll.dice.len(), dice.count as usize);
+            prop_assert!(roll.dice.iter().all(|&(value, _)| (1..=dice.sides).contains(&value)));
+            let kept: Vec<_> = roll.dice.iter().filter(|(_, kept)| *kept).map(|&(value, _)| value).collect();
+            let expected_kept = match dice.keep {
+                None => dice.count,
+                Some(Keep::Highest(kept) | Keep::Lowest(kept)) => kept,
+            };
+            prop_assert_eq!(kept.len(), expected_kept as usize);
+            let total = kept.iter().map(|&value| i64::from(value)).sum::<i64>() + i64::from(dice.modifier);
+            prop_assert_eq!(roll.total, total);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write;
+
+use futures::Stream;
+use otaku::db::guild_games;
+use poise::CreateReply;
+use tracing::instrument;
+
+use crate::commands::gifs::{self, GifError};
+use crate::commands::CommandError;
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Text};
+use crate::moderation;
+
+/// Separates the aliases of a game.
+const ALIAS_SEPARATOR: char = ',';
+/// The longest name discord allows for an autocomplete choice.
+const MAX_NAME_LENGTH: usize = 100;
+
+#[instrument(skip_all)]
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "Settings",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list")
+)]
+/// Manage the games of /play in this server
+pub(crate) async fn games(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Add a game to /play in this server
+pub(crate) async fn add(
+    ctx: Context<'_, '_>,
+    #[description = "The name of the game"] name: String,
+    #[description = "The query to search GIFs of the game with, the name by default"] query: Option<
+        String,
+    >,
+    #[description = "Other names of the game, separated by ,"] aliases: Option<String>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        let content =
+            Text::InvalidGameName.format(locale, &[("max", &MAX_NAME_LENGTH.to_string())]);
+        let reply = CreateReply::default().ephemeral(true).content(content);
+        ctx.send(reply).await?;
+        return Ok(());
+    }
+    let query = match query.as_deref().map(str::trim) {
+        Some(query) if !query.is_empty() => query.to_lowercase(),
+        _ => gifs::game_query(name),
+    };
+    let aliases = parse_aliases(aliases.as_deref().unwrap_or_default());
+    let moderation = moderation::level(ctx).await;
+    let restricted = std::iter::once(name)
+        .chain(std::iter::once(query.as_str()))
+        .chain(aliases.iter().map(String::as_str))
+        .find(|text| !moderation::is_allowed(text, moderation));
+    if let Some(text) = restricted {
+        return Err(GifError::RestrictedQuery(text.to_string()).into());
+    }
+    let added =
+        guild_games::add_game(ctx.database(), guild_id.into(), name, &aliases, &query).await?;
+    let text = if added {
+        Text::GameAdded
+    } else {
+        Text::GameExists
+    };
+    let content = text.format(locale, &[("game", name)]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+async fn game_autocomplete<'a>(
+    ctx: Context<'a, '_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let lower_partial = partial.to_lowercase();
+    let names: Vec<_> = gifs::guild_games(ctx)
+        .await
+        .into_iter()
+        .map(|game| game.name)
+        .filter(|name| name.to_lowercase().starts_with(&lower_partial))
+        .collect();
+    futures::stream::iter(names)
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Remove a game from /play in this server
+pub(crate) async fn remove(
+    ctx: Context<'_, '_>,
+    #[description = "The name of the game"]
+    #[autocomplete = "game_autocomplete"]
+    name: String,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let name = name.trim();
+    let removed = guild_games::remove_game(ctx.database(), guild_id.into(), name).await?;
+    let text = if removed {
+        Text::GameRemoved
+    } else {
+        Text::GameNotFound
+    };
+    let locale = i18n::locale(ctx).await;
+    let content = text.format(locale, &[("game", name)]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// List the games this server added to /play
+pub(crate) async fn list(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let games = guild_games::get_games(ctx.database(), guild_id.into()).await?;
+    let locale = i18n::locale(ctx).await;
+    let content = if games.is_empty() {
+        Text::GamesEmpty.get(locale).to_string()
+    } else {
+        games.iter().fold(String::new(), |mut content, game| {
+            let _ = write!(content, "**{}**: \"{}\"", game.name, game.query);
+            if !game.aliases.is_empty() {
+                let _ = write!(content, " ({})", game.aliases.join(", "));
+            }
+            content.push('\n');
+            content
+        })
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Split `aliases` at the separator, leaving out empty aliases.
+fn parse_aliases(aliases: &str) -> Vec<String> {
+    aliases
+        .split(ALIAS_SEPARATOR)
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aliases_are_trimmed() {
+        assert_eq!(
+            parse_aliases(" drg ,, rock and stone,"),
+            vec!["drg", "rock and stone"]
+        );
+        assert!(parse_aliases("").is_empty());
+    }
+}
@@ -0,0 +1,225 @@
+use std::fmt::Write;
+
+use otaku::db::gif_blocklist::{self, BlockedGif};
+use otaku::db::gif_overrides::{self, GifOverride};
+use poise::CreateReply;
+use tracing::instrument;
+use url::Url;
+
+use crate::commands::gifs::GifError;
+use crate::commands::CommandError;
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Text};
+use crate::moderation;
+
+/// The commands that can use the custom GIFs of a guild.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub(crate) enum GifCommand {
+    #[name = "sleep"]
+    Sleep,
+    #[name = "play"]
+    Play,
+    #[name = "hurry"]
+    Hurry,
+    #[name = "goodmorning"]
+    Goodmorning,
+}
+
+impl GifCommand {
+    /// The name the custom GIFs of the command are stored under.
+    pub(crate) fn command(self) -> &'static str {
+        match self {
+            GifCommand::Sleep => "sleep",
+            GifCommand::Play => "play",
+            GifCommand::Hurry => "hurry",
+            GifCommand::Goodmorning => "goodmorning",
+        }
+    }
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "Settings",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list", "block", "unblock", "blocked")
+)]
+/// Manage the custom GIFs of this server
+pub(crate) async fn gifconfig(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Add a GIF or a search query to a command in this server
+pub(crate) async fn add(
+    ctx: Context<'_, '_>,
+    #[description = "The command that uses the GIF"] command: GifCommand,
+    #[description = "The link of a GIF or a query to search for"] value: String,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let value = value.trim();
+    let value = if is_url(value) {
+        GifOverride::Url(value.to_string())
+    } else {
+        let query = value.to_lowercase();
+        if !moderation::is_allowed(&query, moderation::level(ctx).await) {
+            return Err(GifError::RestrictedQuery(query).into());
+        }
+        GifOverride::Query(query)
+    };
+    let command = command.command();
+    let added =
+        gif_overrides::add_gif_override(ctx.database(), guild_id.into(), command, &value).await?;
+    let text = if added {
+        Text::CustomGifAdded
+    } else {
+        Text::CustomGifExists
+    };
+    let locale = i18n::locale(ctx).await;
+    let content = text.format(locale, &[("command", command), ("value", value.value())]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Remove a custom GIF from this server
+pub(crate) async fn remove(
+    ctx: Context<'_, '_>,
+    #[description = "The number of the GIF in /gifconfig list"] id: i64,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let removed = gif_overrides::remove_gif_override(ctx.database(), guild_id.into(), id).await?;
+    let text = if removed {
+        Text::CustomGifRemoved
+    } else {
+        Text::CustomGifNotFound
+    };
+    let locale = i18n::locale(ctx).await;
+    let content = text.format(locale, &[("id", &id.to_string())]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// List the custom GIFs of this server
+pub(crate) async fn list(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let overrides = gif_overrides::get_gif_overrides(ctx.database(), guild_id.into()).await?;
+    let locale = i18n::locale(ctx).await;
+    let content = if overrides.is_empty() {
+        Text::CustomGifsEmpty.get(locale).to_string()
+    } else {
+        overrides
+            .iter()
+            .fold(String::new(), |mut content, gif_override| {
+                let value = match &gif_override.value {
+                    GifOverride::Url(url) => format!("<{url}>"),
+                    GifOverride::Query(query) => format!("\"{query}\""),
+                };
+                let _ = writeln!(
+                    content,
+                    "`{}` /{}: {value}",
+                    gif_override.id, gif_override.command
+                );
+                content
+            })
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Stop the bot from posting a GIF or the GIFs of a query in this server
+pub(crate) async fn block(
+    ctx: Context<'_, '_>,
+    #[description = "The link of a GIF or a query"] value: String,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let value = value.trim();
+    let value = if is_url(value) {
+        BlockedGif::Url(value.to_string())
+    } else {
+        BlockedGif::Query(value.to_lowercase())
+    };
+    let blocked = gif_blocklist::block_gif(ctx.database(), guild_id.into(), &value).await?;
+    let text = if blocked {
+        Text::GifBlocked
+    } else {
+        Text::GifAlreadyBlocked
+    };
+    let locale = i18n::locale(ctx).await;
+    let content = text.format(locale, &[("value", value.value())]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Allow a blocked GIF or query again in this server
+pub(crate) async fn unblock(
+    ctx: Context<'_, '_>,
+    #[description = "The number of the entry in /gifconfig blocked"] id: i64,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let unblocked = gif_blocklist::unblock_gif(ctx.database(), guild_id.into(), id).await?;
+    let text = if unblocked {
+        Text::GifUnblocked
+    } else {
+        Text::BlockedGifNotFound
+    };
+    let locale = i18n::locale(ctx).await;
+    let content = text.format(locale, &[("id", &id.to_string())]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// List the blocked GIFs and queries of this server
+pub(crate) async fn blocked(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let entries = gif_blocklist::get_blocked_gifs(ctx.database(), guild_id.into()).await?;
+    let locale = i18n::locale(ctx).await;
+    let content = if entries.is_empty() {
+        Text::BlocklistEmpty.get(locale).to_string()
+    } else {
+        entries.iter().fold(String::new(), |mut content, entry| {
+            let value = match &entry.value {
+                BlockedGif::Url(url) => format!("<{url}>"),
+                BlockedGif::Query(query) => format!("\"{query}\""),
+            };
+            let _ = writeln!(content, "`{}` {value}", entry.id);
+            content
+        })
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Whether a value given to /gifconfig is the link of a GIF, instead of a query.
+fn is_url(value: &str) -> bool {
+    Url::parse(value).is_ok_and(|url| matches!(url.scheme(), "http" | "https"))
+}
@@ -1,21 +1,43 @@
+mod blocklist;
 mod play;
+mod react;
+mod reactions;
 mod sleep;
 
-use crate::commands::CommandError;
-use crate::consts::{LONG_CACHE_LIFETIME, SHORT_CACHE_LIFETIME};
-use crate::context::{Context, GifCacheExt, GifContextExt};
+use crate::cache::CacheLookup;
+use crate::commands::gifconfig::GifCommand;
+use crate::commands::{sessions, sleep_stats, timezone, CommandError};
+use crate::config::RuntimeConfig;
+use crate::consts::HURRY_ESCALATION_DELAY;
+use crate::context::{Command, Context, DatabaseExt, GifCacheExt, GifContextExt};
+use crate::gif_provider::{self, ProviderError};
+use crate::i18n::{self, Locale, Text};
+use crate::moderation;
+use blocklist::Blocklist;
+use chrono::{NaiveTime, TimeDelta, Utc};
 use futures::Stream;
+use otaku::db::gif_blocklist;
+use otaku::db::gif_overrides::{self, GifOverride};
+use otaku::db::guild_games::{self, GuildGame};
+use otaku::db::guild_settings::GifDelivery;
+use otaku::db::hurry_escalations::{self, NewHurryEscalation};
+use otaku::db::GuildSettingsConnection;
 use poise::serenity_prelude as serenity;
+use poise::CreateReply;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use serenity::all::MessageFlags;
-use serenity::{CreateMessage, Mentionable, User};
+use serenity::{
+    CreateAllowedMentions, CreateEmbed, CreateEmbedFooter, CreateMessage, Mentionable, Message,
+    User,
+};
+use sleep::Greeting;
 use std::borrow::Cow;
+use std::collections::HashSet;
+use std::future::Future;
 use std::sync::Arc;
 use std::time::Duration;
-use tenor::error::Error as TenorError;
-use tenor::models::{Gif, MediaFilter};
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 use url::Url;
 
 const MAX_AUTOCOMPLETE_RESULTS: usize = 25;
@@ -24,117 +46,671 @@ const RANDOM_CONFIG: tenor::Config = tenor::Config::new().random(true);
 static HURRY_QUERY: &str = "hurry up";
 static MORBIN_QUERY: &str = "morbin_time";
 
+/// How urgently someone is told to hurry up, every level has its own GIFs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub(crate) enum HurryLevel {
+    #[name = "Nudge"]
+    Nudge,
+    #[name = "Impatient"]
+    Impatient,
+    #[name = "Furious"]
+    Furious,
+}
+
+impl HurryLevel {
+    const ALL: [HurryLevel; 3] = [
+        HurryLevel::Nudge,
+        HurryLevel::Impatient,
+        HurryLevel::Furious,
+    ];
+
+    fn query(self) -> &'static str {
+        match self {
+            HurryLevel::Nudge => HURRY_QUERY,
+            HurryLevel::Impatient => "hurry up impatient",
+            HurryLevel::Furious => "hurry up angry",
+        }
+    }
+
+    fn text(self) -> Text {
+        match self {
+            HurryLevel::Nudge => Text::Hurry,
+            HurryLevel::Impatient => Text::HurryImpatient,
+            HurryLevel::Furious => Text::HurryFurious,
+        }
+    }
+
+    /// The level of the follow-up when the member does not respond.
+    fn escalated(self) -> Self {
+        match self {
+            HurryLevel::Nudge => HurryLevel::Impatient,
+            HurryLevel::Impatient | HurryLevel::Furious => HurryLevel::Furious,
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum GifError {
     #[error(transparent)]
-    Tenor(#[from] TenorError),
+    Provider(#[from] ProviderError),
     #[error("The query \"{0}\" was not allowed")]
     RestrictedQuery(String),
     #[error("no gifs found")]
     NoGifs,
 }
 
-trait GifSliceExt {
-    fn take(&self) -> Result<String, GifError>;
-}
-
-impl GifSliceExt for &[Url] {
-    fn take(&self) -> Result<String, GifError> {
-        let url = self.choose(&mut thread_rng()).ok_or(GifError::NoGifs)?;
-        Ok(url.as_str().into())
-    }
-}
-
-impl GifSliceExt for Arc<[Url]> {
-    fn take(&self) -> Result<String, GifError> {
-        let url = self.choose(&mut thread_rng()).ok_or(GifError::NoGifs)?;
-        Ok(url.as_str().into())
-    }
+/// Pick a GIF of `gifs` that was not one of the last GIFs posted in `channel_id`.
+fn choose_gif(
+    context: &impl GifCacheExt,
+    gifs: &[Url],
+    channel_id: u64,
+) -> Result<String, GifError> {
+    let url = context
+        .gif_cache()
+        .choose_for(gifs, channel_id)
+        .ok_or(GifError::NoGifs)?;
+    Ok(url.into())
 }
 
-// Allow this unused async because autocomplete functions need to be async
-#[allow(clippy::unused_async)]
 async fn play_autocomplete<'a>(
-    _: Context<'_, '_>,
+    ctx: Context<'a, '_>,
     partial: &'a str,
-) -> impl Stream<Item = &'static str> + 'a {
-    play::autocomplete(partial)
+) -> impl Stream<Item = String> + 'a {
+    let guild_games = guild_games(ctx).await;
+    futures::stream::iter(play::autocomplete(partial, &guild_games))
 }
 
 #[instrument(skip_all)]
-#[poise::command(slash_command)]
+#[poise::command(
+    slash_command,
+    category = "Gifs",
+    user_cooldown = 30,
+    channel_cooldown = 10
+)]
 /// Tag someone to play some games with
 pub(crate) async fn play(
-    ctx: Context<'_, '_>,
+    ctx: Context<'_, 'static>,
     #[description = "Who to play games with"] user: Option<User>,
     #[description = "What game you want to play"]
     #[autocomplete = "play_autocomplete"]
     game: Option<String>,
+    #[description = "When to play, such as 20:30 or 8pm in your timezone"] at: Option<String>,
+) -> Result<(), CommandError> {
+    let time = if let Some(at) = at {
+        let Some(time) = sessions::parse_time(&at) else {
+            let locale = i18n::locale(ctx).await;
+            let content = Text::InvalidSessionTime.format(locale, &[("time", &at)]);
+            let reply = CreateReply::default().ephemeral(true).content(content);
+            ctx.send(reply).await?;
+            return Ok(());
+        };
+        Some(time)
+    } else {
+        None
+    };
+    play_with(ctx, user.as_ref(), game, time).await
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    context_menu_command = "Play a game",
+    category = "Gifs",
+    user_cooldown = 30,
+    channel_cooldown = 10
+)]
+/// Tag someone to play some games with
+pub(crate) async fn play_user(ctx: Context<'_, 'static>, user: User) -> Result<(), CommandError> {
+    play_with(ctx, Some(&user), None, None).await
+}
+
+async fn play_with(
+    ctx: Context<'_, 'static>,
+    user: Option<&User>,
+    game: Option<String>,
+    time: Option<NaiveTime>,
 ) -> Result<(), CommandError> {
-    let mention = mention_or_here(user.as_ref());
-    let output = play::get_command_output(&ctx, &mention, game).await?;
-    ctx.reply(output.message).await?;
-    send_gif_message(ctx, output.gif).await?;
+    ctx.defer().await?;
+    let mention = mention_or_here(user);
+    let locale = i18n::locale(ctx).await;
+    let moderation = moderation::level(ctx).await;
+    let custom_gifs = game.is_none();
+    let guild_games = if game.is_some() {
+        guild_games(ctx).await
+    } else {
+        Vec::new()
+    };
+    let channel_id = ctx.channel_id().get();
+    let session_game = time.and(game.clone());
+    let output = play::get_command_output(
+        &ctx,
+        channel_id,
+        locale,
+        moderation,
+        &mention,
+        game,
+        &guild_games,
+    )
+    .await?;
+    let (query, gif) = if custom_gifs {
+        with_custom_gifs(ctx, GifCommand::Play, output.query, output.gif).await
+    } else {
+        (output.query, output.gif)
+    };
+    let (query, gif) = check_blocklist(ctx, query, gif).await?;
+    match time {
+        Some(time) => sessions::start(ctx, session_game.as_deref(), time, output.message).await?,
+        None => {
+            ctx.reply(output.message).await?;
+        }
+    }
+    send_gif_message(ctx, &query, &gif).await?;
+    record_gif_usage(ctx, &query, &gif).await;
     Ok(())
 }
 
 #[instrument(skip_all)]
-#[poise::command(slash_command)]
+#[poise::command(
+    slash_command,
+    category = "Gifs",
+    user_cooldown = 30,
+    channel_cooldown = 10
+)]
 ///Tell someone to hurry up
 pub(crate) async fn hurry(
-    ctx: Context<'_, '_>,
+    ctx: Context<'_, 'static>,
     #[description = "Who should hurry up"] user: Option<User>,
+    #[description = "How urgent it is, a nudge by default"] level: Option<HurryLevel>,
 ) -> Result<(), CommandError> {
-    let mention = mention_or_here(user.as_ref());
-    let gif = get_cached_gif(&ctx, HURRY_QUERY).await?;
-    ctx.reply(format!("{mention}! Hurry up!")).await?;
-    send_gif_message(ctx, gif).await?;
+    hurry_up(ctx, user.as_ref(), level.unwrap_or(HurryLevel::Nudge)).await
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    context_menu_command = "Hurry up",
+    category = "Gifs",
+    user_cooldown = 30,
+    channel_cooldown = 10
+)]
+/// Tell someone to hurry up
+pub(crate) async fn hurry_user(ctx: Context<'_, 'static>, user: User) -> Result<(), CommandError> {
+    hurry_up(ctx, Some(&user), HurryLevel::Nudge).await
+}
+
+async fn hurry_up(
+    ctx: Context<'_, 'static>,
+    user: Option<&User>,
+    level: HurryLevel,
+) -> Result<(), CommandError> {
+    ctx.defer().await?;
+    let mention = mention_or_here(user);
+    let gif = get_cached_gif(
+        &ctx,
+        level.query(),
+        Some(RANDOM_CONFIG),
+        ctx.channel_id().get(),
+    )
+    .await?;
+    let (query, gif) =
+        with_custom_gifs(ctx, GifCommand::Hurry, Cow::Borrowed(level.query()), gif).await;
+    let (query, gif) = check_blocklist(ctx, query, gif).await?;
+    let locale = i18n::locale(ctx).await;
+    ctx.reply(level.text().format(locale, &[("mention", &*mention)]))
+        .await?;
+    let message = send_gif_message(ctx, &query, &gif).await?;
+    record_gif_usage(ctx, &query, &gif).await;
+    if let Some(user) = user.filter(|user| !user.bot) {
+        escalate_hurry(ctx, user, level.escalated(), &message).await;
+    }
     Ok(())
 }
 
+/// Post a GIF of `level` after [`HURRY_ESCALATION_DELAY`] when `user` has not posted anything
+/// after `message` by then.
+async fn escalate_hurry(
+    ctx: Context<'_, 'static>,
+    user: &User,
+    level: HurryLevel,
+    message: &Message,
+) {
+    let gif = match get_cached_gif(
+        &ctx,
+        level.query(),
+        Some(RANDOM_CONFIG),
+        ctx.channel_id().get(),
+    )
+    .await
+    {
+        Ok(gif) => gif,
+        Err(err) => {
+            debug!("No follow-up for hurry up: {err}");
+            return;
+        }
+    };
+    let (query, gif) = match check_blocklist(ctx, Cow::Borrowed(level.query()), gif).await {
+        Ok(gif) => gif,
+        Err(err) => {
+            debug!("No follow-up for hurry up: {err}");
+            return;
+        }
+    };
+    let due_at = Utc::now() + TimeDelta::from_std(HURRY_ESCALATION_DELAY).unwrap_or_default();
+    let escalation = NewHurryEscalation {
+        guild_id: ctx.guild_id().map(Into::into),
+        channel_id: ctx.channel_id().into(),
+        message_id: message.id.into(),
+        user_id: user.id.into(),
+        query: &query,
+        gif: &gif,
+        due_at,
+    };
+    if let Err(err) = hurry_escalations::add_escalation(ctx.database(), &escalation).await {
+        warn!(
+            "Failed to store the hurry up follow-up for {}: {err}",
+            user.id
+        );
+    }
+}
+
 #[instrument(skip_all)]
-#[poise::command(slash_command)]
+#[poise::command(
+    slash_command,
+    category = "Gifs",
+    user_cooldown = 10,
+    channel_cooldown = 5
+)]
 /// It's Morbin time
-pub(crate) async fn morbin(ctx: Context<'_, '_>) -> Result<(), CommandError> {
-    let gif = get_cached_gif(&ctx, MORBIN_QUERY).await?;
-    ctx.reply(gif).await?;
+pub(crate) async fn morbin(ctx: Context<'_, 'static>) -> Result<(), CommandError> {
+    ctx.defer().await?;
+    let gif = get_cached_gif(&ctx, MORBIN_QUERY, None, ctx.channel_id().get()).await?;
+    let (query, gif) = check_blocklist(ctx, Cow::Borrowed(MORBIN_QUERY), gif).await?;
+    reply_gif(ctx, &query, &gif).await?;
+    record_gif_usage(ctx, &query, &gif).await;
     Ok(())
 }
 
 #[instrument(skip_all)]
-#[poise::command(slash_command)]
+#[poise::command(
+    slash_command,
+    category = "Gifs",
+    user_cooldown = 10,
+    channel_cooldown = 5
+)]
 /// Posts a random good night GIF
-pub(crate) async fn sleep(ctx: Context<'_, '_>) -> Result<(), CommandError> {
-    let gif = sleep::get_gif(&ctx).await?;
-    ctx.reply(gif).await?;
+pub(crate) async fn sleep(ctx: Context<'_, 'static>) -> Result<(), CommandError> {
+    greet(ctx, Greeting::Night, GifCommand::Sleep).await
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    slash_command,
+    category = "Gifs",
+    user_cooldown = 10,
+    channel_cooldown = 5
+)]
+/// Posts a random good morning GIF
+pub(crate) async fn goodmorning(ctx: Context<'_, 'static>) -> Result<(), CommandError> {
+    greet(ctx, Greeting::Morning, GifCommand::Goodmorning).await
+}
+
+async fn greet(
+    ctx: Context<'_, 'static>,
+    greeting: Greeting,
+    command: GifCommand,
+) -> Result<(), CommandError> {
+    ctx.defer().await?;
+    let settings = match ctx.guild_id() {
+        Some(guild_id) => Some(ctx.database().get_guild_settings(guild_id.into()).await?),
+        None => None,
+    };
+    let tuning = settings
+        .as_ref()
+        .map(sleep::Tuning::from)
+        .unwrap_or_default();
+    let local_time = timezone::local_time(ctx).await;
+    if greeting == Greeting::Night {
+        sleep_stats::record_night(ctx, local_time).await;
+    }
+    let channel_id = ctx.channel_id().get();
+    let (query, gif) = sleep::get_gif(&ctx, greeting, channel_id, tuning, local_time).await?;
+    let (query, gif) = with_custom_gifs(ctx, command, Cow::Borrowed(query), gif).await;
+    let (query, gif) = check_blocklist(ctx, query, gif).await?;
+    reply_gif(ctx, &query, &gif).await?;
+    record_gif_usage(ctx, &query, &gif).await;
+    Ok(())
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    slash_command,
+    category = "Gifs",
+    user_cooldown = 10,
+    channel_cooldown = 5
+)]
+/// React to someone with a GIF
+async fn reaction(
+    ctx: Context<'_, '_>,
+    #[description = "Who the reaction is for"] user: User,
+) -> Result<(), CommandError> {
+    let Some(reaction) = reactions::find(&ctx.command().name) else {
+        return Ok(());
+    };
+    ctx.defer().await?;
+    let (query, gif) = reactions::get_gif(&ctx, reaction, ctx.channel_id().get()).await?;
+    let (query, gif) = check_blocklist(ctx, Cow::Borrowed(query), gif).await?;
+    let locale = i18n::locale(ctx).await;
+    let author = ctx.author().mention().to_string();
+    let target = user.mention().to_string();
+    let message = reaction
+        .text
+        .format(locale, &[("author", &author), ("target", &target)]);
+    ctx.reply(message).await?;
+    send_gif_message(ctx, &query, &gif).await?;
+    record_gif_usage(ctx, &query, &gif).await;
     Ok(())
 }
 
-async fn send_gif_message(ctx: Context<'_, '_>, gif: String) -> Result<(), serenity::Error> {
-    let gif_message = CreateMessage::new()
+/// A command for every reaction, such as `/hug`, which all run the reaction command.
+pub(crate) fn reactions() -> Vec<Command<'static>> {
+    reactions::reactions()
+        .iter()
+        .map(|reaction_type| {
+            let mut command = reaction();
+            command.name = reaction_type.name.to_string();
+            command.qualified_name = reaction_type.name.to_string();
+            command.identifying_name = reaction_type.name.to_string();
+            command.description = Some(reaction_type.description.to_string());
+            command
+        })
+        .collect()
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    context_menu_command = "GIF react",
+    category = "Gifs",
+    user_cooldown = 10,
+    channel_cooldown = 5
+)]
+/// Reply to a message with a GIF that matches its text
+pub(crate) async fn gif_react(ctx: Context<'_, '_>, message: Message) -> Result<(), CommandError> {
+    ctx.defer_ephemeral().await?;
+    let moderation = moderation::level(ctx).await;
+    let channel_id = message.channel_id.get();
+    let (query, gif) = react::get_gif(&ctx, &message.content, channel_id, moderation).await?;
+    let (query, gif) = check_blocklist(ctx, Cow::Owned(query), gif).await?;
+    let reply = CreateMessage::new()
         .flags(MessageFlags::SUPPRESS_NOTIFICATIONS)
-        .content(gif);
-    ctx.channel_id().send_message(ctx, gif_message).await?;
+        .allowed_mentions(CreateAllowedMentions::new())
+        .reference_message(&message)
+        .content(&gif);
+    message.channel_id.send_message(ctx, reply).await?;
+    record_gif_usage(ctx, &query, &gif).await;
+    let locale = i18n::locale(ctx).await;
+    let msg = CreateReply::default()
+        .ephemeral(true)
+        .content(Text::GifReactSent.get(locale));
+    ctx.send(msg).await?;
+    Ok(())
+}
+
+/// Post `gif` in a new message, as its URL or in an embed depending on the guild settings.
+async fn send_gif_message(
+    ctx: Context<'_, '_>,
+    query: &str,
+    gif: &str,
+) -> Result<Message, serenity::Error> {
+    let gif_message = CreateMessage::new().flags(MessageFlags::SUPPRESS_NOTIFICATIONS);
+    let gif_message = match guild_gif_embed(ctx, query, gif).await {
+        Some(embed) => gif_message.embed(embed),
+        None => gif_message.content(gif),
+    };
+    ctx.channel_id().send_message(ctx, gif_message).await
+}
+
+/// Reply with `gif`, as its URL or in an embed depending on the guild settings.
+async fn reply_gif(ctx: Context<'_, '_>, query: &str, gif: &str) -> Result<(), serenity::Error> {
+    let reply = match guild_gif_embed(ctx, query, gif).await {
+        Some(embed) => CreateReply::default().embed(embed),
+        None => CreateReply::default().content(gif),
+    };
+    ctx.send(reply).await?;
     Ok(())
 }
 
+/// The embed of `gif`, when the guild of `ctx` posts GIFs in embeds.
+async fn guild_gif_embed(ctx: Context<'_, '_>, query: &str, gif: &str) -> Option<CreateEmbed> {
+    let guild_id = ctx.guild_id()?;
+    let delivery = match ctx.database().get_guild_settings(guild_id.into()).await {
+        Ok(settings) => settings.gif_delivery,
+        Err(err) => {
+            warn!("Failed to get the GIF delivery of {guild_id}: {err}");
+            GifDelivery::default()
+        }
+    };
+    if delivery != GifDelivery::Embed {
+        return None;
+    }
+    let locale = i18n::locale(ctx).await;
+    Some(gif_embed(locale, query, gif))
+}
+
+/// The embed that shows `gif` with its `query` and the service that hosts it.
+pub(crate) fn gif_embed(locale: Locale, query: &str, gif: &str) -> CreateEmbed {
+    let embed = CreateEmbed::new().title(query.replace('_', " ")).image(gif);
+    let attribution = Url::parse(gif)
+        .ok()
+        .as_ref()
+        .and_then(gif_provider::attribution);
+    match attribution {
+        Some(provider) => embed.footer(CreateEmbedFooter::new(
+            Text::GifAttribution.format(locale, &[("provider", provider)]),
+        )),
+        None => embed,
+    }
+}
+
+async fn record_gif_usage(ctx: Context<'_, '_>, query: &str, gif: &str) {
+    let guild_id = ctx.guild_id().map(otaku::GuildId::from);
+    let database = ctx.database();
+    if let Err(err) = otaku::db::gif_usage::record_gif_usage(database, guild_id, query, gif).await {
+        error!("Failed to record gif usage for {query}: {err}");
+    }
+}
+
 #[instrument(skip_all)]
 pub(crate) async fn update_gif_cache(context: &impl GifContextExt<'_>) {
-    let tenor = context.tenor();
-    match tenor.search(HURRY_QUERY, Some(RANDOM_CONFIG)).await {
-        Ok(gifs) => {
-            cache_gifs(context, HURRY_QUERY, gifs, LONG_CACHE_LIFETIME).await;
+    let lifetime = context.config().update_cache_lifetime;
+    for level in HurryLevel::ALL {
+        let query = level.query();
+        match search_gifs(context, query, Some(RANDOM_CONFIG)).await {
+            Ok(gifs) => {
+                cache_gifs(context, query, gifs, lifetime).await;
+            }
+            Err(error) => error!("Error caching gifs for {query}: {error}"),
         }
-        Err(error) => error!("Error caching gifs for {HURRY_QUERY}: {error}"),
     }
-    match tenor.search(MORBIN_QUERY, None).await {
+    match search_gifs(context, MORBIN_QUERY, None).await {
         Ok(gifs) => {
-            cache_gifs(context, MORBIN_QUERY, gifs, LONG_CACHE_LIFETIME).await;
+            cache_gifs(context, MORBIN_QUERY, gifs, lifetime).await;
         }
         Err(error) => error!("Error caching gifs for {HURRY_QUERY}: {error}"),
     }
     play::update_gif_cache(context).await;
     sleep::update_gif_cache(context).await;
+    reactions::update_gif_cache(context).await;
+}
+
+/// Cache the GIFs that /hurry, /morbin, /play without a game, /sleep and /goodmorning post, unless
+/// they were loaded from the snapshot. The other GIFs are left to [`update_gif_cache`].
+#[instrument(skip_all)]
+pub(crate) async fn warm_gif_cache(context: &impl GifContextExt<'_>) {
+    warm_query(context, HURRY_QUERY, Some(RANDOM_CONFIG)).await;
+    warm_query(context, MORBIN_QUERY, None).await;
+    play::warm_gif_cache(context).await;
+    sleep::warm_gif_cache(context).await;
+}
+
+/// Whether the cache misses any of the GIFs that [`update_gif_cache`] refreshes, apart from the
+/// seasons of /sleep and /goodmorning.
+pub(crate) async fn is_cache_cold(context: &impl GifContextExt<'_>) -> bool {
+    let queries = HurryLevel::ALL
+        .map(HurryLevel::query)
+        .into_iter()
+        .chain([MORBIN_QUERY])
+        .chain(play::cached_queries())
+        .chain(reactions::cached_queries());
+    for query in queries {
+        if !matches!(context.gif_cache().lookup(query).await, CacheLookup::Hit(_)) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Search and cache the GIFs of `query` when the cache has none.
+async fn warm_query(
+    context: &impl GifContextExt<'_>,
+    query: &'static str,
+    config: Option<tenor::Config<'_>>,
+) {
+    if let CacheLookup::Hit(_) = context.gif_cache().lookup(query).await {
+        return;
+    }
+    let lifetime = context.config().update_cache_lifetime;
+    match search_gifs(context, query, config).await {
+        Ok(gifs) => {
+            cache_gifs(context, query, gifs, lifetime).await;
+        }
+        Err(error) => error!("Error caching gifs for {query}: {error}"),
+    }
+}
+
+/// The names of the seasons of the sleep and good morning collections.
+pub(crate) fn season_names() -> impl Iterator<Item = &'static str> {
+    sleep::season_names()
+}
+
+/// Replace `gif` with another GIF of the cached collection `query` when the guild of `ctx` has
+/// blocked it.
+///
+/// # Errors
+///
+/// Will return an error when the guild has blocked `query` or every GIF of the collection.
+async fn check_blocklist(
+    ctx: Context<'_, '_>,
+    query: Cow<'static, str>,
+    gif: String,
+) -> Result<(Cow<'static, str>, String), GifError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok((query, gif));
+    };
+    let blocklist = match gif_blocklist::get_blocked_gifs(ctx.database(), guild_id.into()).await {
+        Ok(entries) => Blocklist::new(entries),
+        Err(err) => {
+            error!("Failed to get the gif blocklist of guild {guild_id}: {err}");
+            return Ok((query, gif));
+        }
+    };
+    if blocklist.blocks_query(&query) {
+        return Err(GifError::RestrictedQuery(query.into_owned()));
+    }
+    if !blocklist.blocks_url(&gif) {
+        return Ok((query, gif));
+    }
+    let CacheLookup::Hit(gifs) = ctx.gif_cache().lookup(&query).await else {
+        return Err(GifError::NoGifs);
+    };
+    let allowed: Vec<_> = gifs
+        .iter()
+        .filter(|url| !blocklist.blocks_url(url.as_str()))
+        .collect();
+    let gif = allowed.choose(&mut thread_rng()).ok_or(GifError::NoGifs)?;
+    Ok((query, gif.to_string()))
+}
+
+/// Pick between `gif` from the cached collection `query` and the custom GIFs of the guild for
+/// `command`, so every GIF of the merged collection is equally likely.
+async fn with_custom_gifs(
+    ctx: Context<'_, '_>,
+    command: GifCommand,
+    query: Cow<'static, str>,
+    gif: String,
+) -> (Cow<'static, str>, String) {
+    let custom = custom_gifs(ctx, command).await;
+    if custom.is_empty() {
+        return (query, gif);
+    }
+    let collection_size = match ctx.gif_cache().lookup(&query).await {
+        CacheLookup::Hit(gifs) => gifs.len().max(1),
+        CacheLookup::Miss | CacheLookup::KnownEmpty => 1,
+    };
+    let index = thread_rng().gen_range(0..collection_size + custom.len());
+    match index.checked_sub(collection_size) {
+        Some(index) => {
+            let (query, gif) = custom.into_iter().nth(index).unwrap_or_default();
+            (Cow::Owned(query), gif)
+        }
+        None => (query, gif),
+    }
+}
+
+/// The games the guild of `ctx` added to /play.
+pub(crate) async fn guild_games(ctx: Context<'_, '_>) -> Vec<GuildGame> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    match guild_games::get_games(ctx.database(), guild_id.into()).await {
+        Ok(games) => games,
+        Err(err) => {
+            error!("Failed to get the games of guild {guild_id}: {err}");
+            Vec::new()
+        }
+    }
+}
+
+/// The query /play searches GIFs of a game with when no other query is given.
+pub(crate) fn game_query(name: &str) -> String {
+    play::transform_game_to_gif_query(name)
+}
+
+/// The custom GIFs of the guild of `ctx` for `command`, with the query they were found for.
+async fn custom_gifs(ctx: Context<'_, '_>, command: GifCommand) -> Vec<(String, String)> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Vec::new();
+    };
+    let command = command.command();
+    let overrides =
+        gif_overrides::get_command_gif_overrides(ctx.database(), guild_id.into(), command).await;
+    let overrides = match overrides {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            error!("Failed to get the custom {command} gifs of guild {guild_id}: {err}");
+            return Vec::new();
+        }
+    };
+    let mut gifs = Vec::new();
+    for gif_override in overrides {
+        match gif_override.value {
+            GifOverride::Url(url) => gifs.push((command.to_string(), url)),
+            GifOverride::Query(query) => {
+                let urls = match ctx.gif_cache().lookup(&query).await {
+                    CacheLookup::Hit(urls) => urls,
+                    CacheLookup::KnownEmpty => continue,
+                    CacheLookup::Miss => {
+                        match update_cached_gifs(&ctx, query.clone(), None).await {
+                            Ok(urls) => urls,
+                            Err(err) => {
+                                warn!("Failed to search the custom gifs for {query}: {err}");
+                                continue;
+                            }
+                        }
+                    }
+                };
+                gifs.extend(urls.iter().map(|url| (query.clone(), url.to_string())));
+            }
+        }
+    }
+    gifs
 }
 
 fn mention_or_here(user: Option<&User>) -> Cow<'static, str> {
@@ -143,16 +719,57 @@ fn mention_or_here(user: Option<&User>) -> Cow<'static, str> {
     })
 }
 
-async fn get_cached_gif(context: &impl GifContextExt<'_>, query: &str) -> Result<String, GifError> {
-    let cached = get_cached_gifs(context, query)
+/// A cached GIF of `query` that was not one of the last GIFs posted in `channel_id`.
+///
+/// Expired GIFs are still used while they are searched again with `config` in the background, and
+/// missing GIFs are searched before picking one.
+async fn get_cached_gif(
+    context: &impl GifContextExt<'static>,
+    query: &'static str,
+    config: Option<tenor::Config<'static>>,
+    channel_id: u64,
+) -> Result<String, GifError> {
+    let lifetime = context.config().update_cache_lifetime;
+    let gifs = context
+        .gif_cache()
+        .get_or_refresh(query, lifetime, || load_gifs(context, [query], config))
         .await
         .ok_or(GifError::NoGifs)?;
-    cached.take()
+    debug!("Found \"{query}\" gifs in cache ");
+    choose_gif(context, &gifs, channel_id)
+}
+
+/// Search the GIFs of all `queries` for [`crate::cache::Memory::get_or_refresh`], with the
+/// providers and config of `context` at the time of the call. Resolves to `None` when a search
+/// fails or nothing was found.
+fn load_gifs(
+    context: &impl GifContextExt<'static>,
+    queries: impl IntoIterator<Item = &'static str>,
+    config: Option<tenor::Config<'static>>,
+) -> impl Future<Output = Option<Arc<[Url]>>> + Send + 'static {
+    search_all(
+        context.gif_providers().clone(),
+        queries.into_iter().collect(),
+        search_config(context.config(), config),
+    )
 }
 
-async fn get_cached_gifs(context: &impl GifContextExt<'_>, query: &str) -> Option<Arc<[Url]>> {
-    let option = context.gif_cache().get(query).await;
-    option.inspect(|_| debug!("Found \"{query}\" gifs in cache "))
+async fn search_all(
+    providers: gif_provider::Providers<'static>,
+    queries: Vec<&'static str>,
+    config: Option<tenor::Config<'static>>,
+) -> Option<Arc<[Url]>> {
+    let mut gifs = HashSet::new();
+    for query in queries {
+        match providers.search(query, config).await {
+            Ok(found) => gifs.extend(found),
+            Err(error) => {
+                error!("Error caching gifs for {query}: {error}");
+                return None;
+            }
+        }
+    }
+    (!gifs.is_empty()).then(|| gifs.into_iter().collect())
 }
 
 async fn update_cached_gifs(
@@ -161,24 +778,49 @@ async fn update_cached_gifs(
     config: Option<tenor::Config<'_>>,
 ) -> Result<Arc<[Url]>, GifError> {
     let query = query.into();
-    let gifs = context.tenor().search(&query, config).await?;
-    Ok(cache_gifs(context, query, gifs, SHORT_CACHE_LIFETIME).await)
+    let runtime_config = context.config();
+    let gifs = search_gifs(context, &query, config).await?;
+    if gifs.is_empty() {
+        // Remember the empty result, so repeated searches for it do not reach the providers.
+        context
+            .gif_cache()
+            .insert_empty(query, runtime_config.empty_cache_lifetime);
+        return Err(GifError::NoGifs);
+    }
+    Ok(cache_gifs(context, query, gifs, runtime_config.search_cache_lifetime).await)
+}
+
+/// Search the gif providers with the current gif config, falling back to the next provider when
+/// one fails or finds nothing.
+async fn search_gifs(
+    context: &impl GifContextExt<'_>,
+    query: &str,
+    config: Option<tenor::Config<'_>>,
+) -> Result<Vec<Url>, ProviderError> {
+    let config = search_config(context.config(), config);
+    context.gif_providers().search(query, config).await
 }
 
-fn map_gif_to_url(mut gif: Gif) -> Url {
-    gif.media_formats
-        .remove(&MediaFilter::Gif)
-        .map_or(gif.url, |s| s.url)
+/// The gif config of `runtime_config`, with the options of `config` on top.
+fn search_config(
+    runtime_config: RuntimeConfig,
+    config: Option<tenor::Config<'_>>,
+) -> Option<tenor::Config<'_>> {
+    let gif_config = runtime_config.gif_config();
+    match config {
+        Some(config) => gif_config.merge(config),
+        None => Some(gif_config),
+    }
 }
 
 async fn cache_gifs(
     context: &impl GifCacheExt,
     key: impl Into<Cow<'static, str>>,
-    gifs: impl IntoIterator<Item = Gif>,
+    gifs: impl IntoIterator<Item = Url>,
     duration: Duration,
 ) -> Arc<[Url]> {
     let key = key.into();
-    let urls: Arc<[Url]> = gifs.into_iter().map(map_gif_to_url).collect();
+    let urls: Arc<[Url]> = gifs.into_iter().collect();
     info!(gif_count = urls.len(), "Putting \"{key}\" gifs into cache");
     context
         .gif_cache()
@@ -0,0 +1,66 @@
+use std::collections::HashSet;
+
+use otaku::db::gif_blocklist::{BlockedGif, GuildBlockedGif};
+
+/// The GIFs and queries a guild does not want the bot to post.
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    queries: HashSet<String>,
+    urls: HashSet<String>,
+}
+
+impl Blocklist {
+    pub fn new(entries: impl IntoIterator<Item = GuildBlockedGif>) -> Self {
+        let mut blocklist = Self::default();
+        for entry in entries {
+            match entry.value {
+                BlockedGif::Query(query) => {
+                    blocklist.queries.insert(normalize_query(&query));
+                }
+                BlockedGif::Url(url) => {
+                    blocklist.urls.insert(url);
+                }
+            }
+        }
+        blocklist
+    }
+
+    pub fn blocks_query(&self, query: &str) -> bool {
+        self.queries.contains(&normalize_query(query))
+    }
+
+    pub fn blocks_url(&self, url: &str) -> bool {
+        self.urls.contains(url)
+    }
+}
+
+/// Queries are compared without case and with underscores as spaces, so blocking `Apex Legends`
+/// also blocks the `apex_legends` query of /play.
+fn normalize_query(query: &str) -> String {
+    query.trim().to_lowercase().replace('_', " ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn entry(value: BlockedGif) -> GuildBlockedGif {
+        GuildBlockedGif { id: 1, value }
+    }
+
+    #[test]
+    fn queries_match_without_case_and_underscores() {
+        let blocklist = Blocklist::new([entry(BlockedGif::Query("Apex Legends".to_string()))]);
+        assert!(blocklist.blocks_query("apex_legends"));
+        assert!(!blocklist.blocks_query("apex"));
+    }
+
+    #[test]
+    fn urls_match_exactly() {
+        let url = "https://media.tenor.com/abc/frog-dance.gif";
+        let blocklist = Blocklist::new([entry(BlockedGif::Url(url.to_string()))]);
+        assert!(blocklist.blocks_url(url));
+        assert!(!blocklist.blocks_url("https://media.tenor.com/abc/frog.gif"));
+        assert!(!blocklist.blocks_query(url));
+    }
+}
@@ -1,9 +1,11 @@
-use super::{cache_gifs, update_cached_gifs, GifSliceExt};
+use super::{cache_gifs, choose_gif, search_gifs, update_cached_gifs, warm_query};
+use crate::cache::CacheLookup;
 use crate::commands::gifs::{get_cached_gif, GifError, MAX_AUTOCOMPLETE_RESULTS};
-use crate::consts::LONG_CACHE_LIFETIME;
 use crate::context::GifContextExt;
-use futures::{Stream, StreamExt};
-use rustrict::CensorStr;
+use crate::i18n::{Locale, Text};
+use crate::moderation;
+use otaku::db::guild_games::GuildGame;
+use otaku::db::guild_settings::ModerationLevel;
 use std::borrow::Cow;
 use tenor::Config;
 use tracing::error;
@@ -17,6 +19,70 @@ struct GameQuery {
     matches: &'static [&'static str],
 }
 
+/// A game of the autocompletion, built in or added by a guild.
+struct Game<'a> {
+    name: &'a str,
+    query: &'a str,
+    /// Lowercase names that the game is found with.
+    matches: Vec<Cow<'a, str>>,
+}
+
+impl Game<'_> {
+    /// The number of typos between `lower_partial` and the start of the closest name of the game.
+    fn prefix_distance(&self, lower_partial: &str) -> usize {
+        self.matches
+            .iter()
+            .filter_map(|name| edit_distances(lower_partial, name).into_iter().min())
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// The number of typos between `lower_input` and the closest name of the game.
+    fn distance(&self, lower_input: &str) -> usize {
+        self.matches
+            .iter()
+            .filter_map(|name| edit_distances(lower_input, name).pop())
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+}
+
+impl<'a> From<&'a GameQuery> for Game<'a> {
+    fn from(game: &'a GameQuery) -> Self {
+        let matches = std::iter::once(Cow::Owned(game.name.to_lowercase()))
+            .chain(game.matches.iter().copied().map(Cow::Borrowed))
+            .collect();
+        Game {
+            name: game.name,
+            query: game.query,
+            matches,
+        }
+    }
+}
+
+impl<'a> From<&'a GuildGame> for Game<'a> {
+    fn from(game: &'a GuildGame) -> Self {
+        let matches = std::iter::once(&game.name)
+            .chain(&game.aliases)
+            .map(|name| Cow::Owned(name.to_lowercase()))
+            .collect();
+        Game {
+            name: &game.name,
+            query: &game.query,
+            matches,
+        }
+    }
+}
+
+/// The games of a guild followed by the built in games, so a guild can replace the query of a
+/// built in game.
+fn games(guild_games: &[GuildGame]) -> impl Iterator<Item = Game<'_>> {
+    guild_games
+        .iter()
+        .map(Game::from)
+        .chain(GAME_AUTOCOMPLETION.iter().map(Game::from))
+}
+
 static GAME_AUTOCOMPLETION: &[GameQuery] = &[
     GameQuery {
         name: "Apex Legends",
@@ -72,76 +138,211 @@ static GAME_AUTOCOMPLETION: &[GameQuery] = &[
 
 pub struct CommandOutput {
     pub message: String,
+    pub query: Cow<'static, str>,
     pub gif: String,
 }
 
-pub fn autocomplete(partial: &str) -> impl Stream<Item = &'static str> + '_ {
+/// The names of the games that start like `partial`, including the games of the guild.
+/// The names of the games that start like `partial` with a few typos at most, including the
+/// games of the guild. The closest games come first.
+pub fn autocomplete(partial: &str, guild_games: &[GuildGame]) -> Vec<String> {
     let lower_partial = partial.to_lowercase();
-    futures::stream::iter(GAME_AUTOCOMPLETION)
-        .filter(move |GameQuery { matches, .. }| {
-            futures::future::ready(matches.iter().any(|s| s.starts_with(&lower_partial)))
-        })
-        .map(|&GameQuery { name, .. }| futures::future::ready(name))
-        .buffered(MAX_AUTOCOMPLETE_RESULTS)
-        .take(MAX_AUTOCOMPLETE_RESULTS)
+    let max_typos = max_typos(&lower_partial);
+    let mut games: Vec<_> = games(guild_games)
+        .map(|game| (game.prefix_distance(&lower_partial), game))
+        .filter(|(distance, _)| *distance <= max_typos)
+        .collect();
+    // The sort is stable, so the games of the guild stay in front of the built in games.
+    games.sort_by_key(|(distance, _)| *distance);
+    let mut names: Vec<String> = Vec::new();
+    for (_, game) in games {
+        if names.len() >= MAX_AUTOCOMPLETE_RESULTS {
+            break;
+        }
+        if !names
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(game.name))
+        {
+            names.push(game.name.to_string());
+        }
+    }
+    names
 }
 
 pub async fn get_command_output(
-    context: &impl GifContextExt<'_>,
+    context: &impl GifContextExt<'static>,
+    channel_id: u64,
+    locale: Locale,
+    moderation: ModerationLevel,
     mention: &str,
     game: Option<String>,
+    guild_games: &[GuildGame],
 ) -> Result<CommandOutput, GifError> {
-    let gif = match &game {
-        None => get_cached_gif(context, PLAY_FALLBACK).await?,
+    let (query, gif) = match &game {
+        None => (
+            Cow::Borrowed(PLAY_FALLBACK),
+            get_cached_gif(context, PLAY_FALLBACK, Some(FALLBACK_CONFIG), channel_id).await?,
+        ),
         Some(game) => {
-            let query = transform_query(game)?;
-            match get_cached_gif(context, &query).await {
-                Ok(gif) => gif,
-                Err(GifError::NoGifs) => {
+            let query = transform_query(game, guild_games, moderation)?;
+            let gif = match context.gif_cache().lookup(&query).await {
+                CacheLookup::Hit(gifs) => choose_gif(context, &gifs, channel_id)?,
+                CacheLookup::KnownEmpty => return Err(GifError::NoGifs),
+                CacheLookup::Miss => {
                     let gifs = update_cached_gifs(context, query.clone(), None).await?;
-                    gifs.take()?
+                    choose_gif(context, &gifs, channel_id)?
                 }
-                Err(err) => Err(err)?,
-            }
+            };
+            (query, gif)
         }
     };
     let message = if let Some(game) = &game {
-        format!("{mention}! Let's play some {game}!")
+        Text::PlayGame.format(locale, &[("mention", mention), ("game", game.as_str())])
     } else {
-        format!("{mention}! Let's play a game!")
+        Text::PlayAnyGame.format(locale, &[("mention", mention)])
     };
-    Ok(CommandOutput { message, gif })
+    Ok(CommandOutput {
+        message,
+        query,
+        gif,
+    })
 }
 
 pub async fn update_gif_cache(context: &impl GifContextExt<'_>) {
-    let tenor = context.tenor();
+    let lifetime = context.config().update_cache_lifetime;
     for GameQuery { query, .. } in GAME_AUTOCOMPLETION {
-        match tenor.search(query, None).await {
+        match search_gifs(context, query, None).await {
             Ok(gifs) => {
-                cache_gifs(context, *query, gifs, LONG_CACHE_LIFETIME).await;
+                cache_gifs(context, *query, gifs, lifetime).await;
             }
             Err(error) => error!("Error caching gifs for {query}: {error}"),
         };
     }
-    match tenor.search(PLAY_FALLBACK, Some(FALLBACK_CONFIG)).await {
+    match search_gifs(context, PLAY_FALLBACK, Some(FALLBACK_CONFIG)).await {
         Ok(gifs) => {
-            cache_gifs(context, PLAY_FALLBACK, gifs, LONG_CACHE_LIFETIME).await;
+            cache_gifs(context, PLAY_FALLBACK, gifs, lifetime).await;
         }
         Err(error) => error!("Error caching gifs for {PLAY_FALLBACK}: {error}"),
     };
 }
 
-fn transform_query(input: &str) -> Result<Cow<'static, str>, GifError> {
-    let query = GAME_AUTOCOMPLETION
+/// The queries that [`update_gif_cache`] refreshes.
+pub fn cached_queries() -> impl Iterator<Item = &'static str> {
+    GAME_AUTOCOMPLETION
         .iter()
-        .find(|GameQuery { name, .. }| name == &input);
-    match query {
-        Some(GameQuery { query, .. }) => Ok(Cow::Borrowed(query)),
-        None if input.is_inappropriate() => Err(GifError::RestrictedQuery(input.to_string())),
+        .map(|game| game.query)
+        .chain([PLAY_FALLBACK])
+}
+
+/// Cache the GIFs of /play without a game, unless the cache has them.
+pub async fn warm_gif_cache(context: &impl GifContextExt<'_>) {
+    warm_query(context, PLAY_FALLBACK, Some(FALLBACK_CONFIG)).await;
+}
+
+fn transform_query(
+    input: &str,
+    guild_games: &[GuildGame],
+    moderation: ModerationLevel,
+) -> Result<Cow<'static, str>, GifError> {
+    let lower_input = input.to_lowercase();
+    let game = games(guild_games)
+        .find(|game| game.name.eq_ignore_ascii_case(input))
+        .or_else(|| {
+            games(guild_games)
+                .map(|game| (game.distance(&lower_input), game))
+                .filter(|(distance, _)| *distance <= max_typos(&lower_input))
+                .min_by_key(|(distance, _)| *distance)
+                .map(|(_, game)| game)
+        });
+    match game {
+        Some(Game { query, .. }) => Ok(Cow::Owned(query.to_string())),
+        None if !moderation::is_allowed(input, moderation) => {
+            Err(GifError::RestrictedQuery(input.to_string()))
+        }
         None => Ok(Cow::Owned(transform_game_to_gif_query(input))),
     }
 }
 
-fn transform_game_to_gif_query(game: &str) -> String {
+/// The number of typos that are allowed in `text`, one for every three characters.
+fn max_typos(text: &str) -> usize {
+    text.chars().count() / 3
+}
+
+/// The edit distance between `a` and every prefix of `b`, from the empty prefix to the whole of
+/// `b`. Swapping two adjacent characters counts as a single edit.
+fn edit_distances(a: &str, b: &str) -> Vec<usize> {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, &a_char) in a.iter().enumerate() {
+        let mut current = Vec::with_capacity(b.len() + 1);
+        current.push(i + 1);
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && a_char == b[j - 1] && a[i - 1] == b_char {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current.push(distance);
+        }
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+    previous
+}
+
+pub fn transform_game_to_gif_query(game: &str) -> String {
     game.to_lowercase().replace(' ', "_")
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn guild_game(name: &str, aliases: &[&str], query: &str) -> GuildGame {
+        GuildGame {
+            id: 1,
+            name: name.to_string(),
+            aliases: aliases.iter().map(ToString::to_string).collect(),
+            query: query.to_string(),
+        }
+    }
+
+    #[test]
+    fn autocomplete_includes_guild_games() {
+        let guild_games = [guild_game("Deep Rock Galactic", &["DRG"], "deep_rock")];
+        assert_eq!(
+            autocomplete("drg", &guild_games),
+            vec!["Deep Rock Galactic"]
+        );
+        assert_eq!(autocomplete("apex", &guild_games), vec!["Apex Legends"]);
+    }
+
+    #[test]
+    fn edit_distance_counts_swaps_once() {
+        assert_eq!(edit_distances("leauge", "league").pop(), Some(1));
+        assert_eq!(edit_distances("kitten", "sitting").pop(), Some(3));
+        assert_eq!(edit_distances("", "abc"), vec![0, 1, 2, 3]);
+        assert_eq!(edit_distances("leag", "league").into_iter().min(), Some(0));
+    }
+
+    #[test]
+    fn games_are_found_with_typos() {
+        assert_eq!(autocomplete("leauge", &[]), vec!["League of Legends"]);
+        assert_eq!(autocomplete("phasmo", &[]), vec!["Phasmophobia"]);
+        let query = transform_query("leauge of legends", &[], ModerationLevel::Standard);
+        assert_eq!(query.unwrap(), "league_of_legends");
+        let query = transform_query("minecraft", &[], ModerationLevel::Standard);
+        assert_eq!(query.unwrap(), "minecraft");
+    }
+
+    #[test]
+    fn guild_games_replace_built_in_games() {
+        let guild_games = [guild_game("Halo Infinite", &[], "halo_infinite")];
+        assert_eq!(autocomplete("halo", &guild_games), vec!["Halo Infinite"]);
+        let query = transform_query("Halo Infinite", &guild_games, ModerationLevel::Standard);
+        assert_eq!(query.unwrap(), "halo_infinite");
+        let query = transform_query("Halo Infinite", &[], ModerationLevel::Standard);
+        assert_eq!(query.unwrap(), "halo");
+    }
+}
@@ -0,0 +1,83 @@
+use super::{choose_gif, update_cached_gifs};
+use crate::cache::CacheLookup;
+use crate::commands::gifs::GifError;
+use crate::context::GifContextExt;
+use crate::moderation;
+use otaku::db::guild_settings::ModerationLevel;
+
+/// Longest query that is searched for a message, longer messages are cut off.
+const MAX_QUERY_LENGTH: usize = 50;
+
+/// Find a gif for the `text` of a message in `channel_id`, returns the searched query and the gif.
+pub async fn get_gif(
+    context: &impl GifContextExt<'_>,
+    text: &str,
+    channel_id: u64,
+    moderation: ModerationLevel,
+) -> Result<(String, String), GifError> {
+    let query = transform_message_to_query(text, moderation)?;
+    let gif = match context.gif_cache().lookup(&query).await {
+        CacheLookup::Hit(gifs) => choose_gif(context, &gifs, channel_id)?,
+        CacheLookup::KnownEmpty => return Err(GifError::NoGifs),
+        CacheLookup::Miss => {
+            let gifs = update_cached_gifs(context, query.clone(), None).await?;
+            choose_gif(context, &gifs, channel_id)?
+        }
+    };
+    Ok((query, gif))
+}
+
+/// Turn the text of a message into a search query, leaving out mentions, emojis and links.
+fn transform_message_to_query(
+    content: &str,
+    moderation: ModerationLevel,
+) -> Result<String, GifError> {
+    let words: Vec<_> = content
+        .split_whitespace()
+        .filter(|word| !word.starts_with('<') && !word.starts_with("http"))
+        .collect();
+    let query: String = words
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(MAX_QUERY_LENGTH)
+        .collect();
+    let query = query.trim_end().to_string();
+    if query.is_empty() {
+        return Err(GifError::NoGifs);
+    }
+    if !moderation::is_allowed(&query, moderation) {
+        return Err(GifError::RestrictedQuery(query));
+    }
+    Ok(query)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_skips_mentions_and_links() {
+        let query = transform_message_to_query(
+            "<@1234> Good  Morning https://example.com",
+            ModerationLevel::Standard,
+        );
+        assert_eq!(query.unwrap(), "good morning");
+    }
+
+    #[test]
+    fn query_is_cut_off() {
+        let query =
+            transform_message_to_query(&"a".repeat(100), ModerationLevel::Standard).unwrap();
+        assert_eq!(query.chars().count(), MAX_QUERY_LENGTH);
+    }
+
+    #[test]
+    fn message_without_text_has_no_gifs() {
+        let query = transform_message_to_query(
+            "<:emoji:1234> https://example.com/cat.gif",
+            ModerationLevel::Standard,
+        );
+        assert!(matches!(query, Err(GifError::NoGifs)));
+    }
+}
@@ -0,0 +1,117 @@
+use super::{cache_gifs, choose_gif, search_gifs, update_cached_gifs};
+use crate::cache::CacheLookup;
+use crate::commands::gifs::GifError;
+use crate::context::GifContextExt;
+use crate::i18n::Text;
+use tenor::Config;
+use tracing::error;
+
+const REACTION_CONFIG: Config = super::RANDOM_CONFIG;
+
+/// A command that posts a GIF of the author doing something to another member.
+pub struct Reaction {
+    /// The name of the slash command.
+    pub name: &'static str,
+    pub description: &'static str,
+    query: &'static str,
+    /// The message above the GIF, with the `{author}` and `{target}` mentions.
+    pub text: Text,
+}
+
+static REACTIONS: &[Reaction] = &[
+    Reaction {
+        name: "hug",
+        description: "Give someone a hug",
+        query: "anime_hug",
+        text: Text::Hug,
+    },
+    Reaction {
+        name: "pat",
+        description: "Give someone a pat on the head",
+        query: "head_pat",
+        text: Text::Pat,
+    },
+    Reaction {
+        name: "highfive",
+        description: "Give someone a high five",
+        query: "high_five",
+        text: Text::Highfive,
+    },
+];
+
+/// Every reaction command.
+pub fn reactions() -> &'static [Reaction] {
+    REACTIONS
+}
+
+/// Find the reaction of the command `name`.
+pub fn find(name: &str) -> Option<&'static Reaction> {
+    REACTIONS.iter().find(|reaction| reaction.name == name)
+}
+
+/// Get a GIF of `reaction`, together with the query it was found for.
+pub async fn get_gif(
+    context: &impl GifContextExt<'_>,
+    reaction: &Reaction,
+    channel_id: u64,
+) -> Result<(&'static str, String), GifError> {
+    let gif = match context.gif_cache().lookup(reaction.query).await {
+        CacheLookup::Hit(gifs) => choose_gif(context, &gifs, channel_id)?,
+        CacheLookup::KnownEmpty => return Err(GifError::NoGifs),
+        CacheLookup::Miss => {
+            let gifs = update_cached_gifs(context, reaction.query, Some(REACTION_CONFIG)).await?;
+            choose_gif(context, &gifs, channel_id)?
+        }
+    };
+    Ok((reaction.query, gif))
+}
+
+pub async fn update_gif_cache(context: &impl GifContextExt<'_>) {
+    let lifetime = context.config().update_cache_lifetime;
+    for Reaction { query, .. } in REACTIONS {
+        match search_gifs(context, query, Some(REACTION_CONFIG)).await {
+            Ok(gifs) => {
+                cache_gifs(context, *query, gifs, lifetime).await;
+            }
+            Err(error) => error!("Error caching gifs for {query}: {error}"),
+        };
+    }
+}
+
+/// The queries that [`update_gif_cache`] refreshes.
+pub fn cached_queries() -> impl Iterator<Item = &'static str> {
+    REACTIONS.iter().map(|reaction| reaction.query)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn names_are_valid_slash_commands() {
+        for reaction in REACTIONS {
+            assert!(
+                (1..=32).contains(&reaction.name.len())
+                    && reaction.name.bytes().all(|b| b.is_ascii_lowercase()),
+                "{} is not a valid command name",
+                reaction.name
+            );
+        }
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let names: HashSet<_> = REACTIONS.iter().map(|reaction| reaction.name).collect();
+        assert_eq!(names.len(), REACTIONS.len());
+    }
+
+    #[test]
+    fn reactions_are_found_by_name() {
+        assert_eq!(
+            find("hug").map(|reaction| reaction.query),
+            Some("anime_hug")
+        );
+        assert!(find("slap").is_none());
+    }
+}
@@ -1,10 +1,9 @@
 use crate::cache;
-use crate::commands::gifs::GifError;
-use crate::consts::{GIF_COUNT, LONG_CACHE_LIFETIME};
-use crate::context::{GifCacheExt, GifContextExt};
-use chrono::{Datelike, TimeDelta, Utc};
+use crate::commands::gifs::{choose_gif, load_gifs, search_gifs, GifError};
+use crate::context::GifContextExt;
+use chrono::{Datelike, NaiveDateTime, TimeDelta, Timelike, Utc};
 use chrono::{Month, NaiveDate};
-use rand::prelude::SliceRandom;
+use otaku::db::guild_settings::{GuildSettings, SeasonOverride};
 use rand::{thread_rng, Rng};
 use std::collections::HashSet;
 use std::num::NonZeroU8;
@@ -32,12 +31,15 @@ macro_rules! day_of_month {
     };
 }
 
+const HALLOWEEN: DateRange = DateRange {
+    start: day_of_month!(15, Month::October),
+    end: day_of_month!(31, Month::October),
+};
+
 static SLEEP_GIF_COLLECTION: &GifCollection = &GifCollection {
     seasons: &[Season {
-        range: DateRange {
-            start: day_of_month!(15, Month::October),
-            end: day_of_month!(31, Month::October),
-        },
+        name: "halloween",
+        range: HALLOWEEN,
         resolver: GifResolver {
             name: "halloween sleep",
             ratio_override: Some(RatioQuery {
@@ -66,31 +68,147 @@ static SLEEP_GIF_COLLECTION: &GifCollection = &GifCollection {
             "animal_sleep",
         ],
     },
+    hours: Hours { start: 20, end: 5 },
+    outside_hours: GifResolver {
+        name: "nap",
+        ratio_override: None,
+        queries: &["nap", "cat_nap", "dog_nap", "power_nap"],
+    },
+};
+
+static MORNING_GIF_COLLECTION: &GifCollection = &GifCollection {
+    seasons: &[Season {
+        name: "halloween",
+        range: HALLOWEEN,
+        resolver: GifResolver {
+            name: "halloween morning",
+            ratio_override: None,
+            queries: &["halloween_morning", "spooky_morning", "pumpkin_coffee"],
+        },
+    }],
+    default: GifResolver {
+        name: "morning",
+        ratio_override: None,
+        queries: &[
+            "good_morning",
+            "morning_coffee",
+            "dog_morning",
+            "cat_morning",
+            "wake_up",
+            "rise_and_shine",
+        ],
+    },
+    hours: Hours { start: 5, end: 12 },
+    outside_hours: GifResolver {
+        name: "late morning",
+        ratio_override: None,
+        queries: &["woke_up_late", "overslept", "good_afternoon"],
+    },
 };
 
+static COLLECTIONS: [&GifCollection; 2] = [SLEEP_GIF_COLLECTION, MORNING_GIF_COLLECTION];
+
+/// What a GIF command wishes the channel.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Greeting {
+    Night,
+    Morning,
+}
+
+impl Greeting {
+    fn collection(self) -> &'static GifCollection<'static> {
+        match self {
+            Greeting::Night => SLEEP_GIF_COLLECTION,
+            Greeting::Morning => MORNING_GIF_COLLECTION,
+        }
+    }
+}
+
+/// The choices of a guild about its sleep and good morning GIFs.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Tuning<'a> {
+    pub season: Option<&'a SeasonOverride>,
+    pub novelty_ratio: Option<u32>,
+}
+
+impl<'a> From<&'a GuildSettings> for Tuning<'a> {
+    fn from(settings: &'a GuildSettings) -> Self {
+        Self {
+            season: settings.season_override.as_ref(),
+            novelty_ratio: settings.novelty_ratio,
+        }
+    }
+}
+
+/// Get a GIF for `greeting` in the current season, together with the name of the collection it
+/// was picked from. GIFs that were recently posted in `channel_id` are avoided.
+///
+/// With the local time of the member, the season follows their date and a greeting outside of
+/// its hours, such as a good morning in the afternoon, uses the collection for those hours.
 #[instrument(skip_all, err)]
-pub async fn get_gif(context: &impl GifCacheExt) -> Result<String, GifError> {
-    let date = Utc::now().date_naive();
-    SLEEP_GIF_COLLECTION
-        .current(date)
-        .get_gif(context.gif_cache())
-        .await
+pub async fn get_gif(
+    context: &impl GifContextExt<'static>,
+    greeting: Greeting,
+    channel_id: u64,
+    tuning: Tuning<'_>,
+    local_time: Option<NaiveDateTime>,
+) -> Result<(&'static str, String), GifError> {
+    let date = local_time.map_or_else(|| Utc::now().date_naive(), |time| time.date());
+    let hour = local_time.map(|time| time.hour());
+    let resolver = greeting.collection().current(date, hour, tuning.season);
+    let gif = resolver
+        .get_gif(context, channel_id, tuning.novelty_ratio)
+        .await?;
+    Ok((resolver.name, gif))
+}
+
+/// The names of the seasons, which a guild can choose to always use.
+pub fn season_names() -> impl Iterator<Item = &'static str> {
+    let mut names: Vec<_> = COLLECTIONS
+        .iter()
+        .flat_map(|collection| collection.seasons)
+        .map(|season| season.name)
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names.into_iter()
 }
 
 pub async fn update_gif_cache(context: &impl GifContextExt<'_>) {
+    // Members in other timezones can be in the next season a day early.
+    let date = Utc::now().date_naive() + TimeDelta::days(1);
+    for collection in COLLECTIONS {
+        for &Season {
+            resolver, range, ..
+        } in collection.seasons
+        {
+            if !range.should_cache(date) {
+                continue;
+            }
+            if let Err(error) = update_sleep_resolver_cache(context, resolver).await {
+                error!("Error caching gifs for {}: {error}", resolver.name);
+            }
+        }
+        for resolver in [collection.default, collection.outside_hours] {
+            if let Err(error) = update_sleep_resolver_cache(context, resolver).await {
+                error!("Error caching gifs for {}: {error}", resolver.name);
+            }
+        }
+    }
+}
+
+/// Cache the collections of today, unless the cache has them.
+pub async fn warm_gif_cache(context: &impl GifContextExt<'_>) {
     let date = Utc::now().date_naive();
-    for &Season { resolver, range } in SLEEP_GIF_COLLECTION.seasons {
-        if !range.should_cache(date) {
+    for collection in COLLECTIONS {
+        let resolver = collection.current(date, None, None);
+        if let cache::CacheLookup::Hit(_) = context.gif_cache().lookup(resolver.name).await {
             continue;
         }
         if let Err(error) = update_sleep_resolver_cache(context, resolver).await {
             error!("Error caching gifs for {}: {error}", resolver.name);
         }
     }
-    let resolver = SLEEP_GIF_COLLECTION.default;
-    if let Err(error) = update_sleep_resolver_cache(context, resolver).await {
-        error!("Error caching gifs for {}: {error}", resolver.name);
-    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -148,10 +266,31 @@ impl DateRange {
     }
 }
 
+/// The hours of the day a greeting is meant for, `end` is exclusive and can be before `start`
+/// for hours around midnight.
+#[derive(Debug, Copy, Clone)]
+struct Hours {
+    start: u32,
+    end: u32,
+}
+
+impl Hours {
+    fn contains(self, hour: u32) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&hour)
+        } else {
+            hour >= self.start || hour < self.end
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct GifCollection<'a> {
     seasons: &'a [Season<'a>],
     default: GifResolver<'a>,
+    hours: Hours,
+    /// Used instead of the other resolvers outside of `hours`.
+    outside_hours: GifResolver<'a>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -170,6 +309,8 @@ struct RatioQuery {
 
 #[derive(Debug, Clone, Copy)]
 struct Season<'a> {
+    /// Identifies the season in the settings of a guild, shared between the collections.
+    name: &'static str,
     range: DateRange,
     resolver: GifResolver<'a>,
 }
@@ -179,7 +320,25 @@ type CollectionData<'a> = &'a [&'a str];
 impl<'a> GifCollection<'a> {
     #[must_use]
     #[instrument(skip_all)]
-    fn current(&self, date: NaiveDate) -> GifResolver {
+    fn current(
+        &self,
+        date: NaiveDate,
+        hour: Option<u32>,
+        season: Option<&SeasonOverride>,
+    ) -> GifResolver {
+        if hour.is_some_and(|hour| !self.hours.contains(hour)) {
+            return self.outside_hours;
+        }
+        match season {
+            Some(SeasonOverride::Disabled) => return self.default,
+            Some(SeasonOverride::Season(name)) => {
+                let chosen = self.seasons.iter().find(|s| s.name == name);
+                if let Some(season) = chosen {
+                    return season.resolver;
+                }
+            }
+            None => {}
+        }
         let season = self.seasons.iter().find(|s| s.range.contains(date));
         match season {
             None => self.default,
@@ -191,25 +350,49 @@ impl<'a> GifCollection<'a> {
     }
 }
 
-impl<'a> GifResolver<'a> {
+impl GifResolver<'static> {
+    /// A GIF of the collection, expired GIFs are searched again in the background. A season chosen
+    /// by the guild is not cached outside of its dates, its GIFs are searched first then.
     #[instrument(skip_all, err)]
-    async fn get_gif(&self, gif_cache: &cache::Memory<[Url]>) -> Result<String, GifError> {
-        if let Some(query) = self.get_override() {
+    async fn get_gif(
+        &self,
+        context: &impl GifContextExt<'static>,
+        channel_id: u64,
+        novelty_ratio: Option<u32>,
+    ) -> Result<String, GifError> {
+        if let Some(query) = self.get_override(novelty_ratio) {
             debug!("Found gif override");
             return Ok(query.to_string());
         }
-        let collection = gif_cache.get(self.name).await.ok_or(GifError::NoGifs)?;
-        let gif = collection
-            .choose(&mut thread_rng())
+        let lifetime = context.config().update_cache_lifetime;
+        let loader = || {
+            load_gifs(
+                context,
+                self.queries.iter().copied(),
+                Some(SLEEP_GIF_CONFIG),
+            )
+        };
+        let gifs = context
+            .gif_cache()
+            .get_or_refresh(self.name, lifetime, loader)
+            .await
             .ok_or(GifError::NoGifs)?;
-        Ok(gif.as_str().to_string())
+        choose_gif(context, &gifs, channel_id)
     }
 
+    /// Pick the novelty GIF by chance, `novelty_ratio` replaces the denominator of the ratio and
+    /// disables the novelty GIF when `0`.
     #[must_use]
-    fn get_override(&self) -> Option<&'static str> {
-        self.ratio_override
-            .filter(|ratio| thread_rng().gen_ratio(ratio.numerator, ratio.denominator))
-            .map(|query| query.query)
+    fn get_override(&self, novelty_ratio: Option<u32>) -> Option<&'static str> {
+        let ratio = self.ratio_override?;
+        let denominator = novelty_ratio.unwrap_or(ratio.denominator);
+        if denominator == 0 {
+            return None;
+        }
+        let numerator = ratio.numerator.min(denominator);
+        thread_rng()
+            .gen_ratio(numerator, denominator)
+            .then_some(ratio.query)
     }
 }
 
@@ -217,19 +400,20 @@ async fn update_sleep_resolver_cache(
     context: &impl GifContextExt<'_>,
     resolver: GifResolver<'_>,
 ) -> Result<(), GifError> {
-    let max_capacity = resolver.queries.len() * usize::from(GIF_COUNT);
+    let runtime_config = context.config();
+    let max_capacity = resolver.queries.len() * usize::from(runtime_config.gif_count);
     let mut gif_collection: HashSet<Url> = HashSet::with_capacity(max_capacity);
-    let (tenor, gif_cache) = context.gif_context();
     for &query in resolver.queries {
-        let gifs = tenor.search(query, Some(SLEEP_GIF_CONFIG)).await?;
-        gif_collection.extend(gifs.into_iter().map(|gif| gif.url));
+        let gifs = search_gifs(context, query, Some(SLEEP_GIF_CONFIG)).await?;
+        gif_collection.extend(gifs);
     }
     let name = resolver.name;
     let urls: Arc<[Url]> = gif_collection.into_iter().collect();
     let gif_count = urls.len();
     info!(gif_count, "Putting \"{name}\" gifs into cache");
-    gif_cache
-        .insert_with_duration(name, urls, LONG_CACHE_LIFETIME)
+    context
+        .gif_cache()
+        .insert_with_duration(name, urls, runtime_config.update_cache_lifetime)
         .await;
     Ok(())
 }
@@ -243,7 +427,7 @@ mod test {
         let mut occurences = 0u32;
         let iterations = 10_000_000u32;
         for _ in 0..iterations {
-            if SLEEP_GIF_COLLECTION.default.get_override().is_some() {
+            if SLEEP_GIF_COLLECTION.default.get_override(None).is_some() {
                 occurences += 1;
             };
         }
@@ -256,7 +440,7 @@ mod test {
     fn all_seasons_have_valid_dates() {
         let years = [(2023, false), (2024, true)];
         for (year, leap_year) in years {
-            for Season { range, .. } in SLEEP_GIF_COLLECTION.seasons {
+            for Season { range, .. } in COLLECTIONS.iter().flat_map(|c| c.seasons) {
                 let start = range.start.adjust_for_leap_year(leap_year);
                 assert!(
                     start.to_naive_date(year).is_some(),
@@ -372,4 +556,54 @@ mod test {
         let date = NaiveDate::from_ymd_opt(2024, 11, 1).unwrap();
         assert!(!range.contains(date));
     }
+
+    #[test]
+    fn disabled_novelty_ratio_never_overrides() {
+        let resolver = SLEEP_GIF_COLLECTION.default;
+        assert!((0..10_000).all(|_| resolver.get_override(Some(0)).is_none()));
+    }
+
+    #[test]
+    fn novelty_ratio_of_one_always_overrides() {
+        let resolver = SLEEP_GIF_COLLECTION.default;
+        assert!((0..100).all(|_| resolver.get_override(Some(1)).is_some()));
+    }
+
+    #[test]
+    fn season_override_replaces_the_calendar() {
+        let halloween = NaiveDate::from_ymd_opt(2024, 10, 20).unwrap();
+        let summer = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let collection = SLEEP_GIF_COLLECTION;
+        let current = |date, season| collection.current(date, None, season).name;
+        assert_eq!(current(halloween, None), "halloween sleep");
+        let disabled = SeasonOverride::Disabled;
+        assert_eq!(current(halloween, Some(&disabled)), "sleep");
+        let season = SeasonOverride::Season("halloween".to_string());
+        assert_eq!(current(summer, Some(&season)), "halloween sleep");
+    }
+
+    #[test]
+    fn hours_wrap_around_midnight() {
+        let night = Hours { start: 20, end: 5 };
+        assert!(night.contains(23));
+        assert!(night.contains(0));
+        assert!(!night.contains(5));
+        assert!(!night.contains(12));
+        let morning = Hours { start: 5, end: 12 };
+        assert!(morning.contains(5));
+        assert!(!morning.contains(12));
+    }
+
+    #[test]
+    fn greeting_outside_its_hours_uses_the_other_collection() {
+        let date = NaiveDate::from_ymd_opt(2024, 7, 1).unwrap();
+        let morning = MORNING_GIF_COLLECTION;
+        assert_eq!(morning.current(date, Some(8), None).name, "morning");
+        assert_eq!(morning.current(date, Some(15), None).name, "late morning");
+        assert_eq!(morning.current(date, None, None).name, "morning");
+        assert_eq!(
+            SLEEP_GIF_COLLECTION.current(date, Some(14), None).name,
+            "nap"
+        );
+    }
 }
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use otaku::db::GuildSettingsConnection;
+use poise::serenity_prelude as serenity;
+use poise::CreateReply;
+use serenity::{
+    CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage,
+};
+use tracing::instrument;
+
+use crate::commands::CommandError;
+use crate::components::{ComponentEvent, CustomId};
+use crate::context::{Command, Context, DatabaseExt};
+use crate::permissions;
+
+/// The page of the commands without a category.
+const UNCATEGORIZED: &str = "Other";
+
+/// The namespace of the buttons that switch between the pages.
+pub(crate) const COMPONENT_NAMESPACE: &str = "help";
+/// How long the buttons of a help message can be used.
+const PAGES_TIMEOUT: Duration = Duration::from_secs(600);
+const PREVIOUS_ACTION: &str = "previous";
+const NEXT_ACTION: &str = "next";
+
+/// The pages of a help message and the page that is shown.
+struct HelpPages {
+    pages: Vec<String>,
+    current: AtomicUsize,
+}
+
+#[instrument(skip_all)]
+#[poise::command(slash_command)]
+/// Show the commands of the bot
+pub(crate) async fn help(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let settings = match ctx.guild_id() {
+        Some(guild_id) => Some(ctx.database().get_guild_settings(guild_id.into()).await?),
+        None => None,
+    };
+    let author_level = permissions::author_level(ctx, settings.as_ref()).await;
+
+    let mut categories: BTreeMap<&str, Vec<&Command>> = BTreeMap::new();
+    for command in &ctx.framework().options().commands {
+        let disabled = settings
+            .as_ref()
+            .is_some_and(|settings| settings.disabled_commands.contains(&command.name));
+        if command.hide_in_help
+            || disabled
+            || permissions::required_level(command, settings.as_ref()) > author_level
+        {
+            continue;
+        }
+        let category = command.category.as_deref().unwrap_or(UNCATEGORIZED);
+        categories.entry(category).or_default().push(command);
+    }
+
+    let pages: Vec<String> = categories
+        .into_iter()
+        .map(|(category, commands)| help_page(category, &commands))
+        .collect();
+    let mut reply = CreateReply::default().content(pages.first().cloned().unwrap_or_default());
+    if pages.len() > 1 {
+        let components = &ctx.framework().user_data.components;
+        let state = Arc::new(HelpPages {
+            pages,
+            current: AtomicUsize::new(0),
+        });
+        let state = components.insert(COMPONENT_NAMESPACE, state, PAGES_TIMEOUT);
+        let button = |action: &'static str, emoji: char| {
+            let id = CustomId {
+                namespace: COMPONENT_NAMESPACE,
+                state,
+                action,
+            };
+            CreateButton::new(id.to_string()).emoji(emoji)
+        };
+        reply = reply.components(vec![CreateActionRow::Buttons(vec![
+            button(PREVIOUS_ACTION, '◀'),
+            button(NEXT_ACTION, '▶'),
+        ])]);
+    }
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Show the previous or next page of a help message.
+pub(crate) fn handle_component(
+    event: ComponentEvent<'_>,
+) -> BoxFuture<'_, Result<(), serenity::Error>> {
+    Box::pin(async move {
+        let Some(state) = event.state::<HelpPages>() else {
+            return Ok(());
+        };
+        let count = state.pages.len();
+        let step = match event.id.action {
+            PREVIOUS_ACTION => count - 1,
+            NEXT_ACTION => 1,
+            _ => return Ok(()),
+        };
+        let current = state.current.load(Ordering::Relaxed);
+        let page = (current + step) % count;
+        state.current.store(page, Ordering::Relaxed);
+        let message = CreateInteractionResponseMessage::new().content(&state.pages[page]);
+        let response = CreateInteractionResponse::UpdateMessage(message);
+        event.interaction.create_response(event.ctx, response).await
+    })
+}
+
+/// List the commands of `category` with their subcommands.
+fn help_page(category: &str, commands: &[&Command]) -> String {
+    let mut page = format!("**{category}**\n");
+    for command in commands {
+        write_command(&mut page, command);
+        for subcommand in &command.subcommands {
+            write_command(&mut page, subcommand);
+        }
+    }
+    page
+}
+
+fn write_command(page: &mut String, command: &Command) {
+    if !command.subcommands.is_empty() {
+        return;
+    }
+    let description = command.description.as_deref().unwrap_or_default();
+    match &command.context_menu_name {
+        Some(name) if command.slash_action.is_none() => {
+            let _ = writeln!(page, "`{name}` (context menu) {description}");
+        }
+        _ => {
+            let _ = writeln!(page, "`/{}` {description}", command.qualified_name);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use futures::future::BoxFuture;
+use otaku::db::polls::{self, NewPoll};
+use otaku::db::Pool;
+use poise::serenity_prelude as serenity;
+use poise::CreateReply;
+use serenity::{
+    CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+    CreateInteractionResponseMessage, UserId,
+};
+use tracing::{instrument, warn};
+
+use crate::commands::CommandError;
+use crate::components::{ComponentEvent, CustomId};
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Locale, Text};
+use crate::moderation;
+
+/// The namespace of the vote buttons.
+pub(crate) const COMPONENT_NAMESPACE: &str = "poll";
+/// Separates the options of a poll.
+const OPTION_SEPARATOR: char = ';';
+const MIN_OPTIONS: usize = 2;
+const MAX_OPTIONS: usize = 10;
+/// The longest label discord allows on a button.
+const MAX_LABEL_LENGTH: usize = 80;
+/// The most buttons discord allows in an action row.
+const MAX_BUTTONS_PER_ROW: usize = 5;
+const DEFAULT_DURATION_MINUTES: u32 = 60;
+
+/// A poll and the votes on it, every member has one vote.
+struct Poll {
+    question: String,
+    options: Vec<String>,
+    locale: Locale,
+    closes_at: DateTime<Utc>,
+    votes: Mutex<HashMap<UserId, usize>>,
+}
+
+impl Poll {
+    /// Vote for the option at `index`, replacing an earlier vote of `user`.
+    fn vote(&self, user: UserId, index: usize) {
+        let mut votes = self.votes.lock().unwrap_or_else(PoisonError::into_inner);
+        votes.insert(user, index);
+    }
+
+    /// The number of votes of every option.
+    fn tallies(&self) -> Vec<usize> {
+        let votes = self.votes.lock().unwrap_or_else(PoisonError::into_inner);
+        tally(self.options.len(), votes.values().copied())
+    }
+
+    fn embed(&self) -> CreateEmbed {
+        let time = format!("<t:{}:R>", self.closes_at.timestamp());
+        let status = Text::PollCloses.format(self.locale, &[("time", time.as_str())]);
+        embed(
+            &self.question,
+            &self.options,
+            &self.tallies(),
+            self.locale,
+            &status,
+        )
+    }
+}
+
+/// A poll that is stored in the database, so it is closed by the `polls` job even when the bot
+/// restarts before it closes.
+struct StoredPoll {
+    id: i64,
+    pool: Pool,
+    poll: Poll,
+}
+
+/// Count the votes for each of the `options` options, ignoring votes for unknown options.
+fn tally(options: usize, votes: impl IntoIterator<Item = usize>) -> Vec<usize> {
+    let mut tallies = vec![0; options];
+    for index in votes {
+        if let Some(tally) = tallies.get_mut(index) {
+            *tally += 1;
+        }
+    }
+    tallies
+}
+
+fn embed(
+    question: &str,
+    options: &[String],
+    tallies: &[usize],
+    locale: Locale,
+    status: &str,
+) -> CreateEmbed {
+    let mut description = String::new();
+    for (option, votes) in options.iter().zip(tallies) {
+        let _ = writeln!(description, "{option}: **{votes}**");
+    }
+    description.push('\n');
+    description.push_str(status);
+    let total = tallies.iter().sum::<usize>().to_string();
+    let footer = Text::PollVotes.format(locale, &[("votes", total.as_str())]);
+    CreateEmbed::new()
+        .title(question)
+        .description(description)
+        .footer(CreateEmbedFooter::new(footer))
+}
+
+/// The embed of a poll that closed, with the final tallies.
+pub(crate) fn closed_embed(poll: &polls::Poll) -> CreateEmbed {
+    let locale = Locale::from_code(&poll.locale).unwrap_or(Locale::English);
+    let tallies = tally(poll.options.len(), poll.votes.iter().copied());
+    embed(
+        &poll.question,
+        &poll.options,
+        &tallies,
+        locale,
+        Text::PollClosed.get(locale),
+    )
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, category = "Fun", user_cooldown = 30)]
+/// Let the members vote on a question
+pub(crate) async fn poll(
+    ctx: Context<'_, '_>,
+    #[description = "What to vote on"] question: String,
+    #[description = "Up to 10 options, separated by ;"] options: String,
+    #[description = "How long the poll is open, one hour by default"]
+    #[min = 1]
+    #[max = 10080]
+    minutes: Option<u32>,
+) -> Result<(), CommandError> {
+    let locale = i18n::locale(ctx).await;
+    let options = parse_options(&options);
+    let error = if (MIN_OPTIONS..=MAX_OPTIONS).contains(&options.len()) {
+        let moderation = moderation::level(ctx).await;
+        let allowed = std::iter::once(&question)
+            .chain(&options)
+            .all(|text| moderation::is_allowed(text, moderation));
+        (!allowed).then_some(Text::PollNotAllowed)
+    } else {
+        Some(Text::PollInvalidOptions)
+    };
+    if let Some(error) = error {
+        let reply = CreateReply::default()
+            .ephemeral(true)
+            .content(error.get(locale));
+        ctx.send(reply).await?;
+        return Ok(());
+    }
+
+    let minutes = minutes.unwrap_or(DEFAULT_DURATION_MINUTES);
+    let duration = Duration::from_secs(u64::from(minutes) * 60);
+    let closes_at = Utc::now() + duration;
+    let new_poll = NewPoll {
+        channel_id: ctx.channel_id().into(),
+        guild_id: ctx.guild_id().map(otaku::GuildId::from),
+        question: &question,
+        options: &options,
+        locale: locale.code(),
+        closes_at,
+    };
+    let id = polls::add_poll(ctx.database(), &new_poll).await?;
+    let poll = Arc::new(StoredPoll {
+        id,
+        pool: ctx.database().clone(),
+        poll: Poll {
+            question,
+            options,
+            locale,
+            closes_at,
+            votes: Mutex::new(HashMap::new()),
+        },
+    });
+    let components = &ctx.framework().user_data.components;
+    let state = components.insert(COMPONENT_NAMESPACE, poll.clone(), duration);
+    let buttons: Vec<_> = poll
+        .poll
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| {
+            let action = index.to_string();
+            let id = CustomId {
+                namespace: COMPONENT_NAMESPACE,
+                state,
+                action: &action,
+            };
+            let label: String = option.chars().take(MAX_LABEL_LENGTH).collect();
+            CreateButton::new(id.to_string()).label(label)
+        })
+        .collect();
+    let rows = buttons
+        .chunks(MAX_BUTTONS_PER_ROW)
+        .map(|buttons| CreateActionRow::Buttons(buttons.to_vec()))
+        .collect();
+    let reply = CreateReply::default()
+        .embed(poll.poll.embed())
+        .components(rows);
+    let message = ctx.send(reply).await?.into_message().await?;
+    polls::set_message(ctx.database(), id, message.id.into()).await?;
+    Ok(())
+}
+
+/// Split `options` at the separator, leaving out empty options.
+fn parse_options(options: &str) -> Vec<String> {
+    options
+        .split(OPTION_SEPARATOR)
+        .map(str::trim)
+        .filter(|option| !option.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Count the vote of a member and show the new tallies.
+pub(crate) fn handle_component(
+    event: ComponentEvent<'_>,
+) -> BoxFuture<'_, Result<(), serenity::Error>> {
+    Box::pin(async move {
+        let Some(stored) = event.state::<StoredPoll>() else {
+            return Ok(());
+        };
+        let poll = &stored.poll;
+        let Some(index) = event
+            .id
+            .action
+            .parse::<usize>()
+            .ok()
+            .filter(|&index| index < poll.options.len())
+        else {
+            return Ok(());
+        };
+        let user = event.interaction.user.id;
+        match polls::set_vote(&stored.pool, stored.id, user.into(), index).await {
+            Ok(true) => poll.vote(user, index),
+            // The poll closed while the member voted.
+            Ok(false) => {}
+            Err(err) => warn!(
+                poll = stored.id,
+                "Failed to store the vote of {user}: {err}"
+            ),
+        }
+        let message = CreateInteractionResponseMessage::new().embed(poll.embed());
+        let response = CreateInteractionResponse::UpdateMessage(message);
+        event.interaction.create_response(event.ctx, response).await
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn poll(options: &[&str]) -> Poll {
+        Poll {
+            question: "Pizza?".to_string(),
+            options: options.iter().map(ToString::to_string).collect(),
+            locale: Locale::English,
+            closes_at: Utc::now(),
+            votes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn options_are_trimmed() {
+        assert_eq!(parse_options(" yes ;; no;"), vec!["yes", "no"]);
+    }
+
+    #[test]
+    fn members_have_one_vote() {
+        let poll = poll(&["yes", "no"]);
+        poll.vote(UserId::new(1), 0);
+        poll.vote(UserId::new(2), 0);
+        poll.vote(UserId::new(1), 1);
+        assert_eq!(poll.tallies(), vec![1, 1]);
+    }
+
+    #[test]
+    fn votes_for_unknown_options_are_ignored() {
+        assert_eq!(tally(3, [0, 2, 2, 3]), vec![1, 0, 2]);
+    }
+}
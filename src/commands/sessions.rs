@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use chrono::{DateTime, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+use futures::future::BoxFuture;
+use otaku::db::play_sessions::{self, NewPlaySession};
+use otaku::db::Pool;
+use poise::serenity_prelude as serenity;
+use poise::CreateReply;
+use serenity::{
+    ButtonStyle, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse,
+    CreateInteractionResponseMessage, Mentionable, UserId,
+};
+use tracing::warn;
+
+use crate::commands::{timezone, CommandError};
+use crate::components::{ComponentEvent, CustomId};
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Locale, Text};
+
+/// The namespace of the RSVP buttons.
+pub(crate) const COMPONENT_NAMESPACE: &str = "session";
+const JOIN_ACTION: &str = "join";
+const DECLINE_ACTION: &str = "decline";
+
+/// A planned session of /play and the answers of the members to it.
+struct Session {
+    id: i64,
+    pool: Pool,
+    locale: Locale,
+    starts_at: DateTime<Utc>,
+    /// Whether every member that answered joins the session.
+    rsvps: Mutex<HashMap<UserId, bool>>,
+}
+
+impl Session {
+    /// Store the answer of `user`, replacing an earlier answer.
+    fn rsvp(&self, user: UserId, attending: bool) {
+        let mut rsvps = self.rsvps.lock().unwrap_or_else(PoisonError::into_inner);
+        rsvps.insert(user, attending);
+    }
+
+    /// The mentions of the members that gave `answer`.
+    fn mentions(&self, answer: bool) -> String {
+        let rsvps = self.rsvps.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut users: Vec<_> = rsvps
+            .iter()
+            .filter(|(_, &attending)| attending == answer)
+            .map(|(user, _)| *user)
+            .collect();
+        users.sort_unstable();
+        if users.is_empty() {
+            return Text::SessionNobody.get(self.locale).to_string();
+        }
+        users
+            .iter()
+            .map(|user| user.mention().to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn embed(&self) -> CreateEmbed {
+        let time = format!("<t:{}:R>", self.starts_at.timestamp());
+        CreateEmbed::new()
+            .description(Text::SessionStarts.format(self.locale, &[("time", time.as_str())]))
+            .field(
+                Text::SessionJoining.get(self.locale),
+                self.mentions(true),
+                true,
+            )
+            .field(
+                Text::SessionDeclined.get(self.locale),
+                self.mentions(false),
+                true,
+            )
+    }
+}
+
+/// Plan a session of `game` at `time` in the timezone of the author and reply with `content` and
+/// the buttons to join or decline it. The author joins the session right away.
+///
+/// # Errors
+///
+/// Will return an error when the session cannot be stored or the reply cannot be sent.
+pub(crate) async fn start(
+    ctx: Context<'_, '_>,
+    game: Option<&str>,
+    time: NaiveTime,
+    content: String,
+) -> Result<(), CommandError> {
+    let locale = i18n::locale(ctx).await;
+    let now = Utc::now();
+    let local_now = timezone::local_time(ctx)
+        .await
+        .unwrap_or_else(|| now.naive_utc());
+    let starts_at = starts_at(now, local_now, time);
+    let host = ctx.author().id;
+    let new_session = NewPlaySession {
+        guild_id: ctx.guild_id().map(Into::into),
+        channel_id: ctx.channel_id().into(),
+        host_id: host.into(),
+        game,
+        starts_at,
+    };
+    let id = play_sessions::add_session(ctx.database(), &new_session).await?;
+    let session = Arc::new(Session {
+        id,
+        pool: ctx.database().clone(),
+        locale,
+        starts_at,
+        rsvps: Mutex::new(HashMap::from([(host, true)])),
+    });
+
+    let timeout = (starts_at - now).to_std().unwrap_or_default();
+    let components = &ctx.framework().user_data.components;
+    let state = components.insert(COMPONENT_NAMESPACE, session.clone(), timeout);
+    let button = |action: &'static str, label: Text, style: ButtonStyle| {
+        let id = CustomId {
+            namespace: COMPONENT_NAMESPACE,
+            state,
+            action,
+        };
+        CreateButton::new(id.to_string())
+            .label(label.get(locale))
+            .style(style)
+    };
+    let reply = CreateReply::default()
+        .content(content)
+        .embed(session.embed())
+        .components(vec![CreateActionRow::Buttons(vec![
+            button(JOIN_ACTION, Text::SessionJoin, ButtonStyle::Success),
+            button(DECLINE_ACTION, Text::SessionDecline, ButtonStyle::Secondary),
+        ])]);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Store the answer of a member and show who joins the session.
+pub(crate) fn handle_component(
+    event: ComponentEvent<'_>,
+) -> BoxFuture<'_, Result<(), serenity::Error>> {
+    Box::pin(async move {
+        let Some(session) = event.state::<Session>() else {
+            return Ok(());
+        };
+        let attending = match event.id.action {
+            JOIN_ACTION => true,
+            DECLINE_ACTION => false,
+            _ => return Ok(()),
+        };
+        let user = event.interaction.user.id;
+        match play_sessions::set_rsvp(&session.pool, session.id, user.into(), attending).await {
+            Ok(true) => session.rsvp(user, attending),
+            // The session started while the member answered.
+            Ok(false) => {}
+            Err(err) => warn!(
+                session = session.id,
+                "Failed to store the rsvp of {user}: {err}"
+            ),
+        }
+        let message = CreateInteractionResponseMessage::new().embed(session.embed());
+        let response = CreateInteractionResponse::UpdateMessage(message);
+        event.interaction.create_response(event.ctx, response).await
+    })
+}
+
+/// Parse a time of day such as `20:30`, `8pm` or `8:30 am`.
+pub(crate) fn parse_time(time: &str) -> Option<NaiveTime> {
+    let time = time.trim().to_lowercase();
+    let (clock, half_offset) = if let Some(clock) = time.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = time.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (time.as_str(), None)
+    };
+    let clock = clock.trim();
+    let (hours, minutes) = clock.split_once(':').unwrap_or((clock, "0"));
+    let is_number =
+        |part: &str| (1..=2).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(hours) || !is_number(minutes) {
+        return None;
+    }
+    let hours = hours.parse::<u32>().ok()?;
+    let minutes = minutes.parse::<u32>().ok()?;
+    let hours = match half_offset {
+        Some(offset) if (1..=12).contains(&hours) => hours % 12 + offset,
+        Some(_) => return None,
+        None => hours,
+    };
+    NaiveTime::from_hms_opt(hours, minutes, 0)
+}
+
+/// The first moment after `now` at which the local clock shows `time`, where `local_now` is the
+/// local time at `now`.
+fn starts_at(now: DateTime<Utc>, local_now: NaiveDateTime, time: NaiveTime) -> DateTime<Utc> {
+    let mut local_start = local_now.date().and_time(time);
+    if local_start <= local_now {
+        local_start += TimeDelta::days(1);
+    }
+    now + (local_start - local_now)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn time(hours: u32, minutes: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hours, minutes, 0).expect("valid time")
+    }
+
+    #[test]
+    fn times_are_parsed() {
+        assert_eq!(parse_time("20:30"), Some(time(20, 30)));
+        assert_eq!(parse_time("8pm"), Some(time(20, 0)));
+        assert_eq!(parse_time("8:15 AM"), Some(time(8, 15)));
+        assert_eq!(parse_time("12am"), Some(time(0, 0)));
+        assert_eq!(parse_time("12pm"), Some(time(12, 0)));
+        assert_eq!(parse_time("9"), Some(time(9, 0)));
+    }
+
+    #[test]
+    fn invalid_times_are_rejected() {
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("13pm"), None);
+        assert_eq!(parse_time("0am"), None);
+        assert_eq!(parse_time("8:60"), None);
+        assert_eq!(parse_time("tonight"), None);
+        assert_eq!(parse_time(""), None);
+    }
+
+    #[test]
+    fn sessions_start_at_the_next_local_time() {
+        let now = DateTime::from_timestamp(12 * 3600, 0).expect("valid timestamp");
+        // Two hours ahead of UTC, so the local time is 14:00.
+        let local_now = now.naive_utc() + TimeDelta::hours(2);
+        let expected = now + TimeDelta::hours(6);
+        assert_eq!(starts_at(now, local_now, time(20, 0)), expected);
+        let expected = now + TimeDelta::hours(23);
+        assert_eq!(starts_at(now, local_now, time(13, 0)), expected);
+    }
+}
@@ -0,0 +1,445 @@
+use futures::Stream;
+use otaku::db::guild_settings::{GifDelivery, ModerationLevel, PermissionLevel, SeasonOverride};
+use otaku::db::GuildSettingsConnection;
+use poise::serenity_prelude::Role;
+use poise::CreateReply;
+use tracing::instrument;
+
+use crate::commands::{gifs, CommandError};
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Locale, Text};
+use crate::permissions;
+
+/// The command that manages the other commands, which can never be disabled.
+const SETTINGS_COMMAND: &str = "settings";
+
+/// The levels a guild can require for a command.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub(crate) enum Permission {
+    #[name = "Everyone"]
+    Everyone,
+    #[name = "Bot managers"]
+    Manager,
+    #[name = "Server admins"]
+    Admin,
+}
+
+impl From<Permission> for PermissionLevel {
+    fn from(permission: Permission) -> Self {
+        match permission {
+            Permission::Everyone => PermissionLevel::Everyone,
+            Permission::Manager => PermissionLevel::Manager,
+            Permission::Admin => PermissionLevel::Admin,
+        }
+    }
+}
+
+/// How strictly a guild can moderate the text of its members.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub(crate) enum Moderation {
+    #[name = "Strict"]
+    Strict,
+    #[name = "Standard"]
+    Standard,
+    #[name = "Lenient"]
+    Lenient,
+}
+
+impl Moderation {
+    fn text(self) -> Text {
+        match self {
+            Moderation::Strict => Text::ModerationStrict,
+            Moderation::Standard => Text::ModerationStandard,
+            Moderation::Lenient => Text::ModerationLenient,
+        }
+    }
+}
+
+impl From<Moderation> for ModerationLevel {
+    fn from(moderation: Moderation) -> Self {
+        match moderation {
+            Moderation::Strict => ModerationLevel::Strict,
+            Moderation::Standard => ModerationLevel::Standard,
+            Moderation::Lenient => ModerationLevel::Lenient,
+        }
+    }
+}
+
+/// How the GIF commands of a guild can post their GIF.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub(crate) enum GifStyle {
+    #[name = "Link"]
+    Link,
+    #[name = "Embed"]
+    Embed,
+}
+
+impl GifStyle {
+    fn text(self) -> Text {
+        match self {
+            GifStyle::Link => Text::GifStyleLink,
+            GifStyle::Embed => Text::GifStyleEmbed,
+        }
+    }
+}
+
+impl From<GifStyle> for GifDelivery {
+    fn from(style: GifStyle) -> Self {
+        match style {
+            GifStyle::Link => GifDelivery::Url,
+            GifStyle::Embed => GifDelivery::Embed,
+        }
+    }
+}
+
+#[instrument(skip_all)]
+#[poise::command(
+    slash_command,
+    guild_only,
+    category = "Settings",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands(
+        "language",
+        "command",
+        "manager",
+        "permission",
+        "moderation",
+        "season",
+        "novelty",
+        "gifstyle"
+    )
+)]
+/// Configure the bot for this server
+pub(crate) async fn settings(_: Context<'_, '_>) -> Result<(), CommandError> {
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Set the language of the replies in this server
+pub(crate) async fn language(
+    ctx: Context<'_, '_>,
+    #[description = "Leave empty to reply in the language of each user"] language: Option<Locale>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let database = ctx.database();
+    let mut settings = database.get_guild_settings(guild_id.into()).await?;
+    settings.locale = language.map(|locale| locale.code().to_string());
+    database.set_guild_settings(&settings).await?;
+
+    let content = match language {
+        Some(locale) => Text::LanguageChanged.get(locale),
+        None => Text::LanguageReset.get(i18n::locale(ctx).await),
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+// Allow this unused async because autocomplete functions need to be async
+#[allow(clippy::unused_async)]
+async fn command_autocomplete<'a>(
+    ctx: Context<'a, '_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let names: Vec<_> = ctx
+        .framework()
+        .options()
+        .commands
+        .iter()
+        .map(|command| command.name.clone())
+        .filter(|name| name != SETTINGS_COMMAND && name.starts_with(partial))
+        .collect();
+    futures::stream::iter(names)
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Allow or forbid a command in this server
+pub(crate) async fn command(
+    ctx: Context<'_, '_>,
+    #[description = "The command to toggle"]
+    #[autocomplete = "command_autocomplete"]
+    name: String,
+    #[description = "Whether the command can be used"] enabled: bool,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let exists = ctx
+        .framework()
+        .options()
+        .commands
+        .iter()
+        .any(|command| command.name == name);
+    let content = if !exists || name == SETTINGS_COMMAND {
+        Text::UnknownCommand.format(locale, &[("command", name.as_str())])
+    } else {
+        let database = ctx.database();
+        let mut settings = database.get_guild_settings(guild_id.into()).await?;
+        settings
+            .disabled_commands
+            .retain(|command| *command != name);
+        let text = if enabled {
+            Text::CommandEnabledInGuild
+        } else {
+            settings.disabled_commands.push(name.clone());
+            Text::CommandDisabledInGuild
+        };
+        database.set_guild_settings(&settings).await?;
+        text.format(locale, &[("command", name.as_str())])
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Allow or forbid the members of a role to manage the bot
+pub(crate) async fn manager(
+    ctx: Context<'_, '_>,
+    #[description = "The role of the bot managers"] role: Role,
+    #[description = "Whether the members of the role manage the bot"] enabled: bool,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let database = ctx.database();
+    let mut settings = database.get_guild_settings(guild_id.into()).await?;
+    let role_id = otaku::RoleId::from(role.id);
+    settings.manager_roles.retain(|&id| id != role_id);
+    let text = if enabled {
+        settings.manager_roles.push(role_id);
+        Text::ManagerRoleAdded
+    } else {
+        Text::ManagerRoleRemoved
+    };
+    database.set_guild_settings(&settings).await?;
+
+    let locale = i18n::locale(ctx).await;
+    let content = text.format(locale, &[("role", role.name.as_str())]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+// Allow this unused async because autocomplete functions need to be async
+#[allow(clippy::unused_async)]
+async fn permission_autocomplete<'a>(
+    ctx: Context<'a, '_>,
+    partial: &'a str,
+) -> impl Stream<Item = String> + 'a {
+    let names: Vec<_> = ctx
+        .framework()
+        .options()
+        .commands
+        .iter()
+        .filter(|command| !command.owners_only && command.name.starts_with(partial))
+        .map(|command| command.name.clone())
+        .collect();
+    futures::stream::iter(names)
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Choose who can use a command in this server
+pub(crate) async fn permission(
+    ctx: Context<'_, '_>,
+    #[description = "The command to change"]
+    #[autocomplete = "permission_autocomplete"]
+    name: String,
+    #[description = "Leave empty to use the default"] level: Option<Permission>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let exists = ctx
+        .framework()
+        .options()
+        .commands
+        .iter()
+        .any(|command| command.name == name && !command.owners_only);
+    let content = if exists {
+        let database = ctx.database();
+        let mut settings = database.get_guild_settings(guild_id.into()).await?;
+        let content = if let Some(level) = level {
+            let level = PermissionLevel::from(level);
+            settings.command_permissions.insert(name.clone(), level);
+            let level = permissions::level_text(level).get(locale);
+            Text::PermissionChanged.format(locale, &[("command", name.as_str()), ("level", level)])
+        } else {
+            settings.command_permissions.remove(&name);
+            Text::PermissionReset.format(locale, &[("command", name.as_str())])
+        };
+        database.set_guild_settings(&settings).await?;
+        content
+    } else {
+        Text::UnknownCommand.format(locale, &[("command", name.as_str())])
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Choose how strictly game names and search queries are moderated in this server
+pub(crate) async fn moderation(
+    ctx: Context<'_, '_>,
+    #[description = "Leave empty to use the default"] level: Option<Moderation>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let database = ctx.database();
+    let mut settings = database.get_guild_settings(guild_id.into()).await?;
+    settings.moderation = level.map(ModerationLevel::from);
+    database.set_guild_settings(&settings).await?;
+
+    let locale = i18n::locale(ctx).await;
+    let content = match level {
+        Some(level) => {
+            let level = level.text().get(locale);
+            Text::ModerationChanged.format(locale, &[("level", level)])
+        }
+        None => Text::ModerationReset.get(locale).to_string(),
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Choosing this season never uses a seasonal collection.
+const SEASONS_OFF: &str = "off";
+
+// Allow this unused async because autocomplete functions need to be async
+#[allow(clippy::unused_async)]
+async fn season_autocomplete<'a>(
+    _: Context<'_, '_>,
+    partial: &'a str,
+) -> impl Stream<Item = &'static str> + 'a {
+    let names: Vec<_> = std::iter::once(SEASONS_OFF)
+        .chain(gifs::season_names())
+        .filter(|name| name.starts_with(partial))
+        .collect();
+    futures::stream::iter(names)
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Choose a seasonal collection of sleep GIFs to always use, or turn them off
+pub(crate) async fn season(
+    ctx: Context<'_, '_>,
+    #[description = "Leave empty to follow the calendar"]
+    #[autocomplete = "season_autocomplete"]
+    season: Option<String>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let season_override = match season.as_deref() {
+        None => None,
+        Some(SEASONS_OFF) => Some(SeasonOverride::Disabled),
+        Some(name) if gifs::season_names().any(|season| season == name) => {
+            Some(SeasonOverride::Season(name.to_string()))
+        }
+        Some(name) => {
+            let content = Text::UnknownSeason.format(locale, &[("season", name)]);
+            let reply = CreateReply::default().ephemeral(true).content(content);
+            ctx.send(reply).await?;
+            return Ok(());
+        }
+    };
+    let content = match &season_override {
+        None => Text::SeasonsFollowCalendar.get(locale).to_string(),
+        Some(SeasonOverride::Disabled) => Text::SeasonsDisabled.get(locale).to_string(),
+        Some(SeasonOverride::Season(name)) => {
+            Text::SeasonChosen.format(locale, &[("season", name.as_str())])
+        }
+    };
+    let database = ctx.database();
+    let mut settings = database.get_guild_settings(guild_id.into()).await?;
+    settings.season_override = season_override;
+    database.set_guild_settings(&settings).await?;
+
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Choose how often a sleep GIF is a surprise
+pub(crate) async fn novelty(
+    ctx: Context<'_, '_>,
+    #[description = "One in how many GIFs is a surprise, 0 for never, empty for the default"]
+    #[max = 100_000]
+    ratio: Option<u32>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let database = ctx.database();
+    let mut settings = database.get_guild_settings(guild_id.into()).await?;
+    settings.novelty_ratio = ratio;
+    database.set_guild_settings(&settings).await?;
+
+    let locale = i18n::locale(ctx).await;
+    let content = match ratio {
+        None => Text::NoveltyReset.get(locale).to_string(),
+        Some(0) => Text::NoveltyDisabled.get(locale).to_string(),
+        Some(ratio) => Text::NoveltyChanged.format(locale, &[("ratio", &ratio.to_string())]),
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only)]
+/// Choose whether GIF commands post a link or an embed with the search and its source
+pub(crate) async fn gifstyle(
+    ctx: Context<'_, '_>,
+    #[description = "How GIFs are posted"] style: GifStyle,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let database = ctx.database();
+    let mut settings = database.get_guild_settings(guild_id.into()).await?;
+    settings.gif_delivery = style.into();
+    database.set_guild_settings(&settings).await?;
+
+    let locale = i18n::locale(ctx).await;
+    let style = style.text().get(locale);
+    let content = Text::GifStyleChanged.format(locale, &[("style", style)]);
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Check for every command whether the guild it is used in has disabled it.
+///
+/// # Errors
+///
+/// Will return an error when the guild settings cannot be read.
+pub(crate) async fn command_check(ctx: Context<'_, '_>) -> Result<bool, CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(true);
+    };
+    let name = ctx
+        .parent_commands()
+        .first()
+        .map_or(&ctx.command().name, |command| &command.name);
+    if name == SETTINGS_COMMAND {
+        return Ok(true);
+    }
+    let settings = ctx.database().get_guild_settings(guild_id.into()).await?;
+    Ok(!settings.disabled_commands.contains(name))
+}
@@ -0,0 +1,191 @@
+use std::fmt::Write;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta, Utc};
+use otaku::db::sleep_nights;
+use poise::serenity_prelude as serenity;
+use poise::CreateReply;
+use serenity::{CreateAllowedMentions, Mentionable, User, UserId};
+use tracing::{instrument, warn};
+
+use crate::commands::CommandError;
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Text};
+
+/// The number of days the most consistent sleepers are counted over.
+const CONSISTENCY_DAYS: u32 = 30;
+const LEADERBOARD_SIZE: i64 = 5;
+
+/// The good nights of a member in a guild.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct Streaks {
+    total: usize,
+    /// Consecutive nights up to tonight or last night.
+    current: usize,
+    longest: usize,
+}
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, guild_only, category = "Gifs")]
+/// Show the good night streaks of a member and the most consistent sleepers
+pub(crate) async fn sleepstats(
+    ctx: Context<'_, '_>,
+    #[description = "Whose streaks to show, yours by default"] user: Option<User>,
+) -> Result<(), CommandError> {
+    let Some(guild_id) = ctx.guild_id() else {
+        return Ok(());
+    };
+    let locale = i18n::locale(ctx).await;
+    let user = user.as_ref().unwrap_or_else(|| ctx.author());
+    let mention = user.mention().to_string();
+    let nights = sleep_nights::get_nights(ctx.database(), guild_id.into(), user.id.into()).await?;
+    let tonight = night_of(Utc::now().naive_utc());
+    let mut content = match streaks(&nights, tonight) {
+        Streaks { total: 0, .. } => Text::SleepStatsEmpty.format(locale, &[("user", &mention)]),
+        Streaks {
+            total,
+            current,
+            longest,
+        } => {
+            let (total, current, longest) =
+                (total.to_string(), current.to_string(), longest.to_string());
+            Text::SleepStats.format(
+                locale,
+                &[
+                    ("user", &mention),
+                    ("total", &total),
+                    ("current", &current),
+                    ("longest", &longest),
+                ],
+            )
+        }
+    };
+
+    let since = tonight - TimeDelta::days(i64::from(CONSISTENCY_DAYS));
+    let sleepers = sleep_nights::get_consistent_sleepers(
+        ctx.database(),
+        guild_id.into(),
+        since,
+        LEADERBOARD_SIZE,
+    )
+    .await?;
+    if !sleepers.is_empty() {
+        let days = CONSISTENCY_DAYS.to_string();
+        let _ = write!(
+            content,
+            "\n\n**{}**",
+            Text::SleepLeaderboard.format(locale, &[("days", &days)])
+        );
+        for (rank, sleeper) in (1..).zip(sleepers) {
+            let rank = rank.to_string();
+            let user = UserId::from(sleeper.user_id).mention().to_string();
+            let nights = sleeper.nights.to_string();
+            let entry = Text::SleepLeaderboardEntry.format(
+                locale,
+                &[("rank", &rank), ("user", &user), ("nights", &nights)],
+            );
+            let _ = write!(content, "\n{entry}");
+        }
+    }
+    let reply = CreateReply::default()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new());
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Record the good night of the author of `ctx` at `local_time`, or the current time in UTC when
+/// they did not set a timezone.
+pub(crate) async fn record_night(ctx: Context<'_, '_>, local_time: Option<NaiveDateTime>) {
+    let Some(guild_id) = ctx.guild_id() else {
+        return;
+    };
+    let night = night_of(local_time.unwrap_or_else(|| Utc::now().naive_utc()));
+    let user_id = ctx.author().id;
+    let result =
+        sleep_nights::record_night(ctx.database(), guild_id.into(), user_id.into(), night).await;
+    if let Err(err) = result {
+        warn!("Failed to record the good night of {user_id}: {err}");
+    }
+}
+
+/// The night a good night at `local_time` belongs to, a good night after midnight counts for the
+/// evening before.
+fn night_of(local_time: NaiveDateTime) -> NaiveDate {
+    (local_time - TimeDelta::hours(12)).date()
+}
+
+/// The streaks of `nights`, which are sorted with the last night first.
+fn streaks(nights: &[NaiveDate], tonight: NaiveDate) -> Streaks {
+    let mut current = 0;
+    let mut longest = 0;
+    let mut run = 0;
+    let mut first_run = true;
+    let mut previous: Option<NaiveDate> = None;
+    for &night in nights {
+        if previous.is_some_and(|previous| previous.pred_opt() == Some(night)) {
+            run += 1;
+        } else {
+            first_run = previous.is_none();
+            run = 1;
+        }
+        previous = Some(night);
+        longest = longest.max(run);
+        if first_run {
+            current = run;
+        }
+    }
+    // The night of a member in another timezone can be a day apart from `tonight`.
+    let recent = nights
+        .first()
+        .and_then(NaiveDate::succ_opt)
+        .is_some_and(|next| next >= tonight);
+    Streaks {
+        total: nights.len(),
+        current: if recent { current } else { 0 },
+        longest,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 10, day).expect("valid date")
+    }
+
+    #[test]
+    fn good_nights_after_midnight_count_for_the_evening() {
+        let evening = date(15).and_hms_opt(23, 0, 0).expect("valid time");
+        let after_midnight = date(16).and_hms_opt(2, 0, 0).expect("valid time");
+        assert_eq!(night_of(evening), date(15));
+        assert_eq!(night_of(after_midnight), date(15));
+    }
+
+    #[test]
+    fn streaks_are_counted() {
+        let nights = [
+            date(20),
+            date(19),
+            date(18),
+            date(15),
+            date(14),
+            date(13),
+            date(12),
+        ];
+        let expected = Streaks {
+            total: 7,
+            current: 3,
+            longest: 4,
+        };
+        assert_eq!(streaks(&nights, date(21)), expected);
+    }
+
+    #[test]
+    fn missed_nights_end_the_current_streak() {
+        let nights = [date(18), date(17)];
+        assert_eq!(streaks(&nights, date(20)).current, 0);
+        assert_eq!(streaks(&nights, date(20)).longest, 2);
+        assert_eq!(streaks(&[], date(20)).total, 0);
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use otaku::ConnectionState;
+use poise::CreateReply;
+use tracing::instrument;
+
+use crate::commands::CommandError;
+use crate::context::{AnimeExt, Context, DatabaseExt, GifCacheExt};
+
+#[instrument(skip_all)]
+#[poise::command(slash_command, owners_only, category = "Owner")]
+/// Show the runtime diagnostics of the bot
+pub(crate) async fn status(ctx: Context<'_, '_>) -> Result<(), CommandError> {
+    let uptime = ctx.framework().user_data.started_at.elapsed();
+    let shards = ctx.framework().shard_manager().runners.lock().await.len();
+    let latency = ctx.ping().await;
+    let gif_cache = ctx.gif_cache();
+    let pool = ctx.database();
+
+    let mut status = String::from("```\n");
+    let _ = writeln!(status, "version    {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(status, "uptime     {}", format_duration(uptime));
+    let _ = writeln!(status, "shards     {shards}");
+    if latency.is_zero() {
+        let _ = writeln!(status, "latency    unknown");
+    } else {
+        let _ = writeln!(status, "latency    {} ms", latency.as_millis());
+    }
+    let _ = writeln!(status, "guilds     {}", ctx.cache().guild_count());
+    let _ = writeln!(
+        status,
+        "gif cache  {} queries, {} gifs",
+        gif_cache.entry_count(),
+        gif_cache.total_weight()
+    );
+    let _ = writeln!(
+        status,
+        "database   {} connections, {} idle",
+        pool.size(),
+        pool.num_idle()
+    );
+    if ctx.anime_api().is_some() {
+        let state = match ctx.anime_state() {
+            ConnectionState::Connecting => "connecting",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Disconnected => "disconnected",
+        };
+        let reconnects = otaku::stream_stats().reconnects;
+        let _ = writeln!(status, "anime api  {state}, {reconnects} reconnects");
+    } else {
+        let _ = writeln!(status, "anime api  disabled");
+    }
+    status.push_str("```");
+
+    let reply = CreateReply::default().ephemeral(true).content(status);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let days = seconds / (24 * 3600);
+    let hours = seconds % (24 * 3600) / 3600;
+    let minutes = seconds % 3600 / 60;
+    format!("{days}d {hours}h {minutes}m")
+}
@@ -0,0 +1,126 @@
+use chrono::{FixedOffset, NaiveDateTime, Utc};
+use otaku::db::user_timezones;
+use poise::CreateReply;
+use tracing::{instrument, warn};
+
+use crate::commands::CommandError;
+use crate::context::{Context, DatabaseExt};
+use crate::i18n::{self, Text};
+
+/// The largest offset from UTC that is in use, UTC+14:00.
+const MAX_OFFSET_MINUTES: i32 = 14 * 60;
+
+#[instrument(skip(ctx))]
+#[poise::command(slash_command, category = "Settings")]
+/// Set your timezone so the bot knows what time it is for you
+pub(crate) async fn timezone(
+    ctx: Context<'_, '_>,
+    #[description = "Your offset from UTC, such as +2 or -5:30, leave empty to remove it"]
+    offset: Option<String>,
+) -> Result<(), CommandError> {
+    let locale = i18n::locale(ctx).await;
+    let user_id = ctx.author().id.into();
+    let content = if let Some(offset) = offset {
+        let Some(minutes) = parse_offset(&offset) else {
+            let content = Text::InvalidTimezone.format(locale, &[("offset", &offset)]);
+            let reply = CreateReply::default().ephemeral(true).content(content);
+            ctx.send(reply).await?;
+            return Ok(());
+        };
+        user_timezones::set_utc_offset(ctx.database(), user_id, Some(minutes)).await?;
+        let offset = format_offset(minutes);
+        Text::TimezoneSet.format(locale, &[("offset", &offset)])
+    } else {
+        user_timezones::set_utc_offset(ctx.database(), user_id, None).await?;
+        Text::TimezoneReset.get(locale).to_string()
+    };
+    let reply = CreateReply::default().ephemeral(true).content(content);
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// The current time of the author of the command, `None` when they did not set a timezone.
+pub(crate) async fn local_time(ctx: Context<'_, '_>) -> Option<NaiveDateTime> {
+    let minutes = match user_timezones::get_utc_offset(ctx.database(), ctx.author().id.into()).await
+    {
+        Ok(minutes) => minutes?,
+        Err(err) => {
+            warn!("Failed to load the timezone of {}: {err}", ctx.author().id);
+            return None;
+        }
+    };
+    let offset = FixedOffset::east_opt(minutes * 60)?;
+    Some(Utc::now().with_timezone(&offset).naive_local())
+}
+
+/// Parse an offset from UTC such as `+2`, `-05:30` or `UTC+1` into minutes.
+fn parse_offset(offset: &str) -> Option<i32> {
+    let offset = offset.trim();
+    let offset = offset
+        .strip_prefix("UTC")
+        .or_else(|| offset.strip_prefix("utc"))
+        .or_else(|| offset.strip_prefix("GMT"))
+        .or_else(|| offset.strip_prefix("gmt"))
+        .unwrap_or(offset)
+        .trim();
+    if offset.is_empty() {
+        return Some(0);
+    }
+    let (sign, offset) = if let Some(offset) = offset.strip_prefix('-') {
+        (-1, offset)
+    } else {
+        (1, offset.strip_prefix('+').unwrap_or(offset))
+    };
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let is_number =
+        |part: &str| (1..=2).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit());
+    if !is_number(hours) || !is_number(minutes) {
+        return None;
+    }
+    let hours = hours.parse::<i32>().ok()?;
+    let minutes = minutes.parse::<i32>().ok()?;
+    if minutes >= 60 {
+        return None;
+    }
+    let total = sign * (hours * 60 + minutes);
+    (total.abs() <= MAX_OFFSET_MINUTES).then_some(total)
+}
+
+/// Format an offset in minutes like `UTC+05:30`.
+fn format_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let minutes = minutes.abs();
+    format!("UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn offsets_are_parsed() {
+        assert_eq!(parse_offset("+2"), Some(120));
+        assert_eq!(parse_offset("+02:00"), Some(120));
+        assert_eq!(parse_offset("-5:30"), Some(-330));
+        assert_eq!(parse_offset("UTC+1"), Some(60));
+        assert_eq!(parse_offset("utc"), Some(0));
+        assert_eq!(parse_offset("3"), Some(180));
+    }
+
+    #[test]
+    fn invalid_offsets_are_rejected() {
+        assert_eq!(parse_offset("+15"), None);
+        assert_eq!(parse_offset("+1:60"), None);
+        assert_eq!(parse_offset("+-1"), None);
+        assert_eq!(parse_offset("+1:"), None);
+        assert_eq!(parse_offset("+100"), None);
+        assert_eq!(parse_offset("Europe/Amsterdam"), None);
+    }
+
+    #[test]
+    fn offsets_are_formatted() {
+        assert_eq!(format_offset(120), "UTC+02:00");
+        assert_eq!(format_offset(-330), "UTC-05:30");
+        assert_eq!(format_offset(0), "UTC+00:00");
+    }
+}
@@ -0,0 +1,264 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use futures::future::BoxFuture;
+use poise::serenity_prelude as serenity;
+use serenity::{
+    ComponentInteraction, CreateInteractionResponse, CreateInteractionResponseMessage, FullEvent,
+    Interaction,
+};
+use tracing::debug;
+
+use crate::commands::CommandError;
+
+/// Separates the namespace, state and action in a custom id.
+const SEPARATOR: char = ':';
+
+/// Handles the interactions with the components of a namespace.
+pub(crate) type Handler =
+    for<'a> fn(ComponentEvent<'a>) -> BoxFuture<'a, Result<(), serenity::Error>>;
+
+/// Identifies the state of the message a component belongs to.
+///
+/// The ids are random, so the components of a message that was sent before a restart can never
+/// resolve to the state of a newer message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct StateId(u64);
+
+/// The custom id of a component, formatted as `namespace:state:action`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct CustomId<'a> {
+    /// The feature that created the component.
+    pub(crate) namespace: &'a str,
+    pub(crate) state: StateId,
+    /// What the component does within its namespace.
+    pub(crate) action: &'a str,
+}
+
+impl<'a> CustomId<'a> {
+    pub(crate) fn parse(custom_id: &'a str) -> Option<Self> {
+        let mut parts = custom_id.splitn(3, SEPARATOR);
+        let namespace = parts.next()?;
+        let state = parts.next()?.parse().ok()?;
+        let action = parts.next()?;
+        Some(Self {
+            namespace,
+            state: StateId(state),
+            action,
+        })
+    }
+}
+
+impl fmt::Display for CustomId<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            namespace,
+            state,
+            action,
+        } = self;
+        write!(f, "{namespace}{SEPARATOR}{}{SEPARATOR}{action}", state.0)
+    }
+}
+
+/// An interaction with a component, passed to the [`Handler`] of its namespace.
+pub(crate) struct ComponentEvent<'a> {
+    pub(crate) ctx: &'a serenity::Context,
+    pub(crate) interaction: &'a ComponentInteraction,
+    pub(crate) id: CustomId<'a>,
+    state: Arc<dyn Any + Send + Sync>,
+}
+
+impl ComponentEvent<'_> {
+    /// The state of the message, or `None` when it has a different type.
+    pub(crate) fn state<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.state.clone().downcast().ok()
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    namespace: &'static str,
+    state: Arc<dyn Any + Send + Sync>,
+    expires_at: Instant,
+}
+
+/// Routes the interactions with buttons and select menus to the handler of their namespace,
+/// together with the state of the message they belong to.
+///
+/// The state of a message is kept until its timeout, after which the components are removed
+/// from the message on the next interaction.
+#[derive(Default)]
+pub(crate) struct Components {
+    handlers: HashMap<&'static str, Handler>,
+    states: Mutex<HashMap<StateId, Entry>>,
+}
+
+impl fmt::Debug for Components {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Components")
+            .field("namespaces", &self.handlers.keys())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Components {
+    /// Handle the components with a custom id in `namespace` with `handler`.
+    #[must_use]
+    pub(crate) fn with_handler(mut self, namespace: &'static str, handler: Handler) -> Self {
+        self.handlers.insert(namespace, handler);
+        self
+    }
+
+    /// Store the state of a new message, its components stop working after `timeout`.
+    pub(crate) fn insert<T: Any + Send + Sync>(
+        &self,
+        namespace: &'static str,
+        state: Arc<T>,
+        timeout: Duration,
+    ) -> StateId {
+        let now = Instant::now();
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        states.retain(|_, entry| entry.expires_at > now);
+        let id = loop {
+            let id = StateId(rand::random());
+            if !states.contains_key(&id) {
+                break id;
+            }
+        };
+        states.insert(
+            id,
+            Entry {
+                namespace,
+                state,
+                expires_at: now + timeout,
+            },
+        );
+        id
+    }
+
+    fn state(&self, id: CustomId<'_>) -> Option<Arc<dyn Any + Send + Sync>> {
+        let mut states = self.states.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = states.get(&id.state)?;
+        if entry.expires_at <= Instant::now() {
+            states.remove(&id.state);
+            return None;
+        }
+        (entry.namespace == id.namespace).then(|| entry.state.clone())
+    }
+
+    async fn dispatch(
+        &self,
+        ctx: &serenity::Context,
+        interaction: &ComponentInteraction,
+    ) -> Result<(), serenity::Error> {
+        // Components with other custom ids, such as the ones of poise, are handled elsewhere.
+        let Some(id) = CustomId::parse(&interaction.data.custom_id) else {
+            return Ok(());
+        };
+        let Some(handler) = self.handlers.get(id.namespace) else {
+            return Ok(());
+        };
+        let Some(state) = self.state(id) else {
+            debug!("Removing the expired components of {id}");
+            let message = CreateInteractionResponseMessage::new().components(Vec::new());
+            let response = CreateInteractionResponse::UpdateMessage(message);
+            return interaction.create_response(ctx, response).await;
+        };
+        handler(ComponentEvent {
+            ctx,
+            interaction,
+            id,
+            state,
+        })
+        .await
+    }
+}
+
+/// Pass the component interactions in `event` to `components`.
+///
+/// # Errors
+///
+/// Will return an error when the handler of the component fails to respond.
+pub(crate) async fn handle_event(
+    ctx: &serenity::Context,
+    event: &FullEvent,
+    components: &Components,
+) -> Result<(), CommandError> {
+    if let FullEvent::InteractionCreate {
+        interaction: Interaction::Component(interaction),
+    } = event
+    {
+        components.dispatch(ctx, interaction).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn custom_id_round_trips() {
+        let id = CustomId {
+            namespace: "help",
+            state: StateId(42),
+            action: "next:page",
+        };
+        let custom_id = id.to_string();
+        assert_eq!(custom_id, "help:42:next:page");
+        assert_eq!(CustomId::parse(&custom_id), Some(id));
+    }
+
+    #[test]
+    fn foreign_custom_ids_are_ignored() {
+        assert_eq!(CustomId::parse("1234next"), None);
+        assert_eq!(CustomId::parse("help:page:next"), None);
+    }
+
+    #[test]
+    fn state_belongs_to_its_namespace() {
+        let components = Components::default();
+        let state = components.insert("help", Arc::new(3usize), MINUTE);
+        let id = |namespace| CustomId {
+            namespace,
+            state,
+            action: "next",
+        };
+        let found = components.state(id("help")).expect("state is stored");
+        assert_eq!(found.downcast_ref::<usize>(), Some(&3));
+        assert!(components.state(id("poll")).is_none());
+    }
+
+    #[test]
+    fn state_is_not_resolved_by_another_process() {
+        let before_restart = Components::default();
+        let state = before_restart.insert("poll", Arc::new(1usize), MINUTE);
+        let after_restart = Components::default();
+        for votes in 0..100usize {
+            after_restart.insert("poll", Arc::new(votes), MINUTE);
+        }
+        let id = CustomId {
+            namespace: "poll",
+            state,
+            action: "vote",
+        };
+        assert!(after_restart.state(id).is_none());
+    }
+
+    #[test]
+    fn state_expires() {
+        let components = Components::default();
+        let state = components.insert("help", Arc::new(()), Duration::ZERO);
+        let id = CustomId {
+            namespace: "help",
+            state,
+            action: "next",
+        };
+        assert!(components.state(id).is_none());
+    }
+}
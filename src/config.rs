@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tenor::models::ContentFilter;
+use tokio::sync::watch;
+
+use crate::consts::{
+    EMPTY_CACHE_LIFETIME, GIF_COUNT, LONG_CACHE_LIFETIME, PRESENCE_INTERVAL, SHORT_CACHE_LIFETIME,
+};
+
+/// The current [`RuntimeConfig`], which is replaced when the config is reloaded.
+pub(crate) type SharedConfig = Arc<watch::Sender<RuntimeConfig>>;
+
+/// Settings that can be changed without restarting the bot.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct RuntimeConfig {
+    /// How long the gifs of a search by a user are cached.
+    pub(crate) search_cache_lifetime: Duration,
+    /// How long the gifs of the periodic update are cached.
+    pub(crate) update_cache_lifetime: Duration,
+    /// How long a search without gifs is remembered.
+    pub(crate) empty_cache_lifetime: Duration,
+    pub(crate) content_filter: ContentFilter,
+    /// Number of gifs requested from tenor per search.
+    pub(crate) gif_count: u8,
+    /// How long each activity of the bot is shown.
+    pub(crate) presence_interval: Duration,
+}
+
+impl RuntimeConfig {
+    /// Read the config from the `.env` file and the environment. The `.env` file is read again on
+    /// every call and takes precedence, so its changes are picked up by a reload.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when the `.env` file or one of the values is invalid.
+    pub(crate) fn from_env() -> anyhow::Result<Self> {
+        let file: HashMap<String, String> = match dotenvy::dotenv_iter() {
+            Ok(iter) => iter.collect::<Result<_, _>>()?,
+            Err(_) => HashMap::new(),
+        };
+        let var = |key: &str| file.get(key).cloned().or_else(|| env::var(key).ok());
+        Ok(Self {
+            search_cache_lifetime: parse_seconds(var("GIF_SEARCH_CACHE_LIFETIME"))?
+                .unwrap_or(SHORT_CACHE_LIFETIME),
+            update_cache_lifetime: parse_seconds(var("GIF_UPDATE_CACHE_LIFETIME"))?
+                .unwrap_or(LONG_CACHE_LIFETIME),
+            empty_cache_lifetime: parse_seconds(var("GIF_EMPTY_CACHE_LIFETIME"))?
+                .unwrap_or(EMPTY_CACHE_LIFETIME),
+            content_filter: parse(var("GIF_CONTENT_FILTER"))?.unwrap_or(ContentFilter::Medium),
+            gif_count: parse(var("GIF_COUNT"))?.unwrap_or(GIF_COUNT),
+            presence_interval: parse_seconds(var("PRESENCE_INTERVAL"))?
+                .unwrap_or(PRESENCE_INTERVAL),
+        })
+    }
+
+    /// The tenor config to apply on top of the config of each search.
+    pub(crate) fn gif_config(&self) -> tenor::Config<'static> {
+        tenor::Config::new()
+            .content_filter(self.content_filter)
+            .limit(self.gif_count)
+    }
+}
+
+fn parse<T>(value: Option<String>) -> anyhow::Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    Ok(value.map(|value| value.parse()).transpose()?)
+}
+
+fn parse_seconds(value: Option<String>) -> anyhow::Result<Option<Duration>> {
+    Ok(parse(value)?.map(Duration::from_secs))
+}
+
+/// Parts of the bot that a deployment can turn off with `FEATURES`, they are checked when the
+/// commands are registered and the background tasks are started.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Features {
+    /// The anime commands and the release announcements.
+    pub(crate) anime: bool,
+    /// The gif commands and the periodic update of the gif cache.
+    pub(crate) gifs: bool,
+}
+
+impl Features {
+    pub(crate) const ALL: Features = Features {
+        anime: true,
+        gifs: true,
+    };
+
+    /// Read the comma separated features from `FEATURES`, every feature is enabled when it is not
+    /// set.
+    ///
+    /// # Errors
+    ///
+    /// Will return an error when `FEATURES` contains an unknown feature.
+    pub(crate) fn from_env() -> anyhow::Result<Self> {
+        let Ok(value) = env::var("FEATURES") else {
+            return Ok(Self::ALL);
+        };
+        let mut features = Features {
+            anime: false,
+            gifs: false,
+        };
+        for feature in value.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            match feature {
+                "anime" => features.anime = true,
+                "gifs" => features.gifs = true,
+                other => anyhow::bail!("Unknown feature: {other}"),
+            }
+        }
+        Ok(features)
+    }
+}
+
+/// Read the config again and replace the current config with it.
+///
+/// # Errors
+///
+/// Will return an error when the config is invalid, the current config is kept in that case.
+pub(crate) fn reload(config: &SharedConfig) -> anyhow::Result<RuntimeConfig> {
+    let runtime_config = RuntimeConfig::from_env()?;
+    config.send_replace(runtime_config);
+    Ok(runtime_config)
+}
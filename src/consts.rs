@@ -1,11 +1,55 @@
+use giphy::models::Rating;
+use otaku::db::RetentionPolicy;
 use std::time::Duration;
 use tenor::models::{ContentFilter, MediaFilter};
 use tenor::Config;
 
 pub(crate) const SHORT_CACHE_LIFETIME: Duration = Duration::from_secs(3600);
 pub(crate) const LONG_CACHE_LIFETIME: Duration = Duration::from_secs(24 * 3600);
+pub(crate) const EMPTY_CACHE_LIFETIME: Duration = Duration::from_secs(15 * 60);
+pub(crate) const RECENT_VALUES_PER_CHANNEL: usize = 10;
+pub(crate) const STALE_CACHE_LIFETIME: Duration = Duration::from_secs(24 * 3600);
+pub(crate) const ANIME_BACKFILL_WINDOW: Duration = Duration::from_secs(24 * 3600);
+/// Discord allows at most 10 embeds per message.
+pub(crate) const MAX_EMBEDS_PER_MESSAGE: usize = 10;
+/// Discord allows at most 2000 characters per message.
+pub(crate) const MAX_MESSAGE_LENGTH: usize = 2000;
+pub(crate) const ANNOUNCEMENT_BATCH_WINDOW: Duration = Duration::from_secs(2);
+pub(crate) const ANNOUNCEMENT_RETRY_ATTEMPTS: u32 = 5;
+pub(crate) const ANNOUNCEMENT_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+pub(crate) const ANNOUNCEMENT_RETRY_QUEUE_SIZE: usize = 64;
+pub(crate) const ERROR_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+pub(crate) const ERROR_REPORT_QUEUE_SIZE: usize = 64;
+/// How long a user can take to submit a modal.
+pub(crate) const MODAL_TIMEOUT: Duration = Duration::from_secs(600);
+pub(crate) const DATABASE_CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+pub(crate) const GIF_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+pub(crate) const REMINDER_INTERVAL: Duration = Duration::from_secs(30);
+pub(crate) const PLAY_SESSION_INTERVAL: Duration = Duration::from_secs(30);
+/// Sessions of /play that started longer ago than this are removed without pinging.
+pub(crate) const PLAY_SESSION_STALE_AFTER: Duration = Duration::from_secs(3600);
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_secs(30);
+pub(crate) const HURRY_ESCALATION_INTERVAL: Duration = Duration::from_secs(30);
+/// How long a member that was told to hurry up can stay silent before the follow-up GIF.
+pub(crate) const HURRY_ESCALATION_DELAY: Duration = Duration::from_secs(5 * 60);
+/// Follow-ups that were due longer ago than this are removed without posting.
+pub(crate) const HURRY_ESCALATION_STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+pub(crate) const RETENTION_POLICY: RetentionPolicy = RetentionPolicy {
+    sent_announcements: Duration::from_secs(30 * 24 * 3600),
+    processed_collections: Duration::from_secs(7 * 24 * 3600),
+    orphaned_subscriptions: true,
+    removed_guilds: Duration::from_secs(30 * 24 * 3600),
+};
+pub(crate) const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+pub(crate) const GIF_CACHE_SNAPSHOT: &str = "gif_cache.json";
+pub(crate) const GIF_CACHE_MAX_ENTRIES: usize = 500;
+pub(crate) const GIF_CACHE_MAX_URLS: usize = 10_000;
 pub(crate) const GIF_COUNT: u8 = 25;
+pub(crate) const PRESENCE_INTERVAL: Duration = Duration::from_secs(5 * 60);
 pub(crate) const BASE_GIF_CONFIG: Config = Config::new()
     .content_filter(ContentFilter::Medium)
     .media_filter(&[MediaFilter::Gif])
     .limit(GIF_COUNT);
+/// Giphy ratings G and PG, like the medium content filter of tenor.
+pub(crate) const BASE_GIPHY_CONFIG: giphy::Config =
+    giphy::Config::new().rating(Rating::Pg).limit(GIF_COUNT);
@@ -1,27 +1,48 @@
 use crate::commands::CommandError;
+use crate::config::{RuntimeConfig, SharedConfig};
+use crate::gif_provider::Providers;
 use crate::{cache, SpiderBot};
+use otaku::db::Pool;
+use otaku::{Api, ConnectionState};
 use url::Url;
 
 pub(crate) type Context<'a, 'tenor_config> =
     poise::Context<'a, SpiderBot<'tenor_config>, CommandError>;
 
+pub(crate) type Command<'tenor_config> = poise::Command<SpiderBot<'tenor_config>, CommandError>;
+
+pub(crate) type ApplicationContext<'a, 'tenor_config> =
+    poise::ApplicationContext<'a, SpiderBot<'tenor_config>, CommandError>;
+
 pub(crate) trait GifCacheExt {
     fn gif_cache(&self) -> &cache::Memory<[Url]>;
 }
 
-pub(crate) trait TenorExt<'tenor_config> {
-    fn tenor(&self) -> &tenor::Client<'tenor_config>;
+pub(crate) trait GifProviderExt<'tenor_config> {
+    fn gif_providers(&self) -> &Providers<'tenor_config>;
+}
+
+pub(crate) trait ConfigExt {
+    fn config(&self) -> RuntimeConfig;
+}
+
+pub(crate) trait DatabaseExt {
+    fn database(&self) -> &Pool;
+}
+
+pub(crate) trait AnimeExt {
+    fn anime_api(&self) -> Option<&Api>;
+    fn anime_state(&self) -> ConnectionState;
 }
 
 pub(crate) trait GifContextExt<'tenor_config>:
-    TenorExt<'tenor_config> + GifCacheExt
+    GifProviderExt<'tenor_config> + GifCacheExt + ConfigExt
 {
-    fn gif_context(&self) -> (&tenor::Client<'tenor_config>, &cache::Memory<[Url]>);
 }
 
-impl<'a, 'tenor_config> TenorExt<'tenor_config> for Context<'a, 'tenor_config> {
-    fn tenor(&self) -> &tenor::Client<'tenor_config> {
-        &self.framework().user_data.tenor
+impl<'a, 'tenor_config> GifProviderExt<'tenor_config> for Context<'a, 'tenor_config> {
+    fn gif_providers(&self) -> &Providers<'tenor_config> {
+        &self.framework().user_data.gif_providers
     }
 }
 
@@ -31,29 +52,47 @@ impl<'a, 'tenor_config> GifCacheExt for Context<'a, 'tenor_config> {
     }
 }
 
-impl<'a, 'tenor_config> GifContextExt<'tenor_config> for Context<'a, 'tenor_config> {
-    fn gif_context(&self) -> (&tenor::Client<'tenor_config>, &cache::Memory<[Url]>) {
-        let context = self.framework().user_data;
-        (&context.tenor, &context.gif_cache)
+impl<'a, 'tenor_config> ConfigExt for Context<'a, 'tenor_config> {
+    fn config(&self) -> RuntimeConfig {
+        *self.framework().user_data.config.borrow()
+    }
+}
+
+impl<'a, 'tenor_config> DatabaseExt for Context<'a, 'tenor_config> {
+    fn database(&self) -> &Pool {
+        &self.framework().user_data.database
+    }
+}
+
+impl<'a, 'tenor_config> AnimeExt for Context<'a, 'tenor_config> {
+    fn anime_api(&self) -> Option<&Api> {
+        self.framework().user_data.anime_api.as_ref()
+    }
+
+    fn anime_state(&self) -> ConnectionState {
+        *self.framework().user_data.anime_state.borrow()
     }
 }
 
-impl<'tenor_config, T> TenorExt<'tenor_config> for (tenor::Client<'tenor_config>, T) {
-    fn tenor(&self) -> &tenor::Client<'tenor_config> {
+impl<'tenor_config, T, U> GifProviderExt<'tenor_config> for (Providers<'tenor_config>, T, U) {
+    fn gif_providers(&self) -> &Providers<'tenor_config> {
         &self.0
     }
 }
 
-impl<T> GifCacheExt for (T, cache::Memory<[Url]>) {
+impl<T, U> GifCacheExt for (T, cache::Memory<[Url]>, U) {
     fn gif_cache(&self) -> &cache::Memory<[Url]> {
         &self.1
     }
 }
 
-impl<'tenor_config> GifContextExt<'tenor_config>
-    for (tenor::Client<'tenor_config>, cache::Memory<[Url]>)
-{
-    fn gif_context(&self) -> (&tenor::Client<'tenor_config>, &cache::Memory<[Url]>) {
-        (&self.0, &self.1)
+impl<T, U> ConfigExt for (T, U, SharedConfig) {
+    fn config(&self) -> RuntimeConfig {
+        *self.2.borrow()
     }
 }
+
+impl<'tenor_config, T> GifContextExt<'tenor_config> for T where
+    T: GifProviderExt<'tenor_config> + GifCacheExt + ConfigExt
+{
+}
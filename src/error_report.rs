@@ -0,0 +1,90 @@
+use std::env;
+use std::fmt::{self, Write};
+use std::num::NonZeroU64;
+
+use serenity::model::id::ChannelId;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::consts::ERROR_REPORT_QUEUE_SIZE;
+
+/// Only the errors of these crates are reported, the errors of dependencies are only logged.
+const REPORTED_TARGETS: &[&str] = &["spider_bot", "otaku", "tenor"];
+
+/// Forwards the error events of the bot to [`ErrorReports`], so an operator can see them in
+/// discord.
+pub(crate) struct ErrorReportLayer {
+    sender: Sender<String>,
+}
+
+/// The errors that are waiting to be posted in `channel_id`.
+pub(crate) struct ErrorReports {
+    pub(crate) channel_id: ChannelId,
+    pub(crate) receiver: Receiver<String>,
+}
+
+/// Create the layer and the reports it sends to when `ERROR_CHANNEL_ID` is set.
+///
+/// # Errors
+///
+/// Will return an error when `ERROR_CHANNEL_ID` is not a valid channel id.
+pub(crate) fn from_env() -> anyhow::Result<Option<(ErrorReportLayer, ErrorReports)>> {
+    let Ok(channel_id) = env::var("ERROR_CHANNEL_ID") else {
+        return Ok(None);
+    };
+    let channel_id = ChannelId::from(channel_id.parse::<NonZeroU64>()?);
+    let (sender, receiver) = channel(ERROR_REPORT_QUEUE_SIZE);
+    Ok(Some((
+        ErrorReportLayer { sender },
+        ErrorReports {
+            channel_id,
+            receiver,
+        },
+    )))
+}
+
+#[derive(Default)]
+struct ReportVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for ReportVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
+
+impl<S> Layer<S> for ErrorReportLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if *metadata.level() != Level::ERROR
+            || !REPORTED_TARGETS
+                .iter()
+                .any(|target| metadata.target().starts_with(target))
+        {
+            return;
+        }
+        let mut visitor = ReportVisitor::default();
+        event.record(&mut visitor);
+
+        let mut report = format!("`{}`", metadata.target());
+        if let Some(scope) = ctx.event_scope(event) {
+            let spans: Vec<_> = scope.from_root().map(|span| span.name()).collect();
+            let _ = write!(report, " in `{}`", spans.join(" > "));
+        }
+        let _ = write!(report, ": {}{}", visitor.message, visitor.fields);
+        // Drop the report instead of blocking the caller when the operator channel cannot keep up.
+        let _ = self.sender.try_send(report);
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tenor::models::{Gif, MediaFilter};
+use tracing::{info, warn};
+use url::Url;
+
+use crate::metrics;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum ProviderError {
+    #[error(transparent)]
+    Tenor(#[from] tenor::error::Error),
+    #[error(transparent)]
+    Giphy(#[from] giphy::error::Error),
+}
+
+/// A service that GIFs can be searched on.
+#[async_trait]
+pub(crate) trait GifProvider: Debug + Send + Sync {
+    /// The name of the provider in the logs and metrics.
+    fn name(&self) -> &'static str;
+
+    /// Search the GIFs of `query`, providers that do not support the tenor `config` search with
+    /// the config they were created with.
+    async fn search(
+        &self,
+        query: &str,
+        config: Option<tenor::Config<'_>>,
+    ) -> Result<Vec<Url>, ProviderError>;
+}
+
+#[async_trait]
+impl<'tenor_config> GifProvider for tenor::Client<'tenor_config> {
+    fn name(&self) -> &'static str {
+        "tenor"
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        config: Option<tenor::Config<'_>>,
+    ) -> Result<Vec<Url>, ProviderError> {
+        let gifs = tenor::Client::search(self, query, config).await?;
+        Ok(gifs.into_iter().map(map_gif_to_url).collect())
+    }
+}
+
+#[async_trait]
+impl GifProvider for giphy::Client {
+    fn name(&self) -> &'static str {
+        "giphy"
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        _: Option<tenor::Config<'_>>,
+    ) -> Result<Vec<Url>, ProviderError> {
+        let gifs = giphy::Client::search(self, query).await?;
+        Ok(gifs
+            .into_iter()
+            .map(|gif| gif.images.original.url)
+            .collect())
+    }
+}
+
+fn map_gif_to_url(mut gif: Gif) -> Url {
+    gif.media_formats
+        .remove(&MediaFilter::Gif)
+        .map_or(gif.url, |s| s.url)
+}
+
+/// The GIF providers in the order they are searched, a provider is only searched when the ones
+/// before it failed or found nothing.
+#[derive(Debug, Clone)]
+pub(crate) struct Providers<'tenor_config> {
+    chain: Vec<Arc<dyn GifProvider + 'tenor_config>>,
+}
+
+impl<'tenor_config> Providers<'tenor_config> {
+    pub(crate) fn new(primary: impl GifProvider + 'tenor_config) -> Self {
+        Self {
+            chain: vec![Arc::new(primary)],
+        }
+    }
+
+    /// Search `fallback` when the providers before it fail or find nothing.
+    #[must_use]
+    pub(crate) fn with_fallback(mut self, fallback: impl GifProvider + 'tenor_config) -> Self {
+        self.chain.push(Arc::new(fallback));
+        self
+    }
+
+    /// Search the GIFs of `query` with every provider until one of them finds GIFs.
+    ///
+    /// # Errors
+    ///
+    /// Will return the error of the last provider that failed when no provider found GIFs, no
+    /// GIFs are returned when every provider could be reached.
+    pub(crate) async fn search(
+        &self,
+        query: &str,
+        config: Option<tenor::Config<'_>>,
+    ) -> Result<Vec<Url>, ProviderError> {
+        let mut last_error = None;
+        for provider in &self.chain {
+            let provider_name = provider.name();
+            match provider.search(query, config).await {
+                Ok(gifs) if !gifs.is_empty() => {
+                    info!(
+                        provider = provider_name,
+                        "Found {} \"{query}\" gifs",
+                        gifs.len()
+                    );
+                    metrics::record_gif_search(provider_name, "found");
+                    return Ok(gifs);
+                }
+                Ok(_) => {
+                    info!(provider = provider_name, "Found no \"{query}\" gifs");
+                    metrics::record_gif_search(provider_name, "empty");
+                }
+                Err(err) => {
+                    warn!(
+                        provider = provider_name,
+                        "Failed to search \"{query}\": {err}"
+                    );
+                    metrics::record_gif_search(provider_name, "error");
+                    last_error = Some(err);
+                }
+            }
+        }
+        last_error.map_or_else(|| Ok(Vec::new()), Err)
+    }
+}
+
+/// The name of the service that hosts `gif`, to credit it when the GIF is shown in an embed.
+pub(crate) fn attribution(gif: &Url) -> Option<&'static str> {
+    let host = gif.host_str()?;
+    let is_domain = |domain: &str| host == domain || host.ends_with(&format!(".{domain}"));
+    if is_domain("tenor.com") {
+        Some("Tenor")
+    } else if is_domain("giphy.com") {
+        Some("GIPHY")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug)]
+    struct Fixed {
+        gifs: Option<Vec<Url>>,
+        searches: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl GifProvider for Fixed {
+        fn name(&self) -> &'static str {
+            "fixed"
+        }
+
+        async fn search(
+            &self,
+            _: &str,
+            _: Option<tenor::Config<'_>>,
+        ) -> Result<Vec<Url>, ProviderError> {
+            self.searches.fetch_add(1, Ordering::Relaxed);
+            match &self.gifs {
+                Some(gifs) => Ok(gifs.clone()),
+                None => Err(tenor::error::Error::Url(url::ParseError::EmptyHost).into()),
+            }
+        }
+    }
+
+    fn provider(gifs: Option<&[&str]>) -> (Fixed, Arc<AtomicUsize>) {
+        let searches = Arc::new(AtomicUsize::new(0));
+        let gifs = gifs.map(|gifs| gifs.iter().map(|gif| Url::parse(gif).unwrap()).collect());
+        let provider = Fixed {
+            gifs,
+            searches: searches.clone(),
+        };
+        (provider, searches)
+    }
+
+    #[tokio::test]
+    async fn fallback_is_not_searched_when_primary_finds_gifs() {
+        let (primary, _) = provider(Some(&["https://example.com/a.gif"]));
+        let (fallback, fallback_searches) = provider(Some(&["https://example.com/b.gif"]));
+        let providers = Providers::new(primary).with_fallback(fallback);
+
+        let gifs = providers.search("cat", None).await.unwrap();
+        assert_eq!(gifs[0].as_str(), "https://example.com/a.gif");
+        assert_eq!(fallback_searches.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn fallback_is_searched_when_primary_fails_or_finds_nothing() {
+        for primary in [None, Some(&[][..])] {
+            let (primary, _) = provider(primary);
+            let (fallback, _) = provider(Some(&["https://example.com/b.gif"]));
+            let providers = Providers::new(primary).with_fallback(fallback);
+
+            let gifs = providers.search("cat", None).await.unwrap();
+            assert_eq!(gifs[0].as_str(), "https://example.com/b.gif");
+        }
+    }
+
+    #[tokio::test]
+    async fn error_is_returned_when_every_provider_fails() {
+        let (primary, _) = provider(None);
+        let (fallback, _) = provider(Some(&[]));
+        assert!(Providers::new(primary)
+            .with_fallback(fallback)
+            .search("cat", None)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn gifs_are_attributed_to_their_host() {
+        let attribute = |gif: &str| attribution(&Url::parse(gif).unwrap());
+        assert_eq!(attribute("https://media.tenor.com/a/b.gif"), Some("Tenor"));
+        assert_eq!(attribute("https://media1.giphy.com/a.gif"), Some("GIPHY"));
+        assert_eq!(attribute("https://notgiphy.com/a.gif"), None);
+        assert_eq!(attribute("https://example.com/a.gif"), None);
+    }
+}
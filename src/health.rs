@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use otaku::db::Pool;
+use otaku::ConnectionState;
+use serde::Serialize;
+use serenity::gateway::{ConnectionStage, ShardManager};
+use tokio::sync::watch;
+use tracing::warn;
+
+/// The dependencies checked by the readiness endpoint.
+#[derive(Clone)]
+pub(crate) struct HealthState {
+    pub(crate) shard_manager: Arc<ShardManager>,
+    pub(crate) pool: Pool,
+    /// `None` when the anime api is not configured.
+    pub(crate) anime_state: Option<watch::Receiver<ConnectionState>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Readiness {
+    discord: bool,
+    database: bool,
+    /// `None` when the anime api is not configured.
+    anime: Option<bool>,
+}
+
+impl Readiness {
+    fn is_ready(&self) -> bool {
+        self.discord && self.database && self.anime.unwrap_or(true)
+    }
+}
+
+/// Routes for `/healthz`, which succeeds while the bot is running, and `/readyz`, which only
+/// succeeds while discord, the database and the anime api are reachable.
+pub(crate) fn router(state: HealthState) -> Router {
+    Router::new()
+        .route("/healthz", get(|| async { StatusCode::OK }))
+        .route("/readyz", get(readyz))
+        .with_state(state)
+}
+
+async fn readyz(State(state): State<HealthState>) -> (StatusCode, Json<Readiness>) {
+    let discord = {
+        let runners = state.shard_manager.runners.lock().await;
+        !runners.is_empty()
+            && runners
+                .values()
+                .all(|runner| runner.stage == ConnectionStage::Connected)
+    };
+    let database = match otaku::db::health(&state.pool).await {
+        Ok(_) => true,
+        Err(err) => {
+            warn!("Database is not ready: {err}");
+            false
+        }
+    };
+    let anime = state
+        .anime_state
+        .map(|anime_state| *anime_state.borrow() == ConnectionState::Connected);
+
+    let readiness = Readiness {
+        discord,
+        database,
+        anime,
+    };
+    let status = if readiness.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
@@ -0,0 +1,559 @@
+use otaku::db::GuildSettingsConnection;
+use tracing::warn;
+
+use crate::context::{Context, DatabaseExt};
+
+/// Languages the command replies are available in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, poise::ChoiceParameter)]
+pub(crate) enum Locale {
+    #[name = "English"]
+    English,
+    #[name = "Nederlands"]
+    Dutch,
+}
+
+impl Locale {
+    /// The language code stored in the guild settings.
+    pub(crate) fn code(self) -> &'static str {
+        match self {
+            Locale::English => "en",
+            Locale::Dutch => "nl",
+        }
+    }
+
+    /// Parse a language code, including the regional locales of discord such as `en-GB`.
+    pub(crate) fn from_code(code: &str) -> Option<Self> {
+        match code.split('-').next()? {
+            "en" => Some(Locale::English),
+            "nl" => Some(Locale::Dutch),
+            _ => None,
+        }
+    }
+}
+
+/// Keys of the translated command replies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Text {
+    InternalError,
+    NoGifs,
+    RestrictedQuery,
+    AnimeUnavailable,
+    AnimeNotFound,
+    AnimeConnected,
+    AnimeConnecting,
+    AnimeDisconnected,
+    Hurry,
+    PlayGame,
+    PlayAnyGame,
+    CacheUnused,
+    CacheRemoved,
+    CacheRemovedPrefix,
+    CacheNotCached,
+    LanguageChanged,
+    LanguageReset,
+    CommandDisabled,
+    CommandEnabledInGuild,
+    CommandDisabledInGuild,
+    UnknownCommand,
+    Cooldown,
+    ConfigReloaded,
+    ConfigReloadFailed,
+    UsageEmpty,
+    GifReactSent,
+    CacheAdded,
+    CacheInvalidUrl,
+    GuildWelcome,
+    MissingPermission,
+    LevelEveryone,
+    LevelManager,
+    LevelAdmin,
+    LevelOwner,
+    ManagerRoleAdded,
+    ManagerRoleRemoved,
+    PermissionChanged,
+    PermissionReset,
+    ModerationChanged,
+    ModerationReset,
+    ModerationStrict,
+    ModerationStandard,
+    ModerationLenient,
+    DiceRolled,
+    InvalidDice,
+    PollInvalidOptions,
+    PollNotAllowed,
+    PollCloses,
+    PollClosed,
+    PollVotes,
+    CustomGifAdded,
+    CustomGifExists,
+    CustomGifRemoved,
+    CustomGifNotFound,
+    CustomGifsEmpty,
+    GifBlocked,
+    GifAlreadyBlocked,
+    GifUnblocked,
+    BlockedGifNotFound,
+    BlocklistEmpty,
+    UnknownSeason,
+    SeasonsFollowCalendar,
+    SeasonsDisabled,
+    SeasonChosen,
+    NoveltyChanged,
+    NoveltyDisabled,
+    NoveltyReset,
+    TimezoneSet,
+    TimezoneReset,
+    InvalidTimezone,
+    Hug,
+    Pat,
+    Highfive,
+    GameAdded,
+    GameExists,
+    GameRemoved,
+    GameNotFound,
+    GamesEmpty,
+    InvalidGameName,
+    InvalidSessionTime,
+    SessionStarts,
+    SessionJoining,
+    SessionDeclined,
+    SessionNobody,
+    SessionJoin,
+    SessionDecline,
+    SessionStarting,
+    SessionStartingAnyGame,
+    SleepStats,
+    SleepStatsEmpty,
+    SleepLeaderboard,
+    SleepLeaderboardEntry,
+    GifStyleLink,
+    GifStyleEmbed,
+    GifStyleChanged,
+    GifAttribution,
+    HurryImpatient,
+    HurryFurious,
+    HurryEscalation,
+}
+
+impl Text {
+    #[cfg(test)]
+    const ALL: &'static [Text] = &[
+        Text::InternalError,
+        Text::NoGifs,
+        Text::RestrictedQuery,
+        Text::AnimeUnavailable,
+        Text::AnimeNotFound,
+        Text::AnimeConnected,
+        Text::AnimeConnecting,
+        Text::AnimeDisconnected,
+        Text::Hurry,
+        Text::PlayGame,
+        Text::PlayAnyGame,
+        Text::CacheUnused,
+        Text::CacheRemoved,
+        Text::CacheRemovedPrefix,
+        Text::CacheNotCached,
+        Text::LanguageChanged,
+        Text::LanguageReset,
+        Text::CommandDisabled,
+        Text::CommandEnabledInGuild,
+        Text::CommandDisabledInGuild,
+        Text::UnknownCommand,
+        Text::Cooldown,
+        Text::ConfigReloaded,
+        Text::ConfigReloadFailed,
+        Text::UsageEmpty,
+        Text::GifReactSent,
+        Text::CacheAdded,
+        Text::CacheInvalidUrl,
+        Text::GuildWelcome,
+        Text::MissingPermission,
+        Text::LevelEveryone,
+        Text::LevelManager,
+        Text::LevelAdmin,
+        Text::LevelOwner,
+        Text::ManagerRoleAdded,
+        Text::ManagerRoleRemoved,
+        Text::PermissionChanged,
+        Text::PermissionReset,
+        Text::ModerationChanged,
+        Text::ModerationReset,
+        Text::ModerationStrict,
+        Text::ModerationStandard,
+        Text::ModerationLenient,
+        Text::DiceRolled,
+        Text::InvalidDice,
+        Text::PollInvalidOptions,
+        Text::PollNotAllowed,
+        Text::PollCloses,
+        Text::PollClosed,
+        Text::PollVotes,
+        Text::CustomGifAdded,
+        Text::CustomGifExists,
+        Text::CustomGifRemoved,
+        Text::CustomGifNotFound,
+        Text::CustomGifsEmpty,
+        Text::GifBlocked,
+        Text::GifAlreadyBlocked,
+        Text::GifUnblocked,
+        Text::BlockedGifNotFound,
+        Text::BlocklistEmpty,
+        Text::UnknownSeason,
+        Text::SeasonsFollowCalendar,
+        Text::SeasonsDisabled,
+        Text::SeasonChosen,
+        Text::NoveltyChanged,
+        Text::NoveltyDisabled,
+        Text::NoveltyReset,
+        Text::TimezoneSet,
+        Text::TimezoneReset,
+        Text::InvalidTimezone,
+        Text::Hug,
+        Text::Pat,
+        Text::Highfive,
+        Text::GameAdded,
+        Text::GameExists,
+        Text::GameRemoved,
+        Text::GameNotFound,
+        Text::GamesEmpty,
+        Text::InvalidGameName,
+        Text::InvalidSessionTime,
+        Text::SessionStarts,
+        Text::SessionJoining,
+        Text::SessionDeclined,
+        Text::SessionNobody,
+        Text::SessionJoin,
+        Text::SessionDecline,
+        Text::SessionStarting,
+        Text::SessionStartingAnyGame,
+        Text::SleepStats,
+        Text::SleepStatsEmpty,
+        Text::SleepLeaderboard,
+        Text::SleepLeaderboardEntry,
+        Text::GifStyleLink,
+        Text::GifStyleEmbed,
+        Text::GifStyleChanged,
+        Text::GifAttribution,
+        Text::HurryImpatient,
+        Text::HurryFurious,
+        Text::HurryEscalation,
+    ];
+
+    // The translation tables have an arm per text, so they grow with every translated reply.
+    #[allow(clippy::too_many_lines)]
+    fn english(self) -> &'static str {
+        match self {
+            Text::InternalError => "Internal error",
+            Text::NoGifs => "No gifs found",
+            Text::RestrictedQuery => "The query \"{query}\" was not allowed",
+            Text::AnimeUnavailable => "The anime api is not configured",
+            Text::AnimeNotFound => "No downloads found for \"{title}\"",
+            Text::AnimeConnected => "Connected to the anime api",
+            Text::AnimeConnecting => "Connecting to the anime api",
+            Text::AnimeDisconnected => "Disconnected from the anime api",
+            Text::Hurry => "{mention}! Hurry up!",
+            Text::PlayGame => "{mention}! Let's play some {game}!",
+            Text::PlayAnyGame => "{mention}! Let's play a game!",
+            Text::CacheUnused => "The gif cache has not been used yet",
+            Text::CacheRemoved => "Removed \"{key}\" from the cache",
+            Text::CacheRemovedPrefix => "Removed {count} cached queries starting with \"{key}\"",
+            Text::CacheNotCached => "\"{key}\" was not cached",
+            Text::LanguageChanged => "The bot now replies in English in this server",
+            Text::LanguageReset => "The bot now replies in the language of each user",
+            Text::CommandDisabled => "This command is disabled in this server",
+            Text::CommandEnabledInGuild => "/{command} can be used in this server again",
+            Text::CommandDisabledInGuild => "/{command} can no longer be used in this server",
+            Text::UnknownCommand => "There is no command called \"{command}\"",
+            Text::Cooldown => "Slow down! Try again in {seconds}s",
+            Text::ConfigReloaded => "Reloaded the configuration",
+            Text::ConfigReloadFailed => "Failed to reload the configuration: {error}",
+            Text::UsageEmpty => "No commands were used in the last {days} days",
+            Text::GifReactSent => "Reacted with a gif",
+            Text::CacheAdded => "Cached {count} gifs for \"{key}\"",
+            Text::CacheInvalidUrl => "\"{url}\" is not a valid url",
+            Text::GuildWelcome => {
+                "Thanks for adding me! Use /help to see what I can do and /settings to configure me"
+            }
+            Text::MissingPermission => "Only {level} can use this command",
+            Text::LevelEveryone => "everyone",
+            Text::LevelManager => "bot managers",
+            Text::LevelAdmin => "server admins",
+            Text::LevelOwner => "the bot owners",
+            Text::ManagerRoleAdded => "{role} can now manage the bot",
+            Text::ManagerRoleRemoved => "{role} can no longer manage the bot",
+            Text::PermissionChanged => "/{command} can now be used by {level}",
+            Text::PermissionReset => "/{command} uses its default permissions again",
+            Text::ModerationChanged => "The moderation of this server is now {level}",
+            Text::ModerationReset => "This server uses the default moderation again",
+            Text::ModerationStrict => "strict",
+            Text::ModerationStandard => "standard",
+            Text::ModerationLenient => "lenient",
+            Text::DiceRolled => "{user} rolled {dice}",
+            Text::InvalidDice => "\"{dice}\" are not dice, try 2d20+3, 4d6kh3 or d20 adv",
+            Text::PollInvalidOptions => "A poll needs 2 to 10 options, separated by ;",
+            Text::PollNotAllowed => "This poll contains text that is not allowed in this server",
+            Text::PollCloses => "Closes {time}",
+            Text::PollClosed => "This poll is closed",
+            Text::PollVotes => "{votes} votes",
+            Text::CustomGifAdded => "/{command} can now post {value}",
+            Text::CustomGifExists => "/{command} already posts {value}",
+            Text::CustomGifRemoved => "Removed custom GIF {id}",
+            Text::CustomGifNotFound => "There is no custom GIF {id}",
+            Text::CustomGifsEmpty => "This server has no custom GIFs",
+            Text::GifBlocked => "The bot will no longer post {value} in this server",
+            Text::GifAlreadyBlocked => "{value} is already blocked",
+            Text::GifUnblocked => "Unblocked {id}",
+            Text::BlockedGifNotFound => "There is no blocked GIF {id}",
+            Text::BlocklistEmpty => "This server has not blocked any GIFs",
+            Text::UnknownSeason => "There is no season \"{season}\"",
+            Text::SeasonsFollowCalendar => "Sleep and good morning GIFs follow the calendar again",
+            Text::SeasonsDisabled => {
+                "Sleep and good morning GIFs no longer change with the seasons"
+            }
+            Text::SeasonChosen => "Sleep and good morning GIFs now always come from {season}",
+            Text::NoveltyChanged => "One in {ratio} sleep GIFs is now a surprise",
+            Text::NoveltyDisabled => "Sleep GIFs no longer have surprises",
+            Text::NoveltyReset => "Sleep GIFs have the default amount of surprises again",
+            Text::TimezoneSet => "Your timezone is now {offset}",
+            Text::TimezoneReset => "Your timezone has been removed",
+            Text::InvalidTimezone => {
+                "\"{offset}\" is not an offset from UTC, try something like +2 or -5:30"
+            }
+            Text::Hug => "{author} hugs {target}",
+            Text::Pat => "{author} pats {target} on the head",
+            Text::Highfive => "{author} high fives {target}",
+            Text::GameAdded => "Added {game} to /play",
+            Text::GameExists => "This server already has a game called {game}",
+            Text::GameRemoved => "Removed {game} from /play",
+            Text::GameNotFound => "This server has no game called {game}",
+            Text::GamesEmpty => "This server has not added any games",
+            Text::InvalidGameName => "The name of a game has 1 to {max} characters",
+            Text::InvalidSessionTime => "\"{time}\" is not a time, try something like 20:30 or 8pm",
+            Text::SessionStarts => "Starts {time}",
+            Text::SessionJoining => "Joining",
+            Text::SessionDeclined => "Declined",
+            Text::SessionNobody => "Nobody yet",
+            Text::SessionJoin => "Join",
+            Text::SessionDecline => "Decline",
+            Text::SessionStarting => "{mentions} {game} is starting!",
+            Text::SessionStartingAnyGame => "{mentions} the game is starting!",
+            Text::SleepStats => {
+                "{user} said good night on {total} nights, {current} in a row now and {longest} at most"
+            }
+            Text::SleepStatsEmpty => "{user} has not said good night in this server yet",
+            Text::SleepLeaderboard => "Most consistent sleepers of the last {days} days",
+            Text::SleepLeaderboardEntry => "{rank}. {user}: {nights} nights",
+            Text::GifStyleLink => "links",
+            Text::GifStyleEmbed => "embeds",
+            Text::GifStyleChanged => "GIF commands now post their GIF as {style}.",
+            Text::GifAttribution => "Via {provider}",
+            Text::HurryImpatient => "{mention}! Come on, hurry up already!",
+            Text::HurryFurious => "{mention}! HURRY UP!",
+            Text::HurryEscalation => "{mention}, we are still waiting for you!",
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn dutch(self) -> &'static str {
+        match self {
+            Text::InternalError => "Interne fout",
+            Text::NoGifs => "Geen gifs gevonden",
+            Text::RestrictedQuery => "De zoekopdracht \"{query}\" is niet toegestaan",
+            Text::AnimeUnavailable => "De anime api is niet ingesteld",
+            Text::AnimeNotFound => "Geen downloads gevonden voor \"{title}\"",
+            Text::AnimeConnected => "Verbonden met de anime api",
+            Text::AnimeConnecting => "Bezig met verbinden met de anime api",
+            Text::AnimeDisconnected => "Niet verbonden met de anime api",
+            Text::Hurry => "{mention}! Schiet op!",
+            Text::PlayGame => "{mention}! Laten we {game} spelen!",
+            Text::PlayAnyGame => "{mention}! Laten we een spelletje spelen!",
+            Text::CacheUnused => "De gif cache is nog niet gebruikt",
+            Text::CacheRemoved => "\"{key}\" is uit de cache verwijderd",
+            Text::CacheRemovedPrefix => {
+                "{count} zoekopdrachten die beginnen met \"{key}\" zijn uit de cache verwijderd"
+            }
+            Text::CacheNotCached => "\"{key}\" stond niet in de cache",
+            Text::LanguageChanged => "De bot antwoordt nu in het Nederlands in deze server",
+            Text::LanguageReset => "De bot antwoordt nu in de taal van elke gebruiker",
+            Text::CommandDisabled => "Dit commando is uitgeschakeld in deze server",
+            Text::CommandEnabledInGuild => "/{command} kan weer gebruikt worden in deze server",
+            Text::CommandDisabledInGuild => {
+                "/{command} kan niet meer gebruikt worden in deze server"
+            }
+            Text::UnknownCommand => "Er is geen commando met de naam \"{command}\"",
+            Text::Cooldown => "Rustig aan! Probeer het over {seconds}s opnieuw",
+            Text::ConfigReloaded => "De configuratie is opnieuw ingeladen",
+            Text::ConfigReloadFailed => {
+                "Het opnieuw inladen van de configuratie is mislukt: {error}"
+            }
+            Text::UsageEmpty => "Er zijn de afgelopen {days} dagen geen commando's gebruikt",
+            Text::GifReactSent => "Gereageerd met een gif",
+            Text::CacheAdded => "{count} gifs voor \"{key}\" in de cache gezet",
+            Text::CacheInvalidUrl => "\"{url}\" is geen geldige url",
+            Text::GuildWelcome => {
+                "Bedankt! Gebruik /help om te zien wat ik kan en /settings om mij in te stellen"
+            }
+            Text::MissingPermission => "Alleen {level} kunnen dit commando gebruiken",
+            Text::LevelEveryone => "iedereen",
+            Text::LevelManager => "botbeheerders",
+            Text::LevelAdmin => "serverbeheerders",
+            Text::LevelOwner => "de eigenaren van de bot",
+            Text::ManagerRoleAdded => "{role} kan de bot nu beheren",
+            Text::ManagerRoleRemoved => "{role} kan de bot niet meer beheren",
+            Text::PermissionChanged => "/{command} kan nu gebruikt worden door {level}",
+            Text::PermissionReset => "/{command} gebruikt weer de standaard rechten",
+            Text::ModerationChanged => "De moderatie van deze server is nu {level}",
+            Text::ModerationReset => "Deze server gebruikt weer de standaard moderatie",
+            Text::ModerationStrict => "streng",
+            Text::ModerationStandard => "standaard",
+            Text::ModerationLenient => "mild",
+            Text::DiceRolled => "{user} gooit {dice}",
+            Text::InvalidDice => {
+                "\"{dice}\" zijn geen dobbelstenen, probeer 2d20+3, 4d6kh3 of d20 adv"
+            }
+            Text::PollInvalidOptions => "Een poll heeft 2 tot 10 opties nodig, gescheiden door ;",
+            Text::PollNotAllowed => "Deze poll bevat tekst die niet is toegestaan in deze server",
+            Text::PollCloses => "Sluit {time}",
+            Text::PollClosed => "Deze poll is gesloten",
+            Text::PollVotes => "{votes} stemmen",
+            Text::CustomGifAdded => "/{command} kan nu {value} plaatsen",
+            Text::CustomGifExists => "/{command} plaatst {value} al",
+            Text::CustomGifRemoved => "Eigen GIF {id} is verwijderd",
+            Text::CustomGifNotFound => "Er is geen eigen GIF {id}",
+            Text::CustomGifsEmpty => "Deze server heeft geen eigen GIFs",
+            Text::GifBlocked => "De bot plaatst {value} niet meer in deze server",
+            Text::GifAlreadyBlocked => "{value} is al geblokkeerd",
+            Text::GifUnblocked => "{id} is niet meer geblokkeerd",
+            Text::BlockedGifNotFound => "Er is geen geblokkeerde GIF {id}",
+            Text::BlocklistEmpty => "Deze server heeft geen GIFs geblokkeerd",
+            Text::UnknownSeason => "Er is geen seizoen \"{season}\"",
+            Text::SeasonsFollowCalendar => "Slaap en goedemorgen GIFs volgen weer de kalender",
+            Text::SeasonsDisabled => {
+                "Slaap en goedemorgen GIFs veranderen niet meer met de seizoenen"
+            }
+            Text::SeasonChosen => "Slaap en goedemorgen GIFs komen nu altijd uit {season}",
+            Text::NoveltyChanged => "Een op de {ratio} slaap GIFs is nu een verrassing",
+            Text::NoveltyDisabled => "Slaap GIFs hebben geen verrassingen meer",
+            Text::NoveltyReset => "Slaap GIFs hebben weer het standaard aantal verrassingen",
+            Text::TimezoneSet => "Je tijdzone is nu {offset}",
+            Text::TimezoneReset => "Je tijdzone is verwijderd",
+            Text::InvalidTimezone => {
+                "\"{offset}\" is geen verschil met UTC, probeer iets als +2 of -5:30"
+            }
+            Text::Hug => "{author} knuffelt {target}",
+            Text::Pat => "{author} aait {target} over het hoofd",
+            Text::Highfive => "{author} geeft {target} een high five",
+            Text::GameAdded => "{game} is toegevoegd aan /play",
+            Text::GameExists => "Deze server heeft al een spel met de naam {game}",
+            Text::GameRemoved => "{game} is verwijderd uit /play",
+            Text::GameNotFound => "Deze server heeft geen spel met de naam {game}",
+            Text::GamesEmpty => "Deze server heeft geen spellen toegevoegd",
+            Text::InvalidGameName => "De naam van een spel heeft 1 tot {max} tekens",
+            Text::InvalidSessionTime => "\"{time}\" is geen tijd, probeer iets als 20:30 of 8pm",
+            Text::SessionStarts => "Begint {time}",
+            Text::SessionJoining => "Doen mee",
+            Text::SessionDeclined => "Doen niet mee",
+            Text::SessionNobody => "Nog niemand",
+            Text::SessionJoin => "Meedoen",
+            Text::SessionDecline => "Afzeggen",
+            Text::SessionStarting => "{mentions} {game} begint!",
+            Text::SessionStartingAnyGame => "{mentions} het spel begint!",
+            Text::SleepStats => {
+                "{user} zei {total} nachten welterusten, nu {current} op een rij en hoogstens {longest}"
+            }
+            Text::SleepStatsEmpty => "{user} heeft in deze server nog geen welterusten gezegd",
+            Text::SleepLeaderboard => "Trouwste slapers van de afgelopen {days} dagen",
+            Text::SleepLeaderboardEntry => "{rank}. {user}: {nights} nachten",
+            Text::GifStyleLink => "links",
+            Text::GifStyleEmbed => "embeds",
+            Text::GifStyleChanged => "GIF-commando's plaatsen hun GIF nu als {style}.",
+            Text::GifAttribution => "Via {provider}",
+            Text::HurryImpatient => "{mention}! Kom op, schiet nou eens op!",
+            Text::HurryFurious => "{mention}! SCHIET OP!",
+            Text::HurryEscalation => "{mention}, we wachten nog steeds op je!",
+        }
+    }
+
+    /// The text in `locale`.
+    pub(crate) fn get(self, locale: Locale) -> &'static str {
+        match locale {
+            Locale::English => self.english(),
+            Locale::Dutch => self.dutch(),
+        }
+    }
+
+    /// The text in `locale` with every `{name}` placeholder replaced by its value in `args`.
+    pub(crate) fn format(self, locale: Locale, args: &[(&str, &str)]) -> String {
+        args.iter()
+            .fold(self.get(locale).to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{name}}}"), value)
+            })
+    }
+}
+
+/// The language of the replies to a command.
+///
+/// The language configured for the guild takes precedence over the language of the user's
+/// discord client, and English is used when neither is available.
+pub(crate) async fn locale(ctx: Context<'_, '_>) -> Locale {
+    if let Some(guild_id) = ctx.guild_id() {
+        match ctx.database().get_guild_settings(guild_id.into()).await {
+            Ok(settings) => {
+                if let Some(locale) = settings.locale.as_deref().and_then(Locale::from_code) {
+                    return locale;
+                }
+            }
+            Err(err) => warn!("Failed to get the settings of guild {guild_id}: {err}"),
+        }
+    }
+    ctx.locale()
+        .and_then(Locale::from_code)
+        .unwrap_or(Locale::English)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut placeholders: Vec<_> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect();
+        placeholders.sort_unstable();
+        placeholders
+    }
+
+    #[test]
+    fn parses_discord_locales() {
+        assert_eq!(Locale::from_code("en-US"), Some(Locale::English));
+        assert_eq!(Locale::from_code("en-GB"), Some(Locale::English));
+        assert_eq!(Locale::from_code("nl"), Some(Locale::Dutch));
+        assert_eq!(Locale::from_code("de"), None);
+    }
+
+    #[test]
+    fn code_round_trips() {
+        for locale in [Locale::English, Locale::Dutch] {
+            assert_eq!(Locale::from_code(locale.code()), Some(locale));
+        }
+    }
+
+    #[test]
+    fn translations_use_the_same_placeholders() {
+        for &text in Text::ALL {
+            assert_eq!(
+                placeholders(text.english()),
+                placeholders(text.dutch()),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn format_replaces_placeholders() {
+        let text = Text::PlayGame.format(Locale::Dutch, &[("mention", "@here"), ("game", "Halo")]);
+        assert_eq!(text, "@here! Laten we Halo spelen!");
+    }
+}
@@ -0,0 +1,57 @@
+use otaku::db::{GuildSettingsConnection, Pool};
+use poise::serenity_prelude as serenity;
+use serenity::{CreateMessage, FullEvent, Guild, GuildId};
+use tracing::info;
+
+use crate::commands::CommandError;
+use crate::i18n::{Locale, Text};
+
+/// Prepare a guild the bot joined and schedule the removal of the data of a guild it left.
+///
+/// # Errors
+///
+/// Will return an error when the guild settings cannot be stored or the setup message cannot
+/// be sent.
+pub(crate) async fn handle_event(
+    ctx: &serenity::Context,
+    event: &FullEvent,
+    database: &Pool,
+) -> Result<(), CommandError> {
+    match event {
+        FullEvent::GuildCreate {
+            guild,
+            is_new: Some(true),
+        } => guild_joined(ctx, guild, database).await,
+        // An unavailable guild is part of an outage, the bot is still a member of it.
+        FullEvent::GuildDelete { incomplete, .. } if !incomplete.unavailable => {
+            guild_left(incomplete.id, database).await
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn guild_joined(
+    ctx: &serenity::Context,
+    guild: &Guild,
+    database: &Pool,
+) -> Result<(), CommandError> {
+    info!("Joined guild {}", guild.id);
+    let guild_id = guild.id.into();
+    otaku::db::cancel_guild_cleanup(database, guild_id).await?;
+    // Store the defaults, unless the guild kept its settings from an earlier stay.
+    let settings = database.get_guild_settings(guild_id).await?;
+    database.set_guild_settings(&settings).await?;
+
+    if let Some(channel_id) = guild.system_channel_id {
+        let locale = Locale::from_code(&guild.preferred_locale).unwrap_or(Locale::English);
+        let message = CreateMessage::new().content(Text::GuildWelcome.get(locale));
+        channel_id.send_message(ctx, message).await?;
+    }
+    Ok(())
+}
+
+async fn guild_left(guild_id: GuildId, database: &Pool) -> Result<(), CommandError> {
+    info!("Left guild {guild_id}");
+    otaku::db::schedule_guild_cleanup(database, guild_id.into()).await?;
+    Ok(())
+}
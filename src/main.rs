@@ -1,66 +1,266 @@
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
-use crate::background_tasks::{start_anime_subscription, start_cache_trim, start_gif_updater};
+use crate::background_tasks::{
+    schedule_database_jobs, schedule_gif_jobs, start_anime_subscription, start_error_reporter,
+    start_presence_rotation, warm_gif_cache,
+};
+use crate::commands::anime::AnimeError;
 use crate::commands::gifs::GifError;
 use crate::commands::CommandError;
-use consts::BASE_GIF_CONFIG;
-use dotenv::dotenv;
+use crate::components::Components;
+use crate::config::{Features, RuntimeConfig};
+use crate::gif_provider::Providers;
+use crate::health::HealthState;
+use crate::i18n::Text;
+use crate::scheduler::Scheduler;
+use chrono::Utc;
+use consts::{
+    ANIME_BACKFILL_WINDOW, BASE_GIF_CONFIG, BASE_GIPHY_CONFIG, GIF_CACHE_MAX_ENTRIES,
+    GIF_CACHE_MAX_URLS, GIF_CACHE_SNAPSHOT, SHUTDOWN_TIMEOUT,
+};
+use dotenvy::dotenv;
+use otaku::db::Pool;
+use otaku::{Api, ChannelConfig, ConnectionState, GrpcSource, TlsConfig};
 use poise::CreateReply;
-use serenity::all::GatewayIntents;
+use serenity::all::{GatewayIntents, ShardManager};
 use serenity::client::Client;
-use tracing::error;
-use tracing_subscriber::prelude::*;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 use url::Url;
 
 mod background_tasks;
 mod cache;
 mod commands;
+mod components;
+mod config;
 mod consts;
 mod context;
+mod error_report;
+mod gif_provider;
+mod health;
+mod i18n;
+mod lifecycle;
+mod metrics;
+mod moderation;
+mod permissions;
+mod presence;
+mod scheduler;
+mod shutdown;
+mod telemetry;
 
 #[derive(Debug, Clone)]
 struct SpiderBot<'tenor_config> {
     gif_cache: cache::Memory<[Url]>,
-    tenor: tenor::Client<'tenor_config>,
+    gif_providers: Providers<'tenor_config>,
+    database: Pool,
+    /// Secret key of the user hashes stored with the command usage, the usage is not recorded
+    /// without it.
+    command_usage_key: Option<String>,
+    config: config::SharedConfig,
+    features: Features,
+    components: Arc<Components>,
+    anime_api: Option<Api>,
+    anime_state: watch::Receiver<ConnectionState>,
+    started_at: Instant,
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let _ = dotenv();
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    let (error_report_layer, error_reports) = error_report::from_env()?.unzip();
+    let tracer_provider = telemetry::init(error_report_layer)?;
 
     let discord_token = env::var("DISCORD_TOKEN")?.leak();
-    let anime_url = match resolve_env("ANIME_URL") {
-        Ok(anime_url) => Some(anime_url.leak()),
-        Err(error) => {
-            error!("Failed to resolve ANIME_URL: {error}");
-            None
-        }
+    let features = Features::from_env()?;
+    let anime_apis = if features.anime {
+        configured_anime_apis()?
+    } else {
+        Vec::new()
     };
     let tenor_token = env::var("TENOR_TOKEN")?;
+    let giphy_token = env::var("GIPHY_TOKEN").ok();
+    let command_usage_key = env::var("COMMAND_USAGE_KEY").ok();
+    if command_usage_key.is_none() {
+        warn!("COMMAND_USAGE_KEY is not set, command usage is not recorded");
+    }
+    let config = Arc::new(watch::Sender::new(RuntimeConfig::from_env()?));
+
+    let pool_config = otaku::db::PoolConfig::from_env()?;
+    let pool = otaku::db::connect_with_backoff(env!("CARGO_PKG_NAME"), pool_config).await?;
+    otaku::db::migrate(&pool).await?;
+    let shutdown = CancellationToken::new();
+
+    let (anime_state_tx, anime_state) = watch::channel(ConnectionState::Disconnected);
+    let anime_state_rx = anime_state.clone();
+
+    let gif_cache_path = env::var("GIF_CACHE_PATH")
+        .map_or_else(|_| PathBuf::from(GIF_CACHE_SNAPSHOT), PathBuf::from);
 
     // Login with a bot token from the environment
     let bot = SpiderBot {
-        gif_cache: cache::Memory::new(),
-        tenor: tenor::Client::with_config(tenor_token, Some(BASE_GIF_CONFIG)),
+        gif_cache: gif_cache(&gif_cache_path).await?,
+        gif_providers: gif_providers(tenor_token, giphy_token),
+        database: pool.clone(),
+        command_usage_key,
+        config: config.clone(),
+        features,
+        components: Arc::new(components()),
+        anime_api: anime_apis.first().cloned(),
+        anime_state,
+        started_at: Instant::now(),
     };
 
-    start_gif_updater(bot.tenor.clone(), bot.gif_cache.clone())?;
-    start_cache_trim(bot.gif_cache.clone());
+    let mut scheduler = Scheduler::new(pool.clone());
+    if bot.features.gifs {
+        // Before the commands are registered, so they do not run into an empty cache.
+        let cold_cache = warm_gif_cache(
+            bot.gif_providers.clone(),
+            bot.gif_cache.clone(),
+            config.clone(),
+        )
+        .await;
+        schedule_gif_jobs(
+            &mut scheduler,
+            bot.gif_providers.clone(),
+            bot.gif_cache.clone(),
+            config.clone(),
+            cold_cache,
+        );
+    }
+    #[cfg(unix)]
+    background_tasks::start_config_reload(config.clone(), shutdown.clone())?;
 
+    let gif_cache = bot.gif_cache.clone();
     let intents = GatewayIntents::non_privileged();
 
-    let framework = poise::Framework::builder()
+    let mut client = Client::builder(discord_token, intents)
+        .framework(framework(bot))
+        .await?;
+
+    schedule_database_jobs(&mut scheduler, pool.clone(), client.http.clone());
+    scheduler.start(&shutdown);
+
+    if let Some(error_reports) = error_reports {
+        start_error_reporter(client.http.clone(), error_reports, shutdown.clone());
+    }
+
+    let anime_state = (!anime_apis.is_empty()).then_some(anime_state_rx);
+    serve_metrics(&client, &pool, gif_cache.clone(), anime_state).await?;
+
+    let (latest_release_tx, latest_release) = watch::channel(None);
+    start_presence_rotation(
+        client.shard_manager.clone(),
+        client.cache.clone(),
+        config,
+        latest_release,
+        shutdown.clone(),
+    );
+
+    let announcements = start_announcements(
+        anime_apis,
+        &pool,
+        &client,
+        anime_state_tx,
+        latest_release_tx,
+        &shutdown,
+    )?;
+
+    shutdown_on_ctrl_c(client.shard_manager.clone(), shutdown);
+
+    // start listening for events by starting a single shard
+    client.start().await?;
+
+    if let Some(announcements) = announcements {
+        if timeout(SHUTDOWN_TIMEOUT, announcements).await.is_err() {
+            warn!("Stopping before all announcements were sent");
+        }
+    }
+    if let Err(err) = gif_cache.save(&gif_cache_path).await {
+        error!("Failed to save gif cache snapshot: {err}");
+    }
+    pool.close().await;
+    if let Some(tracer_provider) = tracer_provider {
+        if let Err(err) = tracer_provider.shutdown() {
+            error!("Failed to flush traces: {err}");
+        }
+    }
+
+    Ok(())
+}
+
+/// The handlers of the buttons and select menus of the commands.
+fn components() -> Components {
+    Components::default()
+        .with_handler(
+            commands::help::COMPONENT_NAMESPACE,
+            commands::help::handle_component,
+        )
+        .with_handler(
+            commands::poll::COMPONENT_NAMESPACE,
+            commands::poll::handle_component,
+        )
+        .with_handler(
+            commands::sessions::COMPONENT_NAMESPACE,
+            commands::sessions::handle_component,
+        )
+}
+
+/// The commands of the enabled `features`.
+fn enabled_commands(features: Features) -> Vec<context::Command<'static>> {
+    let mut commands = vec![
+        commands::admin::admin(),
+        commands::cache::cache(),
+        commands::config::config(),
+        commands::dice::roll(),
+        commands::help::help(),
+        commands::poll::poll(),
+        commands::settings::settings(),
+        commands::status::status(),
+        commands::timezone::timezone(),
+    ];
+    if features.anime {
+        commands.push(commands::anime::anime());
+    }
+    if features.gifs {
+        commands.extend([
+            commands::gifs::hurry(),
+            commands::gifs::morbin(),
+            commands::gifs::play(),
+            commands::gifs::sleep(),
+            commands::sleep_stats::sleepstats(),
+            commands::gifs::goodmorning(),
+            commands::gifs::gif_react(),
+            commands::gifconfig::gifconfig(),
+            commands::games::games(),
+            commands::gifs::hurry_user(),
+            commands::gifs::play_user(),
+        ]);
+        commands.extend(commands::gifs::reactions());
+    }
+    commands
+}
+
+/// The poise framework with the commands of the enabled features, which registers them once the
+/// bot is ready.
+fn framework(bot: SpiderBot<'static>) -> poise::Framework<SpiderBot<'static>, CommandError> {
+    poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![
-                commands::gifs::hurry(),
-                commands::gifs::morbin(),
-                commands::gifs::play(),
-                commands::gifs::sleep(),
-            ],
+            commands: enabled_commands(bot.features),
+            event_handler: |ctx, event, _framework, data| {
+                Box::pin(async move {
+                    components::handle_event(ctx, event, &data.components).await?;
+                    lifecycle::handle_event(ctx, event, &data.database).await
+                })
+            },
+            command_check: Some(|ctx| Box::pin(command_check(ctx))),
+            pre_command: |ctx| Box::pin(metrics::pre_command(ctx)),
+            post_command: |ctx| Box::pin(metrics::post_command(ctx)),
             on_error: |error| {
                 Box::pin(async move {
                     if let Err(e) = on_error(error).await {
@@ -76,31 +276,118 @@ async fn main() -> anyhow::Result<()> {
                 Ok(bot)
             })
         })
-        .build();
-
-    let mut client = Client::builder(discord_token, intents)
-        .framework(framework)
-        .await?;
+        .build()
+}
 
-    if let Some(anime_url) = anime_url {
-        let pool = otaku::db::connect(env!("CARGO_PKG_NAME")).await?;
-        otaku::db::migrate(&pool).await?;
-        start_anime_subscription(pool, anime_url, client.cache.clone(), client.http.clone());
+/// Serve the metrics and the health of the bot on `METRICS_ADDR`, when it is set.
+async fn serve_metrics(
+    client: &Client,
+    pool: &Pool,
+    gif_cache: cache::Memory<[Url]>,
+    anime_state: Option<watch::Receiver<ConnectionState>>,
+) -> anyhow::Result<()> {
+    if let Ok(address) = env::var("METRICS_ADDR") {
+        let health = HealthState {
+            shard_manager: client.shard_manager.clone(),
+            pool: pool.clone(),
+            anime_state,
+        };
+        metrics::start_metrics_server(address.parse()?, gif_cache, health).await?;
     }
+    Ok(())
+}
 
-    let shard_manager = client.shard_manager.clone();
+/// Announce the releases of the anime apis to their subscribers, when any api is configured.
+///
+/// Returns the task that sends the announcements until `shutdown` is cancelled.
+fn start_announcements(
+    anime_apis: Vec<Api>,
+    pool: &Pool,
+    client: &Client,
+    anime_state: watch::Sender<ConnectionState>,
+    latest_release: watch::Sender<Option<String>>,
+    shutdown: &CancellationToken,
+) -> anyhow::Result<Option<JoinHandle<()>>> {
+    if anime_apis.is_empty() {
+        return Ok(None);
+    }
+    let source = GrpcSource::new(
+        anime_apis,
+        otaku::Config::from_env()?,
+        pool.clone(),
+        anime_state,
+        shutdown.clone(),
+    )
+    .with_backfill(Utc::now() - ANIME_BACKFILL_WINDOW);
+    Ok(Some(start_anime_subscription(
+        source,
+        pool.clone(),
+        client.cache.clone(),
+        client.http.clone(),
+        latest_release,
+        shutdown.clone(),
+    )))
+}
 
+/// Cancel `shutdown` on ctrl+c and stop the shards once the running commands have finished.
+fn shutdown_on_ctrl_c(shard_manager: Arc<ShardManager>, shutdown: CancellationToken) {
     tokio::spawn(async move {
         tokio::signal::ctrl_c()
             .await
             .expect("Could not register ctrl+c handler");
+        info!("Shutting down");
+        shutdown.cancel();
+        let running = shutdown::wait_for_commands(SHUTDOWN_TIMEOUT).await;
+        if running > 0 {
+            warn!("Stopping with {running} commands still running");
+        }
         shard_manager.shutdown_all().await;
     });
+}
 
-    // start listening for events by starting a single shard
-    client.start().await?;
+/// The gif cache, shared through redis when `REDIS_URL` is set and filled with the snapshot at
+/// `path`.
+async fn gif_cache(path: &Path) -> anyhow::Result<cache::Memory<[Url]>> {
+    let gif_cache = cache::Memory::with_limits(gif_cache_limits()?, <[Url]>::len);
+    #[cfg(feature = "redis")]
+    let gif_cache = match env::var("REDIS_URL") {
+        Ok(url) => gif_cache.with_remote(cache::RedisCache::connect(&url, "gif-cache").await?),
+        Err(_) => gif_cache,
+    };
+    match gif_cache.load(path).await {
+        Ok(count) => info!("Loaded {count} cached gif collections"),
+        Err(err) => warn!("Failed to load gif cache snapshot: {err}"),
+    }
+    Ok(gif_cache)
+}
 
-    Ok(())
+fn gif_cache_limits() -> anyhow::Result<cache::Limits> {
+    let max_entries = match env::var("GIF_CACHE_MAX_ENTRIES") {
+        Ok(value) => value.parse()?,
+        Err(_) => GIF_CACHE_MAX_ENTRIES,
+    };
+    let max_urls = match env::var("GIF_CACHE_MAX_URLS") {
+        Ok(value) => value.parse()?,
+        Err(_) => GIF_CACHE_MAX_URLS,
+    };
+    Ok(cache::Limits {
+        max_entries: Some(max_entries),
+        max_weight: Some(max_urls),
+    })
+}
+
+/// Search tenor first and giphy when it fails or finds nothing, when a giphy token is set.
+fn gif_providers(tenor_token: String, giphy_token: Option<String>) -> Providers<'static> {
+    let providers = Providers::new(tenor::Client::with_config(
+        tenor_token,
+        Some(BASE_GIF_CONFIG),
+    ));
+    match giphy_token {
+        Some(token) => {
+            providers.with_fallback(giphy::Client::with_config(token, BASE_GIPHY_CONFIG))
+        }
+        None => providers,
+    }
 }
 
 fn resolve_env(key: &str) -> anyhow::Result<String> {
@@ -113,24 +400,124 @@ fn resolve_env(key: &str) -> anyhow::Result<String> {
     Ok(envmnt::expand(&key, Some(options)))
 }
 
+/// The anime apis in `ANIME_URL`, none when it cannot be resolved.
+fn configured_anime_apis() -> anyhow::Result<Vec<Api>> {
+    match resolve_env("ANIME_URL") {
+        Ok(anime_urls) => anime_apis(&*anime_urls.leak()),
+        Err(error) => {
+            error!("Failed to resolve ANIME_URL: {error}");
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Parse a comma separated list of anime api urls, the primary api first and then its mirrors.
+fn anime_apis(urls: &'static str) -> anyhow::Result<Vec<Api>> {
+    let tls = TlsConfig::from_env()?;
+    let channel = ChannelConfig::from_env()?;
+    let token = env::var("ANIME_TOKEN").ok();
+    urls.split(',')
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(|url| -> anyhow::Result<Api> {
+            let api = Api::new(url)
+                .with_tls(tls.clone())
+                .with_channel_config(channel);
+            match &token {
+                Some(token) => Ok(api.with_token(token)?),
+                None => Ok(api),
+            }
+        })
+        .collect()
+}
+
+/// Check whether the command is enabled in the guild and whether its author may use it.
+async fn command_check(ctx: context::Context<'_, '_>) -> Result<bool, CommandError> {
+    Ok(commands::settings::command_check(ctx).await? && permissions::command_check(ctx).await?)
+}
+
 async fn on_error(
     error: poise::FrameworkError<'_, SpiderBot<'_>, CommandError>,
 ) -> Result<(), serenity::Error> {
     match error {
         poise::FrameworkError::Command { ctx, error, .. } => {
-            let error_message = match error {
-                CommandError::GifError(GifError::NoGifs | GifError::RestrictedQuery(_)) => {
-                    error.to_string()
+            metrics::record_command_error(ctx).await;
+            let locale = i18n::locale(ctx).await;
+            let error_message = match &error {
+                CommandError::GifError(GifError::NoGifs) => Text::NoGifs.get(locale).to_string(),
+                CommandError::GifError(GifError::RestrictedQuery(query)) => {
+                    Text::RestrictedQuery.format(locale, &[("query", query.as_str())])
+                }
+                CommandError::AnimeError(AnimeError::Unavailable) => {
+                    Text::AnimeUnavailable.get(locale).to_string()
+                }
+                CommandError::AnimeError(AnimeError::NotFound(title)) => {
+                    Text::AnimeNotFound.format(locale, &[("title", title.as_str())])
+                }
+                _ => {
+                    error!(
+                        command = ctx.command().qualified_name,
+                        user = %ctx.author().id,
+                        guild = ?ctx.guild_id(),
+                        "An error occurred in a command: {error}"
+                    );
+                    Text::InternalError.get(locale).to_string()
                 }
-                _ => "Internal error".to_string(),
             };
-            eprintln!("An error occurred in a command: {error}");
             let msg = CreateReply::default()
                 .ephemeral(true)
                 .content(error_message);
             ctx.send(msg).await?;
             Ok(())
         }
+        poise::FrameworkError::CommandCheckFailed {
+            ctx, error: None, ..
+        } => {
+            let locale = i18n::locale(ctx).await;
+            let msg = CreateReply::default()
+                .ephemeral(true)
+                .content(Text::CommandDisabled.get(locale));
+            ctx.send(msg).await?;
+            Ok(())
+        }
+        poise::FrameworkError::CommandCheckFailed {
+            ctx,
+            error: Some(CommandError::MissingPermission(level)),
+            ..
+        } => {
+            let locale = i18n::locale(ctx).await;
+            let level = permissions::level_text(level).get(locale);
+            let msg = CreateReply::default()
+                .ephemeral(true)
+                .content(Text::MissingPermission.format(locale, &[("level", level)]));
+            ctx.send(msg).await?;
+            Ok(())
+        }
+        poise::FrameworkError::CooldownHit {
+            ctx,
+            remaining_cooldown,
+            ..
+        } => {
+            let locale = i18n::locale(ctx).await;
+            let seconds = remaining_cooldown.as_millis().div_ceil(1000).to_string();
+            let msg = CreateReply::default()
+                .ephemeral(true)
+                .content(Text::Cooldown.format(locale, &[("seconds", seconds.as_str())]));
+            ctx.send(msg).await?;
+            Ok(())
+        }
+        error @ poise::FrameworkError::CommandPanic { .. } => {
+            if let poise::FrameworkError::CommandPanic { ctx, payload, .. } = &error {
+                error!(
+                    command = ctx.command().qualified_name,
+                    user = %ctx.author().id,
+                    guild = ?ctx.guild_id(),
+                    "A command panicked: {}",
+                    payload.as_deref().unwrap_or("unknown payload")
+                );
+            }
+            poise::builtins::on_error(error).await
+        }
         error => poise::builtins::on_error(error).await,
     }
 }
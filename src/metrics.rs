@@ -0,0 +1,247 @@
+use std::net::SocketAddr;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{
+    Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+use url::Url;
+
+use crate::cache;
+use crate::context::{Context, DatabaseExt};
+use crate::health::{self, HealthState};
+use crate::shutdown::{self, InFlightCommand};
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+static COMMAND_INVOCATIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("command_invocations_total", "Invoked commands"),
+        &["command"],
+    ))
+});
+static COMMAND_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("command_errors_total", "Commands that returned an error"),
+        &["command"],
+    ))
+});
+static COMMAND_LATENCY: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "command_duration_seconds",
+            "Time between the start and the end of a command",
+        ),
+        &["command"],
+    ))
+});
+static JOB_RUNS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("scheduled_job_runs_total", "Runs of the scheduled jobs"),
+        &["job", "outcome"],
+    ))
+});
+static JOB_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(HistogramVec::new(
+        prometheus::HistogramOpts::new(
+            "scheduled_job_duration_seconds",
+            "Time between the start and the end of a scheduled job",
+        ),
+        &["job"],
+    ))
+});
+static GIF_SEARCHES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("gif_searches_total", "Searches on the gif providers"),
+        &["provider", "outcome"],
+    ))
+});
+static CACHE_HITS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(IntGaugeVec::new(
+        Opts::new("gif_cache_hits", "Lookups of a cached gif query"),
+        &["key"],
+    ))
+});
+static CACHE_MISSES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(IntGaugeVec::new(
+        Opts::new(
+            "gif_cache_misses",
+            "Lookups of a missing or expired gif query",
+        ),
+        &["key"],
+    ))
+});
+static ANIME_RECONNECTS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(IntGauge::new(
+        "anime_reconnects",
+        "Times the anime api stream was opened again",
+    ))
+});
+static ANIME_MESSAGES: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(IntGaugeVec::new(
+        Opts::new("anime_messages", "Messages received from the anime api"),
+        &["outcome"],
+    ))
+});
+
+fn register<T>(metric: prometheus::Result<T>) -> T
+where
+    T: prometheus::core::Collector + Clone + 'static,
+{
+    let metric = metric.expect("metric options are valid");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("metric is registered once");
+    metric
+}
+
+/// Start of a command, stored as invocation data by [`pre_command`].
+///
+/// The invocation data lives until poise is done with the invocation, including its error
+/// handling, so it also keeps the command counted as running.
+struct CommandStart {
+    at: Instant,
+    _in_flight: InFlightCommand,
+}
+
+pub(crate) async fn pre_command(ctx: Context<'_, '_>) {
+    let name = ctx.command().qualified_name.as_str();
+    COMMAND_INVOCATIONS.with_label_values(&[name]).inc();
+    let start = CommandStart {
+        at: Instant::now(),
+        _in_flight: shutdown::command_started(),
+    };
+    ctx.set_invocation_data(start).await;
+}
+
+pub(crate) async fn post_command(ctx: Context<'_, '_>) {
+    let name = ctx.command().qualified_name.as_str();
+    let latency = command_latency(ctx).await;
+    COMMAND_LATENCY
+        .with_label_values(&[name])
+        .observe(latency.as_secs_f64());
+    record_command_usage(ctx, latency, true).await;
+}
+
+pub(crate) async fn record_command_error(ctx: Context<'_, '_>) {
+    let name = ctx.command().qualified_name.as_str();
+    COMMAND_ERRORS.with_label_values(&[name]).inc();
+    let latency = command_latency(ctx).await;
+    record_command_usage(ctx, latency, false).await;
+}
+
+async fn command_latency(ctx: Context<'_, '_>) -> Duration {
+    ctx.invocation_data::<CommandStart>()
+        .await
+        .map_or(Duration::ZERO, |start| start.at.elapsed())
+}
+
+/// Store the invocation in the database, so unused commands can be found with `/admin usage`.
+///
+/// Nothing is stored when `COMMAND_USAGE_KEY` is not set.
+async fn record_command_usage(ctx: Context<'_, '_>, latency: Duration, success: bool) {
+    let Some(key) = &ctx.data().command_usage_key else {
+        return;
+    };
+    let name = ctx.command().qualified_name.as_str();
+    let result = otaku::db::command_usage::record_command_usage(
+        ctx.database(),
+        key.as_bytes(),
+        name,
+        ctx.guild_id().map(otaku::GuildId::from),
+        ctx.author().id.into(),
+        latency,
+        success,
+    )
+    .await;
+    if let Err(err) = result {
+        warn!("Failed to record usage of {name}: {err}");
+    }
+}
+
+pub(crate) fn record_job_run(job: &str, duration: Duration, success: bool) {
+    let outcome = if success { "success" } else { "failure" };
+    JOB_RUNS.with_label_values(&[job, outcome]).inc();
+    JOB_DURATION
+        .with_label_values(&[job])
+        .observe(duration.as_secs_f64());
+}
+
+/// Count a search on a gif provider, `outcome` is `found`, `empty` or `error`.
+pub(crate) fn record_gif_search(provider: &str, outcome: &str) {
+    GIF_SEARCHES.with_label_values(&[provider, outcome]).inc();
+}
+
+/// Copy the counters that are kept by other subsystems into the registry.
+fn collect(gif_cache: &cache::Memory<[Url]>) {
+    CACHE_HITS.reset();
+    CACHE_MISSES.reset();
+    for (key, stats) in gif_cache.stats() {
+        CACHE_HITS
+            .with_label_values(&[&key])
+            .set(i64::try_from(stats.hits).unwrap_or(i64::MAX));
+        CACHE_MISSES
+            .with_label_values(&[&key])
+            .set(i64::try_from(stats.misses).unwrap_or(i64::MAX));
+    }
+
+    let stream = otaku::stream_stats();
+    ANIME_RECONNECTS.set(i64::try_from(stream.reconnects).unwrap_or(i64::MAX));
+    for (outcome, count) in [
+        ("received", stream.messages_received),
+        ("filtered", stream.messages_filtered),
+        ("conversion_error", stream.conversion_errors),
+    ] {
+        ANIME_MESSAGES
+            .with_label_values(&[outcome])
+            .set(i64::try_from(count).unwrap_or(i64::MAX));
+    }
+}
+
+async fn metrics(State(gif_cache): State<cache::Memory<[Url]>>) -> impl IntoResponse {
+    collect(&gif_cache);
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+    if let Err(err) = encoder.encode(&REGISTRY.gather(), &mut body) {
+        error!("Failed to encode metrics: {err}");
+    }
+    ([(CONTENT_TYPE, encoder.format_type().to_string())], body)
+}
+
+/// Serve the metrics in the prometheus text format on `/metrics`, together with the health
+/// checks of [`health::router`].
+///
+/// ### Arguments
+///
+/// - `address` - the address to listen on
+/// - `gif_cache` - the cache of GIFs
+/// - `health` - the dependencies checked for readiness
+///
+/// # Errors
+///
+/// Will return an error when the address cannot be bound.
+pub(crate) async fn start_metrics_server(
+    address: SocketAddr,
+    gif_cache: cache::Memory<[Url]>,
+    health: HealthState,
+) -> anyhow::Result<()> {
+    let router = Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(gif_cache)
+        .merge(health::router(health));
+    let listener = TcpListener::bind(address).await?;
+    info!("Serving metrics on {address}");
+    tokio::spawn(async move {
+        if let Err(err) = axum::serve(listener, router).await {
+            error!("Metrics server stopped: {err}");
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use otaku::db::guild_settings::ModerationLevel;
+use otaku::db::GuildSettingsConnection;
+use rustrict::{CensorStr, Type};
+use tracing::warn;
+
+use crate::context::{Context, DatabaseExt};
+
+/// The level outside guilds and in guilds that have not chosen one.
+pub(crate) const DEFAULT_LEVEL: ModerationLevel = ModerationLevel::Standard;
+
+/// The kinds of text that are refused at `level`.
+fn threshold(level: ModerationLevel) -> Type {
+    match level {
+        ModerationLevel::Strict => {
+            Type::INAPPROPRIATE
+                | (Type::PROFANE | Type::OFFENSIVE | Type::SEXUAL | Type::MEAN)
+                    & Type::MILD_OR_HIGHER
+        }
+        ModerationLevel::Standard => Type::INAPPROPRIATE,
+        ModerationLevel::Lenient => Type::INAPPROPRIATE & Type::SEVERE,
+    }
+}
+
+/// Whether the bot may use `text` from a member, such as a search query or the name of a game.
+pub(crate) fn is_allowed(text: &str, level: ModerationLevel) -> bool {
+    !text.is(threshold(level))
+}
+
+/// The moderation level of the guild `ctx` is used in.
+pub(crate) async fn level(ctx: Context<'_, '_>) -> ModerationLevel {
+    let Some(guild_id) = ctx.guild_id() else {
+        return DEFAULT_LEVEL;
+    };
+    match ctx.database().get_guild_settings(guild_id.into()).await {
+        Ok(settings) => settings.moderation.unwrap_or(DEFAULT_LEVEL),
+        Err(err) => {
+            warn!("Failed to get the settings of guild {guild_id}: {err}");
+            DEFAULT_LEVEL
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn clean_text_is_allowed_at_every_level() {
+        for level in [
+            ModerationLevel::Strict,
+            ModerationLevel::Standard,
+            ModerationLevel::Lenient,
+        ] {
+            assert!(is_allowed("lethal company", level));
+        }
+    }
+
+    #[test]
+    fn stricter_levels_refuse_more() {
+        let texts = ["damn", "crap", "shut up", "fuck you", "hello"];
+        for text in texts {
+            if is_allowed(text, ModerationLevel::Strict) {
+                assert!(is_allowed(text, ModerationLevel::Standard));
+            }
+            if is_allowed(text, ModerationLevel::Standard) {
+                assert!(is_allowed(text, ModerationLevel::Lenient));
+            }
+        }
+    }
+}
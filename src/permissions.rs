@@ -0,0 +1,97 @@
+use otaku::db::guild_settings::{GuildSettings, PermissionLevel};
+use otaku::db::GuildSettingsConnection;
+use serenity::all::Permissions;
+
+use crate::commands::CommandError;
+use crate::context::{Command, Context, DatabaseExt};
+use crate::i18n::Text;
+
+/// The level `command` requires when the guild has not chosen one.
+///
+/// Commands that discord hides from members by default are meant for the admins of a guild.
+fn default_level(command: &Command) -> PermissionLevel {
+    if command.owners_only {
+        PermissionLevel::Owner
+    } else if !command.default_member_permissions.is_empty() {
+        PermissionLevel::Admin
+    } else {
+        PermissionLevel::Everyone
+    }
+}
+
+/// The level `command` requires in a guild with `settings`, the commands of the owners can not
+/// be opened up by a guild.
+pub(crate) fn required_level(
+    command: &Command,
+    settings: Option<&GuildSettings>,
+) -> PermissionLevel {
+    if command.owners_only {
+        return PermissionLevel::Owner;
+    }
+    settings
+        .and_then(|settings| settings.command_permissions.get(&command.name))
+        .copied()
+        .unwrap_or_else(|| default_level(command))
+}
+
+/// The highest level of the author of the command in a guild with `settings`.
+pub(crate) async fn author_level(
+    ctx: Context<'_, '_>,
+    settings: Option<&GuildSettings>,
+) -> PermissionLevel {
+    if ctx.framework().options().owners.contains(&ctx.author().id) {
+        return PermissionLevel::Owner;
+    }
+    let Some(member) = ctx.author_member().await else {
+        return PermissionLevel::Everyone;
+    };
+    if member.permissions.is_some_and(Permissions::manage_guild) {
+        return PermissionLevel::Admin;
+    }
+    let is_manager = settings.is_some_and(|settings| {
+        member
+            .roles
+            .iter()
+            .any(|&role| settings.manager_roles.contains(&role.into()))
+    });
+    if is_manager {
+        PermissionLevel::Manager
+    } else {
+        PermissionLevel::Everyone
+    }
+}
+
+/// The name of `level` in the replies.
+pub(crate) fn level_text(level: PermissionLevel) -> Text {
+    match level {
+        PermissionLevel::Everyone => Text::LevelEveryone,
+        PermissionLevel::Manager => Text::LevelManager,
+        PermissionLevel::Admin => Text::LevelAdmin,
+        PermissionLevel::Owner => Text::LevelOwner,
+    }
+}
+
+/// Check for every command whether its author has the level the command requires. Subcommands
+/// require the level of their top level command.
+///
+/// # Errors
+///
+/// Will return [`CommandError::MissingPermission`] when the author may not use the command, or
+/// an error when the guild settings cannot be read.
+pub(crate) async fn command_check(ctx: Context<'_, '_>) -> Result<bool, CommandError> {
+    let command = ctx
+        .parent_commands()
+        .first()
+        .copied()
+        .unwrap_or_else(|| ctx.command());
+    let settings = match ctx.guild_id() {
+        Some(guild_id) => Some(ctx.database().get_guild_settings(guild_id.into()).await?),
+        None => None,
+    };
+    let required = required_level(command, settings.as_ref());
+    if author_level(ctx, settings.as_ref()).await >= required {
+        Ok(true)
+    } else {
+        Err(CommandError::MissingPermission(required))
+    }
+}
@@ -0,0 +1,60 @@
+use chrono::{Datelike, NaiveDate};
+use serenity::all::ActivityData;
+
+/// The activities the bot cycles through on `today`.
+///
+/// ### Arguments
+///
+/// - `guilds` - the number of guilds the bot is in
+/// - `latest_release` - the title of the last announced anime release
+/// - `today` - selects the seasonal activity
+pub(crate) fn activities(
+    guilds: usize,
+    latest_release: Option<&str>,
+    today: NaiveDate,
+) -> Vec<ActivityData> {
+    let mut activities = vec![
+        ActivityData::watching(format!("{guilds} servers")),
+        ActivityData::listening("/help"),
+    ];
+    if let Some(title) = latest_release {
+        activities.push(ActivityData::watching(format!("new episode: {title}")));
+    }
+    if let Some(activity) = seasonal_activity(today) {
+        activities.push(ActivityData::playing(activity));
+    }
+    activities
+}
+
+fn seasonal_activity(today: NaiveDate) -> Option<&'static str> {
+    match (today.month(), today.day()) {
+        (10, 15..=31) => Some("trick or treat"),
+        (12, 24..=26) => Some("in the snow"),
+        (12, 31) | (1, 1) => Some("with fireworks"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).expect("valid date")
+    }
+
+    #[test]
+    fn seasons_cover_their_dates() {
+        assert_eq!(seasonal_activity(date(10, 31)), Some("trick or treat"));
+        assert_eq!(seasonal_activity(date(12, 25)), Some("in the snow"));
+        assert_eq!(seasonal_activity(date(1, 1)), Some("with fireworks"));
+        assert_eq!(seasonal_activity(date(6, 1)), None);
+    }
+
+    #[test]
+    fn latest_release_is_shown() {
+        let activities = activities(3, Some("Frieren 12"), date(6, 1));
+        let names: Vec<_> = activities.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["3 servers", "/help", "new episode: Frieren 12"]);
+    }
+}
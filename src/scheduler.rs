@@ -0,0 +1,174 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::future::BoxFuture;
+use otaku::db::Pool;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::metrics;
+
+/// When a job runs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Schedule {
+    /// At every multiple of the period since the unix epoch, like the cron schedule `0 */6 * * *`
+    /// for a period of 6 hours.
+    Aligned(Duration),
+    /// The period after the end of the previous run.
+    Every(Duration),
+}
+
+impl Schedule {
+    /// The first run of the schedule after `after`.
+    pub(crate) fn next_after(self, after: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Schedule::Aligned(period) => {
+                let period = i64::try_from(period.as_secs()).unwrap_or(i64::MAX).max(1);
+                let next = (after.timestamp().div_euclid(period) + 1).saturating_mul(period);
+                DateTime::from_timestamp(next, 0).unwrap_or(DateTime::<Utc>::MAX_UTC)
+            }
+            Schedule::Every(period) => TimeDelta::from_std(period)
+                .ok()
+                .and_then(|period| after.checked_add_signed(period))
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+        }
+    }
+}
+
+type Run = Box<dyn Fn() -> BoxFuture<'static, anyhow::Result<()>> + Send + Sync>;
+
+struct Job {
+    name: &'static str,
+    schedule: Schedule,
+    run: Run,
+    /// Run at start, before the stored next run.
+    run_at_start: bool,
+}
+
+/// Runs named jobs on their schedule. The next run of every job is stored in the database, so a
+/// restart neither skips nor repeats a run. A job that has never run starts right away.
+///
+/// Every run is a separate task, so a job that fails or panics does not stop the other jobs or
+/// its own next run.
+pub(crate) struct Scheduler {
+    pool: Pool,
+    jobs: Vec<Job>,
+}
+
+impl Scheduler {
+    pub(crate) fn new(pool: Pool) -> Self {
+        Self {
+            pool,
+            jobs: Vec::new(),
+        }
+    }
+
+    /// Run `run` on `schedule`, `name` identifies the job in the database, logs and metrics.
+    pub(crate) fn add<F, Fut>(&mut self, name: &'static str, schedule: Schedule, run: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.jobs.push(Job {
+            name,
+            schedule,
+            run: Box::new(move || Box::pin(run())),
+            run_at_start: false,
+        });
+    }
+
+    /// Run the job `name` right away when the scheduler starts, instead of at its stored next run.
+    pub(crate) fn run_at_start(&mut self, name: &str) {
+        for job in self.jobs.iter_mut().filter(|job| job.name == name) {
+            job.run_at_start = true;
+        }
+    }
+
+    /// Launch every job.
+    ///
+    /// ### Arguments
+    ///
+    /// - `shutdown` - stops the jobs when cancelled, a running job is finished first
+    pub(crate) fn start(self, shutdown: &CancellationToken) {
+        for job in self.jobs {
+            tokio::spawn(run_job(self.pool.clone(), job, shutdown.clone()));
+        }
+    }
+}
+
+async fn run_job(pool: Pool, job: Job, shutdown: CancellationToken) {
+    let next_run = if job.run_at_start {
+        Ok(None)
+    } else {
+        otaku::db::scheduled_jobs::get_next_run(&pool, job.name).await
+    };
+    let mut next_run = match next_run {
+        Ok(next_run) => next_run.unwrap_or_else(Utc::now),
+        Err(err) => {
+            warn!(
+                job = job.name,
+                "Failed to get the next run, running now: {err}"
+            );
+            Utc::now()
+        }
+    };
+    loop {
+        let wait = (next_run - Utc::now()).to_std().unwrap_or_default();
+        tokio::select! {
+            () = shutdown.cancelled() => break,
+            () = tokio::time::sleep(wait) => {}
+        }
+
+        let start = Instant::now();
+        let success = match tokio::spawn((job.run)()).await {
+            Ok(Ok(())) => true,
+            Ok(Err(err)) => {
+                error!(job = job.name, "Job failed: {err}");
+                false
+            }
+            Err(err) => {
+                error!(job = job.name, "Job panicked: {err}");
+                false
+            }
+        };
+        let duration = start.elapsed();
+        metrics::record_job_run(job.name, duration, success);
+
+        next_run = job.schedule.next_after(Utc::now());
+        info!(job = job.name, ?duration, %next_run, "Job finished");
+        if let Err(err) = otaku::db::scheduled_jobs::set_next_run(&pool, job.name, next_run).await {
+            warn!(job = job.name, "Failed to store the next run: {err}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn time(timestamp: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(timestamp, 0).expect("valid timestamp")
+    }
+
+    #[test]
+    fn aligned_runs_at_multiples_of_the_period() {
+        let schedule = Schedule::Aligned(6 * HOUR);
+        assert_eq!(schedule.next_after(time(3600)), time(6 * 3600));
+        assert_eq!(schedule.next_after(time(6 * 3600)), time(12 * 3600));
+    }
+
+    #[test]
+    fn every_runs_after_the_period() {
+        let schedule = Schedule::Every(HOUR);
+        assert_eq!(schedule.next_after(time(1234)), time(1234 + 3600));
+    }
+
+    #[test]
+    fn overflowing_schedule_never_runs() {
+        let schedule = Schedule::Every(Duration::MAX);
+        assert_eq!(schedule.next_after(time(0)), DateTime::<Utc>::MAX_UTC);
+    }
+}
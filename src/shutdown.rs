@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use tokio::time::{sleep, Instant};
+
+/// Number of commands that started but have not finished yet.
+static IN_FLIGHT_COMMANDS: AtomicUsize = AtomicUsize::new(0);
+
+/// Marks a command as running until it is dropped, so the command is also counted as finished
+/// when poise reports an error without calling `post_command`.
+#[must_use]
+pub(crate) struct InFlightCommand(());
+
+impl Drop for InFlightCommand {
+    fn drop(&mut self) {
+        IN_FLIGHT_COMMANDS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn command_started() -> InFlightCommand {
+    IN_FLIGHT_COMMANDS.fetch_add(1, Ordering::Relaxed);
+    InFlightCommand(())
+}
+
+/// Wait until no commands are running, or `timeout` has passed.
+///
+/// Returns the number of commands that are still running.
+pub(crate) async fn wait_for_commands(timeout: Duration) -> usize {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let in_flight = IN_FLIGHT_COMMANDS.load(Ordering::Relaxed);
+        if in_flight == 0 || Instant::now() >= deadline {
+            return in_flight;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn command_finishes_when_dropped() {
+        let command = command_started();
+        assert_eq!(wait_for_commands(Duration::ZERO).await, 1);
+        drop(command);
+        assert_eq!(wait_for_commands(Duration::ZERO).await, 0);
+    }
+}
@@ -0,0 +1,50 @@
+use std::env;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::trace::{Config, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use tracing_subscriber::prelude::*;
+
+use crate::error_report::ErrorReportLayer;
+
+/// Install the tracing subscriber, which logs to stdout and, when
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is set, exports the spans to that OTLP collector. Errors are
+/// also passed to `error_reports` when it is given.
+///
+/// Returns the provider of the exported spans, which must be shut down to flush them.
+///
+/// # Errors
+///
+/// Will return an error when the exporter cannot be created.
+pub(crate) fn init(
+    error_reports: Option<ErrorReportLayer>,
+) -> anyhow::Result<Option<TracerProvider>> {
+    let provider = match env::var("OTEL_EXPORTER_OTLP_ENDPOINT") {
+        Ok(endpoint) => {
+            let exporter = opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint);
+            let resource = Resource::new([KeyValue::new("service.name", env!("CARGO_PKG_NAME"))]);
+            let provider = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(exporter)
+                .with_trace_config(Config::default().with_resource(resource))
+                .install_batch(runtime::Tokio)?;
+            Some(provider)
+        }
+        Err(_) => None,
+    };
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(env!("CARGO_PKG_NAME")))
+    });
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::from_default_env())
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .with(error_reports)
+        .init();
+    Ok(provider)
+}